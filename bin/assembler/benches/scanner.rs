@@ -0,0 +1,36 @@
+use assembler::{lexer::Lexer, scanner::Scanner};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+/// How many copies of snake.asm to assemble at once, which puts it at the size of a large data file
+const COPIES: usize = 100;
+
+fn scaled_source() -> String {
+    include_str!("../../../resources/test/snake.asm").repeat(COPIES)
+}
+
+fn parse_lines(source: &str) -> usize {
+    let mut scanner = Scanner::new(source);
+    let mut count = 0;
+    while !scanner.is_done() {
+        if scanner.next_line().is_err() {
+            scanner.recover();
+        }
+        count += 1;
+    }
+
+    count
+}
+
+fn scanner_benchmark(c: &mut Criterion) {
+    let source = scaled_source();
+
+    let mut group = c.benchmark_group("snake.asm x100");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.sample_size(10);
+    group.bench_function("tokenize", |b| b.iter(|| Lexer::new(black_box(&source), 0).count()));
+    group.bench_function("parse lines", |b| b.iter(|| parse_lines(black_box(&source))));
+    group.finish();
+}
+
+criterion_group!(benches, scanner_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,517 @@
+use std::collections::{HashMap, HashSet};
+
+use forge_lib::{
+    address::{AddressMode, AddressModeGeneric},
+    cpu::{Cpu, CpuState},
+    directive::{ByteArgs, Directive, FarAddrArgs, WordArgs},
+    expression::ExpressionNode,
+    label::{anonymous_label_index, anonymous_reference_name, local_label_name},
+    line::{Labels, Line, MainComponent},
+    mnemonic::{find_opcode, Mnemonic},
+    operand::Operand,
+    span::Span,
+};
+
+use crate::{process::LocationCounters, warning::Warning};
+
+/// Looks over the parsed lines for code that assembles but is probably not what was meant.
+///
+/// This has to run before the expressions are resolved, since that replaces the names in the
+/// operands with the addresses they point to
+pub fn check_lines(lines: &[Line], cpu: Cpu) -> Vec<(Warning, Span)> {
+    let mut warnings = Vec::new();
+    let locations = locate_lines(lines, cpu);
+    let symbols = collect_symbols(lines, &locations, &mut warnings);
+    let references = collect_references(lines);
+
+    let mut state = CpuState::new(cpu);
+    let mut scope = String::new();
+    let mut anonymous_defined: u16 = 0;
+    // The instruction that control never comes back from, and whether the code after it has been reported
+    let mut dead_after: Option<Mnemonic> = None;
+    let mut dead_reported = false;
+
+    for (line, line_locations) in lines.iter().zip(&locations) {
+        match &line.label {
+            Some(Labels::Label(name)) => scope = name.clone(),
+            Some(Labels::Anonymous) => anonymous_defined += 1,
+            _ => {}
+        }
+
+        // Anything with a label can be jumped to
+        if line.label.is_some() {
+            dead_after = None;
+            dead_reported = false;
+        }
+
+        for (main_component, location) in line.main_components.iter().zip(line_locations) {
+            let instruction = match main_component {
+                MainComponent::Instruction(instruction) => Some(instruction),
+                // A new proc or origin starts a new block of code
+                MainComponent::Directive(Directive::ORG(_) | Directive::PROC(_) | Directive::SCOPE(_) | Directive::SEGMENT(_), _) => {
+                    dead_after = None;
+                    dead_reported = false;
+                    None
+                }
+                MainComponent::Directive(..) => None,
+            };
+
+            if let Some(instruction) = instruction {
+                let mnemonic = instruction.mnemonic;
+
+                if let Some(after) = dead_after.filter(|_| !dead_reported) {
+                    warnings.push((Warning::UnreachableCode { after }, instruction.span));
+                    dead_reported = true;
+                }
+                if dead_after.is_none() && ends_flow(mnemonic) {
+                    dead_after = Some(mnemonic);
+                }
+
+                match &instruction.operand {
+                    Some(operand) if is_branch(mnemonic, state.cpu) => {
+                        let target = match operand {
+                            Operand::AddressMode(AddressMode::ZeroPageOrAbsoluteIdent(name)) => {
+                                symbols.labels.get(name).map(|label| (label.0, name.clone()))
+                            }
+                            Operand::LocalLabel(name) => {
+                                symbols.labels.get(&local_label_name(&scope, name)).map(|label| (label.0, format!("@{}", name)))
+                            }
+                            Operand::AnonymousLabel(offset) => {
+                                let total = symbols.anonymous.len() as u16;
+                                anonymous_label_index(anonymous_defined, total, *offset)
+                                    .map(|index| (symbols.anonymous[index as usize], anonymous_reference_name(*offset)))
+                            }
+                            _ => None,
+                        };
+
+                        // The extra cycle is spent when the branch lands on a different page to the next
+                        // instruction. Pages are only known where `.org` gave the address, since
+                        // otherwise the linker decides where the segment goes
+                        let next = location.address.map(|address| address.wrapping_add(2));
+                        if let (Some(next), Some((target_location, target))) = (next, target) {
+                            let crosses = target_location.address.is_some_and(|address| next & 0xFF00 != address & 0xFF00);
+                            if crosses {
+                                warnings.push((Warning::PageCrossingBranch { mnemonic, target }, instruction.span));
+                            }
+                        }
+                    }
+                    // Absolute addressing forced with a: or .a is what was asked for
+                    Some(Operand::AddressMode(address_mode)) if instruction.address_size.is_none() => {
+                        let zero_page = match address_mode {
+                            AddressMode::Absolute(value) => Some((*value, AddressModeGeneric::ZeroPage)),
+                            AddressMode::AbsoluteX(value) => Some((*value, AddressModeGeneric::ZeroPageX)),
+                            AddressMode::AbsoluteY(value) => Some((*value, AddressModeGeneric::ZeroPageY)),
+                            _ => None,
+                        };
+
+                        if let Some((value, mode)) = zero_page.filter(|(value, _)| *value <= 0xFF) {
+                            if has_address_mode(mnemonic, mode, state.cpu) {
+                                warnings.push((Warning::ZeroPageAbsolute { mnemonic, address: value }, instruction.span));
+                            }
+                        }
+                    }
+                    None if has_address_mode(mnemonic, AddressModeGeneric::Accumulator, state.cpu) => {
+                        warnings.push((Warning::ImplicitAccumulator { mnemonic }, instruction.span));
+                    }
+                    _ => {}
+                }
+            }
+
+            state.update(main_component);
+        }
+    }
+
+    warnings.extend(symbols.unused(&references));
+
+    warnings
+}
+
+/// Every symbol defined in the lines. Labels are keyed by name, except local labels which are keyed
+/// by the global label they follow, since the same local name can be reused under each one
+#[derive(Default)]
+struct Symbols {
+    /// The location, display name and span of each label
+    labels: HashMap<String, (Location, String, Span)>,
+    constants: HashMap<String, Span>,
+    procs: HashMap<String, Span>,
+    /// The location of each anonymous label, in the order they are defined
+    anonymous: Vec<Location>,
+}
+
+/// Where a statement is assembled, as the index of its segment and the offset from the start of it
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Location {
+    segment: usize,
+    offset: u16,
+    /// The final address, if `.org` said where the segment goes. Otherwise it is up to the linker
+    address: Option<u16>,
+}
+
+/// Works out where each statement on each line goes the same way the lines are assembled, with the
+/// location after the last statement on a line at the end. Labels are at the first location of their line
+fn locate_lines(lines: &[Line], cpu: Cpu) -> Vec<Vec<Location>> {
+    let mut counters = LocationCounters::new();
+    let mut state = CpuState::new(cpu);
+    // The address each segment given one with `.org` would start at
+    let mut origins: HashMap<usize, u16> = HashMap::new();
+    let location = |counters: &LocationCounters, origins: &HashMap<usize, u16>| {
+        let (segment, offset) = (counters.current(), counters.offset());
+        Location { segment, offset, address: origins.get(&segment).map(|origin| origin.wrapping_add(offset)) }
+    };
+
+    lines
+        .iter()
+        .map(|line| {
+            let mut locations = vec![location(&counters, &origins)];
+            for main_component in &line.main_components {
+                match main_component {
+                    MainComponent::Directive(Directive::SEGMENT(name), _) => counters.switch(name),
+                    MainComponent::Directive(Directive::ORG(address), _) => {
+                        origins.insert(counters.current(), address.wrapping_sub(counters.offset()));
+                    }
+                    MainComponent::Directive(directive, _) => counters.advance(directive.size() as u16),
+                    MainComponent::Instruction(instruction) => counters.advance(instruction.size(&state) as u16),
+                }
+                state.update(main_component);
+                locations.push(location(&counters, &origins));
+            }
+            locations
+        })
+        .collect()
+}
+
+impl Symbols {
+    /// Returns a warning for each symbol that is never referenced, in the order they were defined.
+    /// Labels used as vectors (e.g. `.word reset`) count as references
+    fn unused(self, references: &HashSet<String>) -> Vec<(Warning, Span)> {
+        let labels = self.labels.into_iter().filter(|(key, _)| !references.contains(key))
+            .map(|(_, (_, name, span))| (Warning::UnusedLabel { name }, span));
+        let constants = self.constants.into_iter().filter(|(name, _)| !references.contains(name))
+            .map(|(name, span)| (Warning::UnusedConstant { name }, span));
+        let procs = self.procs.into_iter().filter(|(name, _)| !references.contains(name))
+            .map(|(name, span)| (Warning::UnusedProc { name }, span));
+
+        let mut unused: Vec<_> = labels.chain(constants).chain(procs).collect();
+        unused.sort_by_key(|(_, span)| (span.line, span.column));
+        unused
+    }
+}
+
+/// Finds the address of every label along with the constants and procs. Constants that redefine
+/// an earlier constant or a label are reported along the way
+fn collect_symbols(lines: &[Line], locations: &[Vec<Location>], warnings: &mut Vec<(Warning, Span)>) -> Symbols {
+    let mut symbols = Symbols::default();
+    let mut scope = String::new();
+
+    for (line, line_locations) in lines.iter().zip(locations) {
+        let location = line_locations[0];
+        if let Some((name, _)) = &line.constant {
+            let span = Span { length: name.chars().count() as u32, ..line.span };
+            let first = symbols.constants.get(name).copied().or_else(|| symbols.labels.get(name).map(|(_, _, span)| *span));
+            if let Some(first) = first {
+                warnings.push((Warning::ShadowedSymbol { name: name.clone(), first }, span));
+            }
+            symbols.constants.entry(name.clone()).or_insert(span);
+        }
+
+        if let (Some(label), Some(span)) = (&line.label, line.label_span()) {
+            let named = match label {
+                Labels::Label(name) => {
+                    scope = name.clone();
+                    if let Some(first) = symbols.constants.get(name) {
+                        warnings.push((Warning::ShadowedSymbol { name: name.clone(), first: *first }, span));
+                    }
+                    Some((name.clone(), name.clone()))
+                }
+                Labels::LocalLabel(name) => Some((local_label_name(&scope, name), format!("@{}", name))),
+                // Anonymous labels have no name to report as unused, so they are only kept for their address
+                Labels::Anonymous => {
+                    symbols.anonymous.push(location);
+                    None
+                }
+            };
+
+            // Duplicate global labels are already an error, so only the first is kept
+            if let Some((key, name)) = named {
+                symbols.labels.entry(key).or_insert((location, name, span));
+            }
+        }
+
+        for main_component in &line.main_components {
+            if let MainComponent::Directive(Directive::PROC(name), span) = main_component {
+                symbols.procs.entry(name.clone()).or_insert(*span);
+            }
+        }
+    }
+
+    symbols
+}
+
+/// Finds every name the lines refer to, using the same keys as collect_symbols
+fn collect_references(lines: &[Line]) -> HashSet<String> {
+    let mut references = HashSet::new();
+    let mut scope = String::new();
+
+    for line in lines {
+        if let Some(Labels::Label(name)) = &line.label {
+            scope = name.clone();
+        }
+
+        for main_component in &line.main_components {
+            match main_component {
+                MainComponent::Instruction(instruction) => match &instruction.operand {
+                    Some(Operand::Expression(expression)) => expression_references(expression, &mut references),
+                    Some(Operand::LocalLabel(name)) => {
+                        references.insert(local_label_name(&scope, name));
+                    }
+                    Some(Operand::AnonymousLabel(_)) => {}
+                    Some(Operand::AddressMode(address_mode)) => address_mode_references(address_mode, &mut references),
+                    None => {}
+                },
+                MainComponent::Directive(Directive::BYTE(args), _) => {
+                    for arg in args {
+                        match arg {
+                            ByteArgs::Identifier(name) => {
+                                references.insert(name.clone());
+                            }
+                            ByteArgs::Expression(expression) => expression_references(expression, &mut references),
+                            ByteArgs::Value(_) => {}
+                        }
+                    }
+                }
+                MainComponent::Directive(Directive::WORD(args) | Directive::ADDR(args), _) => {
+                    for arg in args {
+                        match arg {
+                            WordArgs::Identifier(name) => {
+                                references.insert(name.clone());
+                            }
+                            WordArgs::Expression(expression) => expression_references(expression, &mut references),
+                            WordArgs::Value(_) => {}
+                        }
+                    }
+                }
+                // Another file may use an exported symbol
+                MainComponent::Directive(Directive::EXPORT { names, .. } | Directive::GLOBAL(names), _) => {
+                    references.extend(names.iter().cloned());
+                }
+                MainComponent::Directive(Directive::FARADDR(args), _) => {
+                    for arg in args {
+                        match arg {
+                            FarAddrArgs::Identifier(name) => {
+                                references.insert(name.clone());
+                            }
+                            FarAddrArgs::Expression(expression) => expression_references(expression, &mut references),
+                            FarAddrArgs::Value(_) => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    references
+}
+
+fn address_mode_references(address_mode: &AddressMode, references: &mut HashSet<String>) {
+    match address_mode {
+        AddressMode::ZeroPageOrAbsoluteIdent(name)
+        | AddressMode::ZeroPageOrAbsoluteXIdent(name)
+        | AddressMode::ZeroPageOrAbsoluteYIdent(name)
+        | AddressMode::ImmediateIdent(name)
+        | AddressMode::IndexedIndirectXIdent(name)
+        | AddressMode::IndirectIndexYIdent(name)
+        | AddressMode::IndirectIdent(name) => {
+            references.insert(name.clone());
+        }
+        AddressMode::ZeroPageOrAbsoluteScopedRef(path)
+        | AddressMode::ZeroPageOrAbsoluteXScopedRef(path)
+        | AddressMode::ZeroPageOrAbsoluteYScopedRef(path)
+        | AddressMode::ImmediateScopedRef(path)
+        | AddressMode::IndexedIndirectXScopedRef(path)
+        | AddressMode::IndirectIndexYScopedRef(path)
+        | AddressMode::IndirectScopedRef(path) => {
+            references.extend(path.iter().cloned());
+        }
+        AddressMode::ImmediateExpression(expression) => expression_references(expression, references),
+        AddressMode::ZeroPageRelative(zero_page, target) => {
+            expression_references(zero_page, references);
+            expression_references(target, references);
+        }
+        _ => {}
+    }
+}
+
+fn expression_references(expression: &ExpressionNode, references: &mut HashSet<String>) {
+    match expression {
+        ExpressionNode::BinOp(_, left, right, _) => {
+            expression_references(left, references);
+            expression_references(right, references);
+        }
+        ExpressionNode::Identifier(name, _) => {
+            references.insert(name.clone());
+        }
+        ExpressionNode::Parenthesized(inner, _) | ExpressionNode::Unary(_, inner, _) => expression_references(inner, references),
+        ExpressionNode::ScopedReference(path, _) => references.extend(path.iter().cloned()),
+        ExpressionNode::Number(..) => {}
+    }
+}
+
+/// Returns true for the instructions that never carry on to the next one
+fn ends_flow(mnemonic: Mnemonic) -> bool {
+    matches!(
+        mnemonic,
+        Mnemonic::JMP | Mnemonic::JML | Mnemonic::RTS | Mnemonic::RTL | Mnemonic::RTI | Mnemonic::BRA | Mnemonic::BRL
+    )
+}
+
+fn is_branch(mnemonic: Mnemonic, cpu: Cpu) -> bool {
+    has_address_mode(mnemonic, AddressModeGeneric::Relative, cpu)
+}
+
+/// Returns true if the mnemonic has an opcode for exactly this address mode, not one of the
+/// alternatives find_opcode falls back on
+fn has_address_mode(mnemonic: Mnemonic, address_mode: AddressModeGeneric, cpu: Cpu) -> bool {
+    find_opcode(mnemonic, address_mode, cpu).is_ok_and(|opcode| opcode.address_mode == address_mode)
+}
+
+#[cfg(test)]
+mod analysis_tests {
+    use forge_lib::cpu::Cpu;
+
+    use crate::{process::scan, warning::Warning};
+
+    use super::check_lines;
+
+    fn check(source: &str) -> Vec<Warning> {
+        check_lines(&scan(source), Cpu::Mos6502).into_iter().map(|(warning, _)| warning).collect()
+    }
+
+    /// Puts the source at $80FC. The origin is given after some code, so the offset in the segment
+    /// is on a different part of its page to the address
+    fn near_page_end(source: &str) -> String {
+        format!("  NOP\n  NOP\n  .org $80F8\n  .byte 0, 0, 0, 0\n{}", source)
+    }
+
+    #[test]
+    fn test_unused_label() {
+        let warnings = check("start:\n  JMP loop\nloop:\n  NOP\n@skip:\n  RTS\n");
+        assert_eq!(warnings, vec![
+            Warning::UnusedLabel { name: String::from("start") },
+            Warning::UnusedLabel { name: String::from("@skip") },
+        ]);
+    }
+
+    #[test]
+    fn test_labels_used_by_data_are_used() {
+        let warnings = check("reset:\n  NOP\n  .word reset\n");
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_exported_labels_are_used() {
+        let warnings = check(".export reset
+.global irq
+reset:
+  NOP
+irq:
+  RTI
+");
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_zero_page_absolute() {
+        let warnings = check("  LDA $0010\n  LDA $0100\n  JMP $0010\n");
+        assert_eq!(warnings, vec![Warning::ZeroPageAbsolute { mnemonic: forge_lib::mnemonic::Mnemonic::LDA, address: 0x10 }]);
+    }
+
+    #[test]
+    fn test_forced_absolute_is_not_reported() {
+        let warnings = check("  LDA a:$10\n  LDA.a $0010,X\n");
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_page_crossing_branch() {
+        let warnings = check(&near_page_end("loop:\n  NOP\n  BNE loop\n  BEQ loop\n"));
+        assert_eq!(warnings, vec![Warning::PageCrossingBranch {
+            mnemonic: forge_lib::mnemonic::Mnemonic::BEQ,
+            target: String::from("loop"),
+        }]);
+    }
+
+    #[test]
+    fn test_branch_addresses_are_per_segment() {
+        // The data is in its own segment, so the loop starts at the beginning of CODE
+        let data = "  .byte 0, 0\n".repeat(127);
+        let warnings = check(&format!(".org $8000\n.rodata\n{}.code\nloop:\n  DEX\n  BNE loop\n  RTS\n", data));
+        assert!(warnings.is_empty());
+
+        // Going back to a segment carries on from where it was left
+        let warnings = check(&near_page_end(".rodata\n  .byte 0\n.code\nloop:\n  NOP\n  BNE loop\n  BEQ loop\n"));
+        assert_eq!(warnings, vec![Warning::PageCrossingBranch {
+            mnemonic: forge_lib::mnemonic::Mnemonic::BEQ,
+            target: String::from("loop"),
+        }]);
+    }
+
+    #[test]
+    fn test_branches_without_an_origin_are_not_checked() {
+        // The linker decides where the segment goes, so the offset says nothing about the page
+        let data = "  .byte 0, 0, 0, 0\n".repeat(0xFC / 4);
+        let warnings = check(&format!("{}loop:\n  NOP\n  BNE loop\n  BEQ loop\n", data));
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_anonymous_labels() {
+        // Anonymous labels are never reported as unused, and branches to them are checked like any other
+        let warnings = check(&near_page_end(":\n  NOP\n  BNE :-\n  BEQ :-\n  BEQ :+\n:\n  RTS\n"));
+        assert_eq!(warnings, vec![Warning::PageCrossingBranch {
+            mnemonic: forge_lib::mnemonic::Mnemonic::BEQ,
+            target: String::from(":-"),
+        }]);
+    }
+
+    #[test]
+    fn test_statements_on_one_line() {
+        // The label binds to the first statement after it, and each statement on a line has its own address
+        let warnings = check(&near_page_end("loop:\n  ; wait\n  NOP : NOP : NOP : BNE loop\n  RTS : NOP\n"));
+        assert_eq!(warnings, vec![
+            Warning::PageCrossingBranch { mnemonic: forge_lib::mnemonic::Mnemonic::BNE, target: String::from("loop") },
+            Warning::UnreachableCode { after: forge_lib::mnemonic::Mnemonic::RTS },
+        ]);
+    }
+
+    #[test]
+    fn test_implicit_accumulator() {
+        let warnings = check("  ASL\n  ASL A\n  NOP\n");
+        assert_eq!(warnings, vec![Warning::ImplicitAccumulator { mnemonic: forge_lib::mnemonic::Mnemonic::ASL }]);
+    }
+
+    #[test]
+    fn test_shadowed_symbol() {
+        let warnings = check("WIDTH = 10\nWIDTH = 12\n  LDA #WIDTH\n");
+        assert!(matches!(&warnings[..], [Warning::ShadowedSymbol { name, first }] if name == "WIDTH" && first.line == 1));
+    }
+
+    #[test]
+    fn test_unused_constant_and_proc() {
+        let warnings = check("SPEED = 2\nUNUSED = 3\n  .proc Player\n  LDA #SPEED\n  JSR Enemy\n  .endproc\n  .proc Enemy\n  RTS\n  .endproc\n");
+        assert_eq!(warnings, vec![
+            Warning::UnusedConstant { name: String::from("UNUSED") },
+            Warning::UnusedProc { name: String::from("Player") },
+        ]);
+    }
+
+    #[test]
+    fn test_unreachable_code() {
+        let warnings = check("main:\n  JMP main\n  NOP\n  NOP\n  .word main\nnext:\n  RTS\n  LDX #$01\n");
+        assert_eq!(warnings, vec![
+            Warning::UnreachableCode { after: forge_lib::mnemonic::Mnemonic::JMP },
+            Warning::UnreachableCode { after: forge_lib::mnemonic::Mnemonic::RTS },
+            Warning::UnusedLabel { name: String::from("next") },
+        ]);
+    }
+}
@@ -0,0 +1,451 @@
+use std::collections::HashMap;
+
+use forge_lib::{
+    address::{AddressMode, AddressModeGeneric},
+    cpu::{Cpu, CpuState},
+    directive::{ByteArgs, Directive, FarAddrArgs, WordArgs},
+    error::ForgeError,
+    expression::{ExpressionNode, UnaryOp},
+    instruction::Instruction,
+    label::{local_label_name, LabelMetaData},
+    line::{Labels, Line, MainComponent},
+    mnemonic::find_opcode,
+    object::{ObjectExpression, Relocation, RelocationKind, Segment, CODE_SEGMENT},
+    operand::Operand,
+    scoped_ref_to_string,
+    span::Span,
+};
+
+use crate::error::{ParseError, SpannedError};
+
+/// Turns names and expressions in the source into object expressions, using the labels and
+/// constants of the file. Anything not defined in the file is left for the linker to find
+pub struct SymbolResolver<'a> {
+    pub constant_map: &'a HashMap<String, u16>,
+    pub label_map: &'a HashMap<String, LabelMetaData>,
+}
+
+impl SymbolResolver<'_> {
+    pub fn symbol(&self, name: &str) -> ObjectExpression {
+        if let Some(label) = self.label_map.get(name) {
+            return ObjectExpression::SegmentOffset { segment: label.segment, offset: label.offset };
+        }
+
+        match self.constant_map.get(name) {
+            Some(value) => ObjectExpression::Number(*value as i32),
+            None => ObjectExpression::Symbol(name.to_string()),
+        }
+    }
+
+    /// Converts the expression, working out the parts that only use numbers and constants
+    pub fn expression(&self, expression: &ExpressionNode) -> ObjectExpression {
+        match expression {
+            ExpressionNode::Number(value, _) => ObjectExpression::Number(*value as i32),
+            ExpressionNode::Identifier(name, _) => self.symbol(name),
+            ExpressionNode::ScopedReference(path, _) => ObjectExpression::Symbol(scoped_ref_to_string(path)),
+            ExpressionNode::Parenthesized(inner, _) => self.expression(inner),
+            ExpressionNode::BinOp(op, left, right, _) => {
+                let expression = ObjectExpression::Binary(
+                    op.clone(),
+                    Box::new(self.expression(left)),
+                    Box::new(self.expression(right)),
+                );
+                fold(expression)
+            }
+            ExpressionNode::Unary(op, inner, _) => fold(ObjectExpression::Unary(*op, Box::new(self.expression(inner)))),
+        }
+    }
+
+    /// Returns the value of the expression if it only uses numbers and constants
+    pub fn constant_value(&self, expression: &ExpressionNode) -> Option<u16> {
+        match self.expression(expression) {
+            ObjectExpression::Number(value) => Some(value as u16),
+            _ => None,
+        }
+    }
+}
+
+/// Replaces an operation on numbers with its result
+fn fold(expression: ObjectExpression) -> ObjectExpression {
+    let constant = match &expression {
+        ObjectExpression::Binary(_, left, right) => {
+            matches!(**left, ObjectExpression::Number(_)) && matches!(**right, ObjectExpression::Number(_))
+        }
+        ObjectExpression::Unary(_, inner) => matches!(**inner, ObjectExpression::Number(_)),
+        _ => false,
+    };
+
+    match constant {
+        true => ObjectExpression::Number(expression.evaluate(&|_| 0, &|_| None).unwrap_or(0)),
+        false => expression,
+    }
+}
+
+/// Encodes the lines into the bytes of each segment. Values that depend on where the segments end
+/// up are written as zeros with a relocation saying what goes there. Every error found is returned
+/// rather than just the first, along with the segments so the passes after this can still check
+/// them. Statements with errors are left out of the segments
+pub fn generate(
+    lines: &[Line],
+    constant_map: &HashMap<String, u16>,
+    label_map: &HashMap<String, LabelMetaData>,
+    cpu: Cpu,
+) -> (Vec<Segment>, Vec<SpannedError>) {
+    let mut generator = Generator {
+        symbols: SymbolResolver { constant_map, label_map },
+        segments: vec![Segment::new(CODE_SEGMENT)],
+        current: 0,
+        scope: String::new(),
+    };
+    let mut state = CpuState::new(cpu);
+    let mut errors = Vec::new();
+
+    for line in lines {
+        if let Some(Labels::Label(name)) = &line.label {
+            generator.scope = name.clone();
+        }
+
+        for main_component in &line.main_components {
+            let result = match main_component {
+                MainComponent::Instruction(instruction) => generator.instruction(instruction, &state),
+                MainComponent::Directive(directive, span) => generator.directive(directive, *span),
+            };
+
+            if let Err(error) = result {
+                errors.push(error);
+            }
+
+            state.update(main_component);
+        }
+    }
+
+    (generator.segments, errors)
+}
+
+struct Generator<'a> {
+    symbols: SymbolResolver<'a>,
+    /// The segments in the order they are first used, which is the order the labels refer to them by
+    segments: Vec<Segment>,
+    /// The index of the segment being assembled into
+    current: usize,
+    /// The global label local labels are found under
+    scope: String,
+}
+
+impl Generator<'_> {
+    fn instruction(&mut self, instruction: &Instruction, state: &CpuState) -> Result<(), SpannedError> {
+        let assembly_error = |error| SpannedError::new(ParseError::Assembly { error }, instruction.span);
+
+        let opcode = find_opcode(instruction.mnemonic, instruction.address_mode(), state.cpu).map_err(assembly_error)?;
+        let operand_size = match opcode.address_mode {
+            AddressModeGeneric::Immediate => state.immediate_size(instruction.mnemonic),
+            address_mode => address_mode.operand_size(),
+        };
+
+        self.segment().bytes.push(opcode.opcode);
+
+        let operand = match &instruction.operand {
+            None | Some(Operand::AddressMode(AddressMode::Accumulator)) => return Ok(()),
+            Some(Operand::AddressMode(AddressMode::ImmediateWord(_))) if operand_size == 1 => {
+                let error = ForgeError::ImmediateTooLarge { mnemonic: instruction.mnemonic, cpu: state.cpu };
+                return Err(assembly_error(error));
+            }
+            // The machine code has the destination bank first
+            Some(Operand::AddressMode(AddressMode::BlockMove(source, destination))) => {
+                self.segment().bytes.extend([*destination, *source]);
+                return Ok(());
+            }
+            Some(Operand::AddressMode(AddressMode::ZeroPageRelative(zero_page, target))) => {
+                let zero_page = self.symbols.expression(zero_page);
+                self.write(zero_page, RelocationKind::ZeroPage8, instruction.span)?;
+                let target = self.symbols.expression(target);
+                return self.write(target, RelocationKind::Relative8, instruction.span);
+            }
+            Some(operand) => self.operand(operand).map_err(assembly_error)?,
+        };
+
+        let kind = match (opcode.address_mode, operand_size) {
+            (AddressModeGeneric::Relative, _) => RelocationKind::Relative8,
+            (AddressModeGeneric::RelativeLong, _) => RelocationKind::Relative16,
+            (AddressModeGeneric::Immediate, 1) => RelocationKind::Byte,
+            (_, 1) => RelocationKind::ZeroPage8,
+            (_, 3) => RelocationKind::Absolute24,
+            _ => RelocationKind::Absolute16,
+        };
+
+        self.write(operand, kind, instruction.span)
+    }
+
+    /// Returns the value of the operand. The address modes without a value are dealt with by the caller
+    fn operand(&self, operand: &Operand) -> Result<ObjectExpression, ForgeError> {
+        let address_mode = match operand {
+            Operand::Expression(expression) => return Ok(self.symbols.expression(expression)),
+            Operand::LocalLabel(name) => {
+                let key = local_label_name(&self.scope, name);
+                return match self.symbols.label_map.get(&key) {
+                    Some(label) => Ok(ObjectExpression::SegmentOffset { segment: label.segment, offset: label.offset }),
+                    None => Err(ForgeError::LabelOrConstantNotFound { label: format!("@{}", name) }),
+                };
+            }
+            // A reference that could not be resolved has already been reported
+            Operand::AnonymousLabel(_) => return Ok(ObjectExpression::Number(0)),
+            Operand::AddressMode(address_mode) => address_mode,
+        };
+
+        let value = match address_mode {
+            AddressMode::ZeroPageOrAbsoluteIdent(name)
+            | AddressMode::ZeroPageOrAbsoluteXIdent(name)
+            | AddressMode::ZeroPageOrAbsoluteYIdent(name)
+            | AddressMode::ImmediateIdent(name)
+            | AddressMode::IndexedIndirectXIdent(name)
+            | AddressMode::IndirectIndexYIdent(name)
+            | AddressMode::IndirectIdent(name) => self.symbols.symbol(name),
+            AddressMode::ZeroPageOrAbsoluteScopedRef(path)
+            | AddressMode::ZeroPageOrAbsoluteXScopedRef(path)
+            | AddressMode::ZeroPageOrAbsoluteYScopedRef(path)
+            | AddressMode::ImmediateScopedRef(path)
+            | AddressMode::IndexedIndirectXScopedRef(path)
+            | AddressMode::IndirectIndexYScopedRef(path)
+            | AddressMode::IndirectScopedRef(path) => ObjectExpression::Symbol(scoped_ref_to_string(path)),
+            AddressMode::ImmediateExpression(expression) => self.symbols.expression(expression),
+            AddressMode::Immediate(value)
+            | AddressMode::ZeroPage(value)
+            | AddressMode::ZeroPageX(value)
+            | AddressMode::ZeroPageY(value)
+            | AddressMode::IndexedIndirectX(value)
+            | AddressMode::IndirectIndexY(value)
+            | AddressMode::ZeroPageIndirect(value)
+            | AddressMode::ZeroPageIndirectLong(value)
+            | AddressMode::ZeroPageIndirectLongY(value)
+            | AddressMode::StackRelative(value)
+            | AddressMode::StackRelativeIndirectY(value) => ObjectExpression::Number(*value as i32),
+            AddressMode::ImmediateWord(value)
+            | AddressMode::Absolute(value)
+            | AddressMode::AbsoluteX(value)
+            | AddressMode::AbsoluteY(value)
+            | AddressMode::Indirect(value)
+            | AddressMode::AbsoluteIndexedIndirect(value)
+            | AddressMode::AbsoluteIndirectLong(value) => ObjectExpression::Number(*value as i32),
+            AddressMode::AbsoluteLong(value) | AddressMode::AbsoluteLongX(value) => ObjectExpression::Number(*value as i32),
+            AddressMode::Accumulator | AddressMode::ZeroPageRelative(..) | AddressMode::BlockMove(..) => {
+                ObjectExpression::Number(0)
+            }
+        };
+
+        Ok(value)
+    }
+
+    fn directive(&mut self, directive: &Directive, span: Span) -> Result<(), SpannedError> {
+        match directive {
+            Directive::BYTE(args) => {
+                for arg in args {
+                    let value = match arg {
+                        ByteArgs::Value(value) => ObjectExpression::Number(*value as i32),
+                        ByteArgs::Identifier(name) => self.symbols.symbol(name),
+                        ByteArgs::Expression(expression) => self.symbols.expression(expression),
+                    };
+                    self.write(value, RelocationKind::Byte, span)?;
+                }
+            }
+            Directive::WORD(args) | Directive::ADDR(args) => {
+                for arg in args {
+                    let value = match arg {
+                        WordArgs::Value(value) => ObjectExpression::Number(*value as i32),
+                        WordArgs::Identifier(name) => self.symbols.symbol(name),
+                        WordArgs::Expression(expression) => self.symbols.expression(expression),
+                    };
+                    self.write(value, RelocationKind::Absolute16, span)?;
+                }
+            }
+            Directive::FARADDR(args) => {
+                for arg in args {
+                    let value = match arg {
+                        FarAddrArgs::Value(value) => ObjectExpression::Number(*value as i32),
+                        FarAddrArgs::Identifier(name) => self.symbols.symbol(name),
+                        FarAddrArgs::Expression(expression) => self.symbols.expression(expression),
+                    };
+                    self.write(value, RelocationKind::Absolute24, span)?;
+                }
+            }
+            Directive::SEGMENT(name) => self.switch_segment(name),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn segment(&mut self) -> &mut Segment {
+        &mut self.segments[self.current]
+    }
+
+    /// Carries on from the end of the segment with the given name, starting it if it is new
+    fn switch_segment(&mut self, name: &str) {
+        self.current = match self.segments.iter().position(|segment| segment.name == name) {
+            Some(index) => index,
+            None => {
+                self.segments.push(Segment::new(name));
+                self.segments.len() - 1
+            }
+        };
+    }
+
+    /// Writes the value at the end of the segment, or a relocation for it if it is not known yet
+    fn write(&mut self, value: ObjectExpression, kind: RelocationKind, span: Span) -> Result<(), SpannedError> {
+        let current = self.current;
+        let offset = self.segment().bytes.len() as u16;
+
+        // A < or > around a whole single byte can be left to the linker as the kind of relocation
+        let (kind, value) = match (kind, value) {
+            (RelocationKind::ZeroPage8 | RelocationKind::Byte, ObjectExpression::Unary(UnaryOp::LowByte, inner)) => {
+                (RelocationKind::LowByte, *inner)
+            }
+            (RelocationKind::ZeroPage8 | RelocationKind::Byte, ObjectExpression::Unary(UnaryOp::HighByte, inner)) => {
+                (RelocationKind::HighByte, *inner)
+            }
+            (kind, value) => (kind, value),
+        };
+
+        // Branches within the segment are known already, since they only depend on the distance
+        let known = match (&value, kind) {
+            (ObjectExpression::SegmentOffset { segment, offset: target }, RelocationKind::Relative8 | RelocationKind::Relative16)
+                if *segment == current =>
+            {
+                Some(*target as i32)
+            }
+            (ObjectExpression::Number(value), kind) if kind != RelocationKind::Relative8 && kind != RelocationKind::Relative16 => {
+                Some(*value)
+            }
+            _ => None,
+        };
+
+        match known {
+            Some(known) => {
+                let bytes = kind.encode(known, offset as i32)
+                    .map_err(|error| SpannedError::new(ParseError::Assembly { error }, span))?;
+                self.segment().bytes.extend(bytes);
+            }
+            None => {
+                self.segment().bytes.extend(std::iter::repeat_n(0, kind.size() as usize));
+                self.segment().relocations.push(Relocation { offset, kind, expression: value, span });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod codegen_tests {
+    use forge_lib::{
+        cpu::Cpu,
+        object::{ObjectExpression, RelocationKind, Segment},
+    };
+
+    use crate::process::resolve;
+
+    use super::generate;
+
+    fn assemble(source: &str) -> Segment {
+        assemble_segments(source).remove(0)
+    }
+
+    fn assemble_segments(source: &str) -> Vec<Segment> {
+        let resolved = resolve(source);
+        let (segments, errors) = generate(&resolved.lines, &resolved.constant_map, &resolved.label_map, Cpu::Mos6502);
+        assert!(errors.is_empty(), "{:?}", errors);
+        segments
+    }
+
+    #[test]
+    fn test_generate_instructions() {
+        let segment = assemble("  LDA #$01\n  STA $0200\n  LDX $10,Y\n  ASL A\n  RTS\n");
+        assert_eq!(segment.bytes, vec![0xA9, 0x01, 0x8D, 0x00, 0x02, 0xB6, 0x10, 0x0A, 0x60]);
+        assert!(segment.relocations.is_empty());
+    }
+
+    #[test]
+    fn test_generate_constants_use_zero_page() {
+        let segment = assemble("ptr = $10\nscreen = $0200\n  LDA ptr\n  STA screen,X\n  LDA (ptr),Y\n");
+        assert_eq!(segment.bytes, vec![0xA5, 0x10, 0x9D, 0x00, 0x02, 0xB1, 0x10]);
+    }
+
+    #[test]
+    fn test_generate_branches() {
+        let segment = assemble("loop:\n  DEX\n  BNE loop\n  BEQ done\n  NOP\ndone:\n  RTS\n");
+        assert_eq!(segment.bytes, vec![0xCA, 0xD0, 0xFD, 0xF0, 0x01, 0xEA, 0x60]);
+        assert!(segment.relocations.is_empty());
+    }
+
+    #[test]
+    fn test_generate_relocations() {
+        let segment = assemble("start:\n  JMP start\n  LDA #<start\n  LDX #>start\n  JSR external\n  .word start + 2\n");
+        assert_eq!(segment.bytes, vec![0x4C, 0, 0, 0xA9, 0, 0xA2, 0, 0x20, 0, 0, 0, 0]);
+
+        let relocations: Vec<_> = segment.relocations.iter().map(|relocation| (relocation.offset, relocation.kind)).collect();
+        assert_eq!(relocations, vec![
+            (1, RelocationKind::Absolute16),
+            (4, RelocationKind::LowByte),
+            (6, RelocationKind::HighByte),
+            (8, RelocationKind::Absolute16),
+            (10, RelocationKind::Absolute16),
+        ]);
+        assert_eq!(segment.relocations[3].expression, ObjectExpression::Symbol(String::from("external")));
+    }
+
+    #[test]
+    fn test_generate_data() {
+        let segment = assemble("value = $1234\n  .byte $4e, <value, >value\n  .word value\n");
+        assert_eq!(segment.bytes, vec![0x4E, 0x34, 0x12, 0x34, 0x12]);
+    }
+
+    #[test]
+    fn test_generate_segments() {
+        let segments = assemble_segments(
+            "  LDA table\n.segment \"RODATA\"\ntable:\n  .byte 1, 2\n.code\n  BNE table\n.segment \"RODATA\"\nmore:\n  .byte 3\n",
+        );
+
+        let names: Vec<_> = segments.iter().map(|segment| segment.name.as_str()).collect();
+        assert_eq!(names, vec!["CODE", "RODATA"]);
+        assert_eq!(segments[0].bytes, vec![0xAD, 0, 0, 0xD0, 0]);
+        assert_eq!(segments[1].bytes, vec![1, 2, 3]);
+
+        // A branch to another segment is only known once the segments are placed
+        let targets: Vec<_> = segments[0].relocations.iter().map(|relocation| (relocation.kind, &relocation.expression)).collect();
+        assert_eq!(targets, vec![
+            (RelocationKind::Absolute16, &ObjectExpression::SegmentOffset { segment: 1, offset: 0 }),
+            (RelocationKind::Relative8, &ObjectExpression::SegmentOffset { segment: 1, offset: 0 }),
+        ]);
+    }
+
+    #[test]
+    fn test_generate_zero_page_labels() {
+        let segments = assemble_segments(".zeropage\nptr:\n  .byte 0, 0\n.code\n  LDA ptr\n  STA ptr,X\n  LDA ptr + 1\n  LDA a:ptr\n");
+        assert_eq!(segments[0].bytes, vec![0xA5, 0, 0x95, 0, 0xA5, 0, 0xAD, 0, 0]);
+
+        let kinds: Vec<_> = segments[0].relocations.iter().map(|relocation| relocation.kind).collect();
+        assert_eq!(kinds, vec![
+            RelocationKind::ZeroPage8,
+            RelocationKind::ZeroPage8,
+            RelocationKind::ZeroPage8,
+            RelocationKind::Absolute16,
+        ]);
+    }
+
+    #[test]
+    fn test_generate_byte_relocations() {
+        let segment = assemble("  LDA #external\n  .byte external, <external\n");
+        assert_eq!(segment.bytes, vec![0xA9, 0, 0, 0]);
+
+        let kinds: Vec<_> = segment.relocations.iter().map(|relocation| relocation.kind).collect();
+        assert_eq!(kinds, vec![RelocationKind::Byte, RelocationKind::Byte, RelocationKind::LowByte]);
+    }
+
+    #[test]
+    fn test_generate_addresses() {
+        let segment = assemble("reset:\n  RTS\n  .addr reset, $FFFA\n  .faraddr reset, $018000\n");
+        assert_eq!(segment.bytes, vec![0x60, 0, 0, 0xFA, 0xFF, 0, 0, 0, 0x00, 0x80, 0x01]);
+
+        let relocations: Vec<_> = segment.relocations.iter().map(|relocation| (relocation.offset, relocation.kind)).collect();
+        assert_eq!(relocations, vec![(1, RelocationKind::Absolute16), (5, RelocationKind::Absolute24)]);
+    }
+}
@@ -0,0 +1,443 @@
+use std::fmt::Write;
+
+use forge_lib::span::Span;
+
+use crate::{error::{ParseError, SpannedError}, warning::Warning};
+
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// How many columns a tab takes up when a source line is printed
+const TAB_WIDTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+
+    fn color(&self) -> &'static str {
+        match self {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        }
+    }
+}
+
+/// A note pointing at some other part of the source, such as where a label was first defined
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// A problem found in the source, ready to be shown to the user
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+    pub labels: Vec<Label>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Error, message.into(), span)
+    }
+
+    pub fn warning(message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Warning, message.into(), span)
+    }
+
+    fn new(severity: Severity, message: String, span: Span) -> Self {
+        Self { severity, message, span, labels: Vec::new(), help: None }
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label { span, message: message.into() });
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+}
+
+impl From<SpannedError> for Diagnostic {
+    fn from(value: SpannedError) -> Self {
+        let diagnostic = Diagnostic::error(value.error.to_string(), value.span);
+
+        match value.error {
+            ParseError::UnknownMnemonic { suggestion: Some(suggestion), .. } => {
+                diagnostic.with_help(format!("did you mean `{}`?", suggestion))
+            }
+            ParseError::DuplicateLabel { first, .. } => diagnostic.with_label(first, "first defined here"),
+            _ => diagnostic,
+        }
+    }
+}
+
+impl Diagnostic {
+    /// Creates the diagnostic for a warning, naming the `-W` flag that controls it. With -Werror
+    /// the warning is reported as an error instead
+    pub fn from_warning(warning: Warning, span: Span, as_errors: bool) -> Self {
+        let message = format!("{} [-W{}]", warning, warning.kind());
+        let diagnostic = match as_errors {
+            true => Diagnostic::error(message, span),
+            false => Diagnostic::warning(message, span),
+        };
+
+        match warning {
+            Warning::ShadowedSymbol { first, .. } => diagnostic.with_label(first, "first defined here"),
+            _ => diagnostic,
+        }
+    }
+}
+
+/// Every diagnostic found while assembling a file, across all of the passes
+pub struct Diagnostics {
+    diagnostics: Vec<Diagnostic>,
+    /// The number of errors to stop collecting at. 0 means there is no limit
+    error_limit: usize,
+}
+
+impl Diagnostics {
+    pub fn new(error_limit: usize) -> Self {
+        Self { diagnostics: Vec::new(), error_limit }
+    }
+
+    /// Adds a diagnostic. Nothing more is collected once the error limit has been reached
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        if !self.limit_reached() {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+
+    /// Returns true if enough errors have been found that there is no point carrying on
+    pub fn limit_reached(&self) -> bool {
+        self.error_limit != 0 && self.error_count() >= self.error_limit
+    }
+
+    /// Puts the diagnostics in the order they appear in the source
+    pub fn sort(&mut self) {
+        self.diagnostics.sort_by_key(|diagnostic| (diagnostic.span.file_id, diagnostic.span.line, diagnostic.span.column));
+    }
+
+    /// Returns every diagnostic rendered one after the other, followed by a summary if there were errors
+    pub fn render(&self, renderer: &Renderer) -> String {
+        let mut out = String::new();
+        for diagnostic in self.diagnostics.iter() {
+            let _ = writeln!(out, "{}", renderer.render(diagnostic));
+        }
+
+        let errors = self.error_count();
+        if errors == 0 {
+            return out;
+        }
+
+        let mut summary = if self.limit_reached() {
+            format!("aborting after {} {}", errors, plural(errors, "error"))
+        } else {
+            format!("could not assemble {} due to {} previous {}", renderer.file_name, errors, plural(errors, "error"))
+        };
+
+        let warnings = self.warning_count();
+        if warnings > 0 {
+            summary = format!("{}; {} {} emitted", summary, warnings, plural(warnings, "warning"));
+        }
+
+        if self.limit_reached() {
+            summary.push_str(". Use --error-limit to see more");
+        }
+
+        let _ = writeln!(out, "{}: {}", renderer.paint("error", RED), renderer.paint(&summary, BOLD));
+        out
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.diagnostics.iter().filter(|diagnostic| diagnostic.severity == severity).count()
+    }
+}
+
+fn plural(count: usize, word: &str) -> String {
+    if count == 1 {
+        word.to_string()
+    } else {
+        format!("{}s", word)
+    }
+}
+
+/// Renders diagnostics against the source they were found in, underlining the offending code
+pub struct Renderer<'a> {
+    file_name: String,
+    lines: Vec<&'a str>,
+    color: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(file_name: impl Into<String>, source: &'a str, color: bool) -> Self {
+        Self {
+            file_name: file_name.into(),
+            // Split the same way the lexer does, where \r\n and a lone \r also end a line
+            lines: source
+                .split('\n')
+                .flat_map(|line| line.strip_suffix('\r').unwrap_or(line).split('\r'))
+                .collect(),
+            color,
+        }
+    }
+
+    /// Returns the diagnostic as text. The primary span is underlined with carets and any labels
+    /// with dashes, followed by the help text if there is any
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let severity = diagnostic.severity;
+        let mut out = String::new();
+
+        // Every underline along with the character it is drawn with and its message, in source order
+        let mut underlines = vec![(diagnostic.span, '^', severity.color(), "")];
+        underlines.extend(diagnostic.labels.iter().map(|label| (label.span, '-', BLUE, label.message.as_str())));
+        underlines.sort_by_key(|(span, ..)| (span.line, span.column));
+
+        let gutter = underlines.iter().map(|(span, ..)| span.line).max().unwrap_or(0).to_string().len();
+        let margin = " ".repeat(gutter);
+
+        let _ = writeln!(out, "{}: {}", self.paint(severity.name(), severity.color()), self.paint(&diagnostic.message, BOLD));
+        let _ = writeln!(out, "{}{} {}:{}", margin, self.paint("-->", BLUE), self.file_name, diagnostic.span);
+        let _ = writeln!(out, "{} {}", margin, self.paint("|", BLUE));
+
+        let mut previous_line = None;
+        for (span, marker, color, message) in underlines {
+            if previous_line != Some(span.line) {
+                if previous_line.is_some_and(|line| span.line > line + 1) {
+                    let _ = writeln!(out, "{}", self.paint("...", BLUE));
+                }
+
+                let source = self.source_line(span.line);
+                let line_number = format!("{:>gutter$}", span.line);
+                let _ = writeln!(out, "{} {} {}", self.paint(&line_number, BLUE), self.paint("|", BLUE), expand_tabs(source));
+                previous_line = Some(span.line);
+            }
+
+            let (offset, width) = self.underline_position(span);
+            let underline = marker.to_string().repeat(width.max(1));
+            let underline = if message.is_empty() { underline } else { format!("{} {}", underline, message) };
+            let _ = writeln!(out, "{} {} {}{}", margin, self.paint("|", BLUE), " ".repeat(offset), self.paint(&underline, color));
+        }
+
+        if let Some(help) = &diagnostic.help {
+            let _ = writeln!(out, "{} {}", margin, self.paint("|", BLUE));
+            let _ = writeln!(out, "{} {} {}", margin, self.paint("= help:", CYAN), help);
+        }
+
+        out
+    }
+
+    fn source_line(&self, line: u32) -> &str {
+        (line as usize).checked_sub(1).and_then(|index| self.lines.get(index)).copied().unwrap_or("")
+    }
+
+    /// Returns how far in the underline starts and how wide it is once tabs have been expanded
+    fn underline_position(&self, span: Span) -> (usize, usize) {
+        let chars: Vec<char> = self.source_line(span.line).chars().collect();
+        let start = (span.column as usize).saturating_sub(1).min(chars.len());
+        let end = (start + span.length as usize).min(chars.len());
+
+        (display_width(&chars[..start]), display_width(&chars[start..end]))
+    }
+
+    fn paint(&self, text: &str, color: &str) -> String {
+        if self.color {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn display_width(chars: &[char]) -> usize {
+    chars.iter().map(|c| if *c == '\t' { TAB_WIDTH } else { 1 }).sum()
+}
+
+fn expand_tabs(line: &str) -> String {
+    line.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+#[cfg(test)]
+mod diagnostic_tests {
+    use forge_lib::{mnemonic::Mnemonic, span::Span};
+
+    use crate::{
+        diagnostic::{Diagnostic, Diagnostics, Renderer},
+        error::{ParseError, SpannedError},
+        scanner::Scanner,
+    };
+
+    /// Parses the source up to its first error and returns the source line and underline rendered for it
+    fn render_first_error(source: &str) -> Vec<String> {
+        let mut scanner = Scanner::new(source);
+        let error = loop {
+            if let Err(error) = scanner.next_line() {
+                break error;
+            }
+        };
+
+        let renderer = Renderer::new("test.asm", source, false);
+        renderer.render(&Diagnostic::from(error)).lines().skip(3).take(2).map(String::from).collect()
+    }
+
+    #[test]
+    fn test_render_caret_under_span() {
+        let source = "NOP\n  LDA #$01 junk\n";
+        let renderer = Renderer::new("test.asm", source, false);
+        let diagnostic = Diagnostic::error("Expected a newline", Span::new(0, 2, 12, 4));
+
+        assert_eq!(
+            renderer.render(&diagnostic),
+            "error: Expected a newline\n \
+             --> test.asm:2:12\n  \
+              |\n\
+             2 |   LDA #$01 junk\n  \
+              |            ^^^^\n"
+        );
+    }
+
+    #[test]
+    fn test_render_underlines_offending_tokens() {
+        assert_eq!(render_first_error("  .byte 300, 2\n"), vec!["1 |   .byte 300, 2", "  |         ^^^"]);
+        assert_eq!(render_first_error("  LDA z:$1234\n"), vec!["1 |   LDA z:$1234", "  |       ^^^^^^^"]);
+        assert_eq!(
+            render_first_error(".cpu 65c02\n.feature illegal\n"),
+            vec!["2 | .feature illegal", "  |          ^^^^^^^"]
+        );
+    }
+
+    #[test]
+    fn test_render_line_endings() {
+        let source = "NOP\r\nNOP\r  LDA #$01 junk\r\n";
+        let renderer = Renderer::new("test.asm", source, false);
+        let diagnostic = Diagnostic::error("Expected a newline", Span::new(0, 3, 12, 4));
+
+        assert!(renderer.render(&diagnostic).contains("3 |   LDA #$01 junk\n"));
+    }
+
+    #[test]
+    fn test_render_help_for_misspelled_mnemonic() {
+        let source = "\tEQR #$01\n";
+        let renderer = Renderer::new("test.asm", source, false);
+        let error = ParseError::UnknownMnemonic { name: String::from("EQR"), suggestion: Some(Mnemonic::EOR), position: 1 };
+        let diagnostic = Diagnostic::from(SpannedError::new(error, Span::new(0, 1, 2, 3)));
+
+        assert_eq!(
+            renderer.render(&diagnostic),
+            "error: Unknown instruction: EQR\n \
+             --> test.asm:1:2\n  \
+              |\n\
+             1 |     EQR #$01\n  \
+              |     ^^^\n  \
+              |\n  \
+              = help: did you mean `EOR`?\n"
+        );
+    }
+
+    #[test]
+    fn test_render_secondary_label() {
+        let source = "loop:\n  NOP\nloop:\n";
+        let renderer = Renderer::new("test.asm", source, false);
+        let error = ParseError::DuplicateLabel { name: String::from("loop"), first: Span::new(0, 1, 1, 5) };
+        let diagnostic = Diagnostic::from(SpannedError::new(error, Span::new(0, 3, 1, 5)));
+
+        assert_eq!(
+            renderer.render(&diagnostic),
+            "error: Label loop is defined more than once\n \
+             --> test.asm:3:1\n  \
+              |\n\
+             1 | loop:\n  \
+              | ----- first defined here\n\
+             ...\n\
+             3 | loop:\n  \
+              | ^^^^^\n"
+        );
+    }
+
+    #[test]
+    fn test_render_color() {
+        let renderer = Renderer::new("test.asm", "BRK\n", true);
+        let output = renderer.render(&Diagnostic::warning("Something odd", Span::new(0, 1, 1, 3)));
+
+        assert!(output.starts_with("\x1b[1;33mwarning\x1b[0m"));
+        assert!(output.contains("\x1b[1;33m^^^\x1b[0m"));
+    }
+
+    #[test]
+    fn test_diagnostics_error_limit() {
+        let mut diagnostics = Diagnostics::new(2);
+        diagnostics.push(Diagnostic::warning("First", Span::new(0, 3, 1, 1)));
+        diagnostics.push(Diagnostic::error("Second", Span::new(0, 2, 1, 1)));
+        assert!(!diagnostics.limit_reached());
+
+        diagnostics.push(Diagnostic::error("Third", Span::new(0, 1, 1, 1)));
+        diagnostics.push(Diagnostic::error("Fourth", Span::new(0, 4, 1, 1)));
+        assert!(diagnostics.limit_reached());
+        assert_eq!(diagnostics.error_count(), 2);
+
+        diagnostics.sort();
+        let renderer = Renderer::new("test.asm", "A\nB\nC\nD\n", false);
+        let output = renderer_output(&diagnostics, &renderer);
+
+        assert_eq!(output[0], "error: Third");
+        assert_eq!(output[1], "error: Second");
+        assert_eq!(output[2], "warning: First");
+        assert_eq!(output[3], "error: aborting after 2 errors; 1 warning emitted. Use --error-limit to see more");
+    }
+
+    #[test]
+    fn test_diagnostics_summary() {
+        let mut diagnostics = Diagnostics::new(0);
+        diagnostics.push(Diagnostic::error("Bad", Span::new(0, 1, 1, 1)));
+        diagnostics.push(Diagnostic::warning("Odd", Span::new(0, 1, 1, 1)));
+
+        let renderer = Renderer::new("test.asm", "A\n", false);
+        let output = renderer_output(&diagnostics, &renderer);
+
+        assert_eq!(output.last().unwrap(), &"error: could not assemble test.asm due to 1 previous error; 1 warning emitted");
+
+        let diagnostics = Diagnostics::new(0);
+        assert!(diagnostics.render(&renderer).is_empty());
+    }
+
+    /// Returns the header line of each diagnostic and the summary
+    fn renderer_output(diagnostics: &Diagnostics, renderer: &Renderer) -> Vec<String> {
+        diagnostics
+            .render(renderer)
+            .lines()
+            .filter(|line| line.starts_with("error") || line.starts_with("warning"))
+            .map(String::from)
+            .collect()
+    }
+}
@@ -1,22 +1,40 @@
 use std::fmt;
 
-use crate::scanner::Token;
+use forge_lib::{cpu::Cpu, directive::Feature, error::ForgeError, label::anonymous_reference_name, mnemonic::Mnemonic, span::Span};
+
+use crate::{scanner::Token, warning::warning_names};
 
 #[derive(Debug, PartialEq)]
 pub enum ParseError {
     UnexpectedEndOfInput,
-    UnexpectedToken { expected: Token, received: Token, position: usize },
+    UnexpectedToken { expected: Box<Token>, received: Box<Token>, position: usize },
     ParseIntError { msg: String, position: usize },
     DirectiveWithNoArg { directive: String },
     ExpectedLiteralU8,
     ExpectedAddressU8,
     ExpectedAddressU16,
+    ExpectedAddressU24,
     ExpectedNewline,
+    ExpectedStatement,
     ExpectedValidMnemonic,
     MissingClosingParenthesis,
     TooManyDigits { msg: String, position: usize },
     ValueTooLarge,
     ValidArgNotFound,
+    UnsupportedMnemonic { mnemonic: Mnemonic, cpu: Cpu },
+    UndocumentedMnemonic { mnemonic: Mnemonic, cpu: Cpu },
+    UnknownMnemonic { name: String, suggestion: Option<Mnemonic>, position: usize },
+    DuplicateLabel { name: String, first: Span },
+    ImportedSymbolDefined { name: String },
+    ExportedSymbolUndefined { name: String },
+    UndeclaredSymbol { name: String },
+    AnonymousLabelNotFound { offset: i8 },
+    ZeroPageOutOfRange { value: u32 },
+    UnknownCpu { name: String },
+    UnknownFeature { name: String },
+    UnsupportedFeature { feature: Feature, cpu: Cpu },
+    UnknownWarning { name: String },
+    Assembly { error: ForgeError },
 }
 
 impl fmt::Display for ParseError {
@@ -25,11 +43,11 @@ impl fmt::Display for ParseError {
             ParseError::UnexpectedEndOfInput => {
                 write!(f, "Unexpected end of input")
             }
-            ParseError::UnexpectedToken { expected, received, position } => {
-                write!(f, "Unexpected token at {}: expected {} - received {}", position, expected.to_generic(), received)
+            ParseError::UnexpectedToken { expected, received, .. } => {
+                write!(f, "Unexpected token: expected {} - received {}", expected.to_generic(), received)
             }
-            ParseError::ParseIntError { msg, position } => {
-                write!(f, "Failed to parse string to integer: {} at {}", msg, position)
+            ParseError::ParseIntError { msg, .. } => {
+                write!(f, "Failed to parse string to integer: {}", msg)
             }
             ParseError::DirectiveWithNoArg { directive } => {
                 write!(f, "Directive {} found with no argument(s)", directive)
@@ -43,17 +61,23 @@ impl fmt::Display for ParseError {
             ParseError::ExpectedAddressU16 => {
                 write!(f, "Expected to parse a u16 address hex value")
             }
+            ParseError::ExpectedAddressU24 => {
+                write!(f, "Expected to parse a u24 address hex value")
+            }
             ParseError::ExpectedNewline => {
                 write!(f, "Expected a newline")
             }
+            ParseError::ExpectedStatement => {
+                write!(f, "Expected an instruction or directive after the colon")
+            }
             ParseError::ExpectedValidMnemonic => {
                 write!(f, "Expected a valid operation")
             }
             ParseError::MissingClosingParenthesis => {
                 write!(f, "Missing closing parenthesis for expression")
             }
-            ParseError::TooManyDigits { msg, position } => {
-                write!(f, "Too many digits found in input: {} at {}", msg, position)
+            ParseError::TooManyDigits { msg, .. } => {
+                write!(f, "Too many digits found in input: {}", msg)
             }
             ParseError::ValidArgNotFound => {
                 write!(f, "Valid argument not found for directive")
@@ -61,6 +85,49 @@ impl fmt::Display for ParseError {
             ParseError::ValueTooLarge => {
                 write!(f, "Value too large")
             }
+            ParseError::UnsupportedMnemonic { mnemonic, cpu } => {
+                write!(f, "{} is not available on the {}. Select another CPU with --cpu or .cpu", mnemonic, cpu)
+            }
+            ParseError::UndocumentedMnemonic { mnemonic, cpu } => {
+                write!(f, "{} is an undocumented instruction. Enable it with --cpu {} or .feature illegal", mnemonic, cpu)
+            }
+            ParseError::UnknownMnemonic { name, .. } => {
+                write!(f, "Unknown instruction: {}", name)
+            }
+            ParseError::DuplicateLabel { name, .. } => {
+                write!(f, "Label {} is defined more than once", name)
+            }
+            ParseError::ImportedSymbolDefined { name } => {
+                write!(f, "{} is imported but is also defined in this file", name)
+            }
+            ParseError::ExportedSymbolUndefined { name } => {
+                write!(f, "{} is exported but is not defined in this file", name)
+            }
+            ParseError::UndeclaredSymbol { name } => {
+                write!(f, "{} is not defined in this file. Import it with .import or assemble with --auto-import", name)
+            }
+            ParseError::AnonymousLabelNotFound { offset } => {
+                let direction = if *offset < 0 { "before" } else { "after" };
+                write!(f, "There are not enough anonymous labels {} this line for {}", direction, anonymous_reference_name(*offset))
+            }
+            ParseError::ZeroPageOutOfRange { value, .. } => {
+                write!(f, "${:04X} is forced to zero page but does not fit in a byte", value)
+            }
+            ParseError::UnknownCpu { name } => {
+                write!(f, "Unknown CPU: {}", name)
+            }
+            ParseError::UnknownFeature { name } => {
+                write!(f, "Unknown feature: {}", name)
+            }
+            ParseError::UnsupportedFeature { feature, cpu } => {
+                write!(f, "The {} feature is not available on the {}", feature, cpu)
+            }
+            ParseError::UnknownWarning { name } => {
+                write!(f, "Unknown warning: {}. Expected all or one of {}", name, warning_names())
+            }
+            ParseError::Assembly { error } => {
+                write!(f, "{}", error)
+            }
         }
     }
 }
@@ -68,11 +135,41 @@ impl fmt::Display for ParseError {
 impl ParseError {
     /// Returns a boolean value if the current error is a fatal error
     pub fn is_fatal(&self) -> bool {
+        !matches!(
+            self,
+            ParseError::TooManyDigits { .. }
+                | ParseError::ExpectedValidMnemonic
+                | ParseError::ValidArgNotFound
+        )
+    }
+
+    /// Returns the index of the token on its line the error was found at, if the error keeps track of it
+    pub fn position(&self) -> Option<usize> {
         match self {
-            ParseError::TooManyDigits { msg: _, position: _ } => false,
-            ParseError::ExpectedValidMnemonic => false,
-            ParseError::ValidArgNotFound => false,
-            _ => true
+            ParseError::UnexpectedToken { position, .. }
+            | ParseError::ParseIntError { position, .. }
+            | ParseError::TooManyDigits { position, .. }
+            | ParseError::UnknownMnemonic { position, .. } => Some(*position),
+            _ => None,
         }
     }
+}
+
+/// An error along with where in the source it was found
+#[derive(Debug, PartialEq)]
+pub struct SpannedError {
+    pub error: ParseError,
+    pub span: Span,
+}
+
+impl SpannedError {
+    pub fn new(error: ParseError, span: Span) -> Self {
+        Self { error, span }
+    }
+}
+
+impl fmt::Display for SpannedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.error)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,296 @@
+use forge_lib::span::Span;
+
+/// The kinds of token the source is split into. Tokens only carry where they are in the source, so
+/// the text of identifiers and numbers is sliced out of the source when the parser needs it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// A letter followed by any letters, digits or underscores
+    Identifier,
+    /// A `$` followed by hex digits. Any letters or digits after the `$` are included so that a
+    /// bad digit is reported as part of the number
+    HexNumber,
+    /// A `%` followed by binary digits
+    BinaryNumber,
+    /// A number starting with a digit. Also covers names such as 65C02
+    DecimalNumber,
+    /// Text between double quotes, including the quotes. The closing quote may be missing
+    String,
+    /// A `;` and the rest of the line
+    Comment,
+    /// A `\n`, `\r\n` or a lone `\r`
+    Newline,
+    Hash,
+    Comma,
+    Colon,
+    DoubleColon,
+    Dot,
+    At,
+    Equals,
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    ShiftLeft,
+    ShiftRight,
+    Less,
+    Greater,
+    Pipe,
+    Ampersand,
+    /// A character that cannot start any token
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lexeme {
+    pub kind: TokenKind,
+    /// The byte offset the token starts at
+    pub start: usize,
+    /// The byte offset just after the token
+    pub end: usize,
+    pub span: Span,
+    /// Whether there was whitespace between this token and the one before it on the same line
+    pub spaced: bool,
+}
+
+/// Splits the source into tokens. Spaces and tabs are skipped, but each token records whether it
+/// came after some since the grammar cares in places (e.g. `label:` or `@local`)
+pub struct Lexer<'a> {
+    input: &'a [u8],
+    position: usize,
+    file_id: u32,
+    line: u32,
+    column: u32,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str, file_id: u32) -> Self {
+        Self {
+            input: input.as_bytes(),
+            position: 0,
+            file_id,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn peek_byte(&self, offset: usize) -> Option<u8> {
+        self.input.get(self.position + offset).copied()
+    }
+
+    /// Moves past bytes as long as they match the predicate
+    fn skip_while(&mut self, predicate: impl Fn(u8) -> bool) {
+        while let Some(byte) = self.peek_byte(0) {
+            if !predicate(byte) {
+                break;
+            }
+            self.position += 1;
+        }
+    }
+
+    fn lex_kind(&mut self, first: u8) -> TokenKind {
+        let single = |lexer: &mut Self, kind| {
+            lexer.position += 1;
+            kind
+        };
+        let double = |lexer: &mut Self, kind| {
+            lexer.position += 2;
+            kind
+        };
+
+        match first {
+            b'a'..=b'z' | b'A'..=b'Z' => {
+                self.skip_while(is_identifier_byte);
+                TokenKind::Identifier
+            }
+            b'0'..=b'9' => {
+                self.skip_while(is_identifier_byte);
+                TokenKind::DecimalNumber
+            }
+            b'$' | b'%' if self.peek_byte(1).is_some_and(is_identifier_byte) => {
+                self.position += 1;
+                self.skip_while(is_identifier_byte);
+                if first == b'$' {
+                    TokenKind::HexNumber
+                } else {
+                    TokenKind::BinaryNumber
+                }
+            }
+            b'"' => {
+                self.position += 1;
+                self.skip_while(|byte| byte != b'"' && !is_newline_byte(byte));
+                if self.peek_byte(0) == Some(b'"') {
+                    self.position += 1;
+                }
+                TokenKind::String
+            }
+            b';' => {
+                self.skip_while(|byte| !is_newline_byte(byte));
+                TokenKind::Comment
+            }
+            b'\r' if self.peek_byte(1) == Some(b'\n') => double(self, TokenKind::Newline),
+            b'\n' | b'\r' => single(self, TokenKind::Newline),
+            b'#' => single(self, TokenKind::Hash),
+            b',' => single(self, TokenKind::Comma),
+            b':' if self.peek_byte(1) == Some(b':') => double(self, TokenKind::DoubleColon),
+            b':' => single(self, TokenKind::Colon),
+            b'.' => single(self, TokenKind::Dot),
+            b'@' => single(self, TokenKind::At),
+            b'=' => single(self, TokenKind::Equals),
+            b'(' => single(self, TokenKind::LeftParen),
+            b')' => single(self, TokenKind::RightParen),
+            b'[' => single(self, TokenKind::LeftBracket),
+            b']' => single(self, TokenKind::RightBracket),
+            b'+' => single(self, TokenKind::Plus),
+            b'-' => single(self, TokenKind::Minus),
+            b'*' => single(self, TokenKind::Star),
+            b'/' => single(self, TokenKind::Slash),
+            b'<' if self.peek_byte(1) == Some(b'<') => double(self, TokenKind::ShiftLeft),
+            b'<' => single(self, TokenKind::Less),
+            b'>' if self.peek_byte(1) == Some(b'>') => double(self, TokenKind::ShiftRight),
+            b'>' => single(self, TokenKind::Greater),
+            b'|' => single(self, TokenKind::Pipe),
+            b'&' => single(self, TokenKind::Ampersand),
+            _ => {
+                // Take the whole character rather than splitting it partway through
+                self.position += 1;
+                self.skip_while(|byte| byte & 0xC0 == 0x80);
+                TokenKind::Unknown
+            }
+        }
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Lexeme;
+
+    fn next(&mut self) -> Option<Lexeme> {
+        let whitespace_start = self.position;
+        self.skip_while(|byte| byte == b' ' || byte == b'\t');
+        let spaced = self.position != whitespace_start;
+        self.column += (self.position - whitespace_start) as u32;
+
+        let start = self.position;
+        let first = self.peek_byte(0)?;
+        let kind = self.lex_kind(first);
+        let end = self.position;
+
+        // Columns are counted in characters, so the continuation bytes of UTF-8 characters are skipped
+        let length = match kind {
+            TokenKind::Comment | TokenKind::String | TokenKind::Unknown => {
+                self.input[start..end].iter().filter(|byte| *byte & 0xC0 != 0x80).count() as u32
+            }
+            _ => (end - start) as u32,
+        };
+        let span = Span::new(self.file_id, self.line, self.column, length);
+
+        if kind == TokenKind::Newline {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += length;
+        }
+
+        Some(Lexeme { kind, start, end, span, spaced })
+    }
+}
+
+fn is_identifier_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+fn is_newline_byte(byte: u8) -> bool {
+    byte == b'\n' || byte == b'\r'
+}
+
+#[cfg(test)]
+mod lexer_tests {
+    use forge_lib::span::Span;
+
+    use super::{Lexer, TokenKind};
+
+    fn kinds(input: &str) -> Vec<TokenKind> {
+        Lexer::new(input, 0).map(|lexeme| lexeme.kind).collect()
+    }
+
+    #[test]
+    fn test_lex_instruction() {
+        assert_eq!(
+            kinds("loop: LDA ($10),Y ; comment\n"),
+            vec![
+                TokenKind::Identifier,
+                TokenKind::Colon,
+                TokenKind::Identifier,
+                TokenKind::LeftParen,
+                TokenKind::HexNumber,
+                TokenKind::RightParen,
+                TokenKind::Comma,
+                TokenKind::Identifier,
+                TokenKind::Comment,
+                TokenKind::Newline,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lex_numbers_and_operators() {
+        assert_eq!(
+            kinds("#$0fcz %0110 65C02 << >> < :: $"),
+            vec![
+                TokenKind::Hash,
+                TokenKind::HexNumber,
+                TokenKind::BinaryNumber,
+                TokenKind::DecimalNumber,
+                TokenKind::ShiftLeft,
+                TokenKind::ShiftRight,
+                TokenKind::Less,
+                TokenKind::DoubleColon,
+                TokenKind::Unknown,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lex_line_endings() {
+        assert_eq!(
+            kinds("NOP\r\n; windows\r\nNOP\rNOP\n"),
+            vec![
+                TokenKind::Identifier,
+                TokenKind::Newline,
+                TokenKind::Comment,
+                TokenKind::Newline,
+                TokenKind::Identifier,
+                TokenKind::Newline,
+                TokenKind::Identifier,
+                TokenKind::Newline,
+            ]
+        );
+
+        let lines: Vec<_> = Lexer::new("NOP\r\nNOP\rNOP", 0).map(|lexeme| lexeme.span.line).collect();
+        assert_eq!(lines, vec![1, 1, 2, 2, 3]);
+    }
+
+    #[test]
+    fn test_lex_spans() {
+        let lexemes: Vec<_> = Lexer::new("  .byte \"é\" ; ü\n\tNOP", 0).collect();
+
+        let spans: Vec<_> = lexemes.iter().map(|lexeme| lexeme.span).collect();
+        assert_eq!(
+            spans,
+            vec![
+                Span::new(0, 1, 3, 1),
+                Span::new(0, 1, 4, 4),
+                Span::new(0, 1, 9, 3),
+                Span::new(0, 1, 13, 3),
+                Span::new(0, 1, 16, 1),
+                Span::new(0, 2, 2, 3),
+            ]
+        );
+
+        let spaced: Vec<_> = lexemes.iter().map(|lexeme| lexeme.spaced).collect();
+        assert_eq!(spaced, vec![true, false, true, true, false, true]);
+    }
+}
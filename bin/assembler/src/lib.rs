@@ -0,0 +1,11 @@
+//! The assembler's passes, from scanning the source through to writing the object file. The
+//! binary in main.rs drives them, and they are exposed as a library so they can be benchmarked
+pub mod analysis;
+pub mod codegen;
+pub mod diagnostic;
+pub mod error;
+pub mod lexer;
+pub mod linkage;
+pub mod process;
+pub mod scanner;
+pub mod warning;
@@ -0,0 +1,321 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use forge_lib::{
+    directive::Directive,
+    label::LabelMetaData,
+    line::{Line, MainComponent},
+    object::{Contents, Import, ObjectExpression, Segment, Symbol, SymbolValue, Visibility, ZEROPAGE_SEGMENT},
+    span::Span,
+};
+
+use crate::error::{ParseError, SpannedError};
+
+/// A name given to `.export`, `.import` or `.global`
+#[derive(Debug, Clone)]
+struct Declaration {
+    name: String,
+    zero_page: bool,
+    /// The directive it was given to
+    span: Span,
+}
+
+/// The symbols a file shares with other files. Where the directives are in the file does not matter,
+/// so they are all collected before anything is assembled
+#[derive(Debug, Default)]
+pub struct Linkage {
+    exports: Vec<Declaration>,
+    imports: Vec<Declaration>,
+    globals: Vec<Declaration>,
+}
+
+impl Linkage {
+    pub fn new(lines: &[Line]) -> Self {
+        let mut linkage = Self::default();
+
+        for main_component in lines.iter().flat_map(|line| &line.main_components) {
+            let MainComponent::Directive(directive, span) = main_component else { continue };
+            let (declarations, names, zero_page) = match directive {
+                Directive::EXPORT { names, zero_page } => (&mut linkage.exports, names, *zero_page),
+                Directive::IMPORT { names, zero_page } => (&mut linkage.imports, names, *zero_page),
+                Directive::GLOBAL(names) => (&mut linkage.globals, names, false),
+                _ => continue,
+            };
+
+            declarations.extend(names.iter().map(|name| Declaration { name: name.clone(), zero_page, span: *span }));
+        }
+
+        linkage
+    }
+
+    /// Returns true if the name was imported with `.importzp`
+    pub fn is_zero_page_import(&self, name: &str) -> bool {
+        self.imports.iter().any(|import| import.zero_page && import.name == name)
+    }
+
+    /// Lists every label and constant in the file with whether it is exported, along with the symbols
+    /// the file needs from other files. Both are sorted by name. Names used in the file but neither
+    /// defined nor imported in it are an error, unless `auto_import` is set, in which case they are
+    /// imported and left for the linker to find
+    pub fn symbols_and_imports(
+        &self,
+        constant_map: &HashMap<String, u16>,
+        label_map: &HashMap<String, LabelMetaData>,
+        segments: &[Segment],
+        auto_import: bool,
+    ) -> Result<(Vec<Symbol>, Vec<Import>), Vec<SpannedError>> {
+        let defined = |name: &str| label_map.contains_key(name) || constant_map.contains_key(name);
+        let mut exports: HashMap<&str, bool> = HashMap::new();
+        let mut imports: HashMap<&str, bool> = HashMap::new();
+        let mut errors = Vec::new();
+
+        for import in &self.imports {
+            match defined(&import.name) {
+                true => {
+                    let error = ParseError::ImportedSymbolDefined { name: import.name.clone() };
+                    errors.push(SpannedError::new(error, import.span));
+                }
+                false => *imports.entry(&import.name).or_default() |= import.zero_page,
+            }
+        }
+
+        for export in &self.exports {
+            match defined(&export.name) {
+                true => *exports.entry(&export.name).or_default() |= export.zero_page,
+                false => {
+                    let error = ParseError::ExportedSymbolUndefined { name: export.name.clone() };
+                    errors.push(SpannedError::new(error, export.span));
+                }
+            }
+        }
+
+        for global in &self.globals {
+            match defined(&global.name) {
+                true => exports.entry(&global.name).or_default(),
+                false => imports.entry(&global.name).or_default(),
+            };
+        }
+
+        // Only the first use of each undeclared name is reported
+        let mut undeclared = HashSet::new();
+        for relocation in segments.iter().flat_map(|segment| &segment.relocations) {
+            for name in relocation.expression.symbol_names() {
+                if auto_import {
+                    imports.entry(name).or_default();
+                } else if !imports.contains_key(name) && undeclared.insert(name) {
+                    let error = ParseError::UndeclaredSymbol { name: name.to_string() };
+                    errors.push(SpannedError::new(error, relocation.span));
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        // Labels in the zero page segment are 1-byte addresses whether or not .exportzp was used
+        let zero_page_segment = segments.iter().position(|segment| segment.name == ZEROPAGE_SEGMENT);
+        let visibility = |name: &str, segment: Option<usize>| match exports.get(name) {
+            Some(true) => Visibility::ExportZeroPage,
+            Some(false) if segment.is_some() && segment == zero_page_segment => Visibility::ExportZeroPage,
+            Some(false) => Visibility::Export,
+            None => Visibility::Local,
+        };
+
+        let labels = label_map.iter().map(|(name, label)| Symbol {
+            name: name.clone(),
+            value: SymbolValue::Label { segment: label.segment, offset: label.offset },
+            visibility: visibility(name, Some(label.segment)),
+        });
+        // A name used for both is a label
+        let constants = constant_map.iter().filter(|(name, _)| !label_map.contains_key(*name)).map(|(name, value)| Symbol {
+            name: name.clone(),
+            value: SymbolValue::Constant(*value),
+            visibility: visibility(name, None),
+        });
+
+        let mut symbols: Vec<Symbol> = labels.chain(constants).collect();
+        symbols.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut imports: Vec<Import> = imports
+            .into_iter()
+            .map(|(name, zero_page)| Import { name: name.to_string(), zero_page })
+            .collect();
+        imports.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok((symbols, imports))
+    }
+}
+
+/// Lowercases the names a file shares with other files, so that when symbols are not case sensitive
+/// they match however the other files spell them. Local symbols keep the spelling they were written with
+pub fn fold_linked_names(contents: &mut Contents) {
+    fn fold(expression: &mut ObjectExpression) {
+        match expression {
+            ObjectExpression::Number(_) | ObjectExpression::SegmentOffset { .. } => {}
+            ObjectExpression::Symbol(name) => *name = name.to_ascii_lowercase(),
+            ObjectExpression::Binary(_, left, right) => {
+                fold(left);
+                fold(right);
+            }
+            ObjectExpression::Unary(_, inner) => fold(inner),
+        }
+    }
+
+    for symbol in contents.symbols.iter_mut().filter(|symbol| symbol.visibility != Visibility::Local) {
+        let name = symbol.name.to_ascii_lowercase();
+        if let Some(comment) = contents.comments.remove(&symbol.name) {
+            contents.comments.insert(name.clone(), comment);
+        }
+        symbol.name = name;
+    }
+    for import in &mut contents.imports {
+        import.name = import.name.to_ascii_lowercase();
+    }
+    for relocation in contents.segments.iter_mut().flat_map(|segment| &mut segment.relocations) {
+        fold(&mut relocation.expression);
+    }
+
+    contents.symbols.sort_by(|a, b| a.name.cmp(&b.name));
+    contents.imports.sort_by(|a, b| a.name.cmp(&b.name));
+}
+
+/// Finds the comment written with each label and constant, either on the same line or on a line of its
+/// own right above it. Labels are matched by where they were defined, so only the first definition
+/// of a duplicated label is used
+pub fn symbol_comments(lines: &[Line], label_map: &HashMap<String, LabelMetaData>) -> BTreeMap<String, String> {
+    let labels: HashMap<(u32, u32, u32), &String> =
+        label_map.iter().map(|(name, label)| ((label.span.file_id, label.span.line, label.span.column), name)).collect();
+    let mut comments = BTreeMap::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let name = match (&line.constant, line.label_span()) {
+            (Some((name, _)), _) => name,
+            (None, Some(span)) => match labels.get(&(span.file_id, span.line, span.column)) {
+                Some(name) => *name,
+                None => continue,
+            },
+            (None, None) => continue,
+        };
+
+        let above = index.checked_sub(1).map(|above| &lines[above]).filter(|above| {
+            above.newlines == 1
+                && above.span.file_id == line.span.file_id
+                && above.label.is_none()
+                && above.constant.is_none()
+                && above.main_components.is_empty()
+        });
+        let Some(comment) = line.comment.as_ref().or(above.and_then(|above| above.comment.as_ref())) else { continue };
+
+        let comment = comment.trim_start_matches(';').trim();
+        if !comment.is_empty() {
+            comments.entry(name.clone()).or_insert_with(|| comment.to_string());
+        }
+    }
+
+    comments
+}
+
+#[cfg(test)]
+mod linkage_tests {
+    use std::path::Path;
+
+    use forge_lib::{
+        cpu::Cpu,
+        object::{Import, ObjectExpression, Symbol, Visibility},
+    };
+
+    use crate::{
+        codegen::generate,
+        error::ParseError,
+        process::{process_file, resolve, scan, ObjectOptions},
+    };
+
+    use super::symbol_comments;
+
+    fn link(source: &str, auto_import: bool) -> Result<(Vec<Symbol>, Vec<Import>), Vec<ParseError>> {
+        let resolved = resolve(source);
+        let (segments, errors) = generate(&resolved.lines, &resolved.constant_map, &resolved.label_map, Cpu::Mos6502);
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        resolved
+            .linkage
+            .symbols_and_imports(&resolved.constant_map, &resolved.label_map, &segments, auto_import)
+            .map_err(|errors| errors.into_iter().map(|error| error.error).collect())
+    }
+
+    fn visibility<'a>(symbols: &'a [Symbol], name: &str) -> Option<&'a Visibility> {
+        symbols.iter().find(|symbol| symbol.name == name).map(|symbol| &symbol.visibility)
+    }
+
+    #[test]
+    fn test_exports() {
+        let (symbols, _) = link(
+            ".export main, SIZE\n.exportzp flag\n.global tick\nSIZE = $10\nflag = $20\nmain:\n  RTS\ntick:\n  RTS\nhelper:\n  RTS\n.zeropage\nptr:\n  .byte 0\n.export ptr\n",
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(visibility(&symbols, "main"), Some(&Visibility::Export));
+        assert_eq!(visibility(&symbols, "SIZE"), Some(&Visibility::Export));
+        assert_eq!(visibility(&symbols, "flag"), Some(&Visibility::ExportZeroPage));
+        assert_eq!(visibility(&symbols, "tick"), Some(&Visibility::Export));
+        assert_eq!(visibility(&symbols, "helper"), Some(&Visibility::Local));
+        assert_eq!(visibility(&symbols, "ptr"), Some(&Visibility::ExportZeroPage));
+    }
+
+    #[test]
+    fn test_imports() {
+        let (_, imports) = link(".import init\n.importzp ptr\n.global tick\n  JSR init\n  LDA ptr\n", false).unwrap();
+
+        assert_eq!(imports, vec![
+            Import { name: String::from("init"), zero_page: false },
+            Import { name: String::from("ptr"), zero_page: true },
+            Import { name: String::from("tick"), zero_page: false },
+        ]);
+    }
+
+    #[test]
+    fn test_auto_import() {
+        let source = ".import init\n  JSR init\n  JMP other\n  LDA other+1\n";
+        assert_eq!(link(source, false).unwrap_err(), vec![ParseError::UndeclaredSymbol { name: String::from("other") }]);
+
+        let (_, imports) = link(source, true).unwrap();
+        assert_eq!(imports, vec![
+            Import { name: String::from("init"), zero_page: false },
+            Import { name: String::from("other"), zero_page: false },
+        ]);
+    }
+
+    #[test]
+    fn test_linkage_errors() {
+        let errors = link(".import main\n.export missing\nmain:\n  RTS\n", false).unwrap_err();
+
+        assert_eq!(errors, vec![
+            ParseError::ImportedSymbolDefined { name: String::from("main") },
+            ParseError::ExportedSymbolUndefined { name: String::from("missing") },
+        ]);
+    }
+
+    #[test]
+    fn test_symbol_comments() {
+        let resolved = resolve("; Entry point\nmain:\n  NOP\nloop: ; spin forever\n  JMP loop\nSIZE = 4 ; bytes\n; not attached\n\nplain:\n  RTS\n");
+        let comments = symbol_comments(&resolved.lines, &resolved.label_map);
+
+        assert_eq!(comments.get("main").map(String::as_str), Some("Entry point"));
+        assert_eq!(comments.get("loop").map(String::as_str), Some("spin forever"));
+        assert_eq!(comments.get("SIZE").map(String::as_str), Some("bytes"));
+        assert_eq!(comments.get("plain"), None);
+    }
+
+    #[test]
+    fn test_fold_linked_names() {
+        let mut lines = scan(".export START\n.import Helper\n; Entry point\nStart:\n  JSR HELPER\nLoop:\n  JMP loop\n");
+        let contents = process_file(&mut lines, Path::new("test.asm"), Cpu::Mos6502, ObjectOptions::default()).unwrap().contents;
+
+        let names: Vec<(&str, Visibility)> = contents.symbols.iter().map(|symbol| (symbol.name.as_str(), symbol.visibility)).collect();
+        assert_eq!(names, vec![("Loop", Visibility::Local), ("start", Visibility::Export)]);
+        assert_eq!(contents.imports, vec![Import { name: String::from("helper"), zero_page: false }]);
+        assert_eq!(contents.segments[0].relocations[0].expression, ObjectExpression::Symbol(String::from("helper")));
+        assert_eq!(contents.comments.get("start").map(String::as_str), Some("Entry point"));
+    }
+}
@@ -1,17 +1,17 @@
-use std::{path::PathBuf, fs};
-
-use clap::{ValueEnum, Parser, command, Subcommand};
-use forge_lib::line::Line;
-use scanner::Scanner;
-use tracing::{metadata::LevelFilter, info, debug};
+use std::{fs, io::IsTerminal, path::{Path, PathBuf}};
+
+use clap::{ValueEnum, Parser, Subcommand};
+use forge_lib::{cpu::Cpu, line::Line, span::Span, write_object_file_to_contents};
+use assembler::{
+    analysis,
+    diagnostic::{Diagnostic, Diagnostics, Renderer},
+    process::{process_file, process_lines, ObjectOptions},
+    scanner::Scanner,
+    warning::{Warning, WarningFlag, WarningOptions},
+};
+use tracing::{metadata::LevelFilter, info};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
-use crate::process::{process_file, process_lines};
-
-mod scanner;
-mod error;
-mod process;
-
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum VerboseLevels {
     Trace,
@@ -21,6 +21,26 @@ enum VerboseLevels {
     Error,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ColorChoice {
+    /// Color diagnostics when printing to a terminal
+    Auto,
+    Always,
+    /// Plain diagnostics, such as for CI logs
+    Never,
+}
+
+impl ColorChoice {
+    /// Returns true if diagnostics should be colored. Auto follows the NO_COLOR convention
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => std::env::var_os("NO_COLOR").is_none() && std::io::stderr().is_terminal(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
 #[derive(Parser)]
 #[command(
     author = "Kyle Gagnon",
@@ -39,6 +59,31 @@ struct Cli {
     #[arg(short, long)]
     verbose: Option<VerboseLevels>,
 
+    /// The CPU to assemble for (6502, 6502X, 65C02, 65816, 2A03 or 2A03X). Can be changed in the source with .cpu
+    #[arg(long, default_value_t = Cpu::Mos6502)]
+    cpu: Cpu,
+
+    /// When to color diagnostics
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// The number of errors to stop after, or 0 to report every error
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    error_limit: usize,
+
+    /// Enables a warning, or disables it with a no- prefix (e.g. -Wno-shadowed-symbol). -Wall
+    /// enables every warning and -Werror reports warnings as errors
+    #[arg(short = 'W', value_name = "WARNING")]
+    warnings: Vec<WarningFlag>,
+
+    /// Treat labels and constants that differ only in case as different symbols
+    #[arg(long)]
+    case_sensitive_symbols: bool,
+
+    /// Import symbols that are used but neither defined nor imported, instead of reporting them
+    #[arg(long)]
+    auto_import: bool,
+
     #[command(subcommand)]
     command: Option<Commands>
 }
@@ -69,7 +114,21 @@ fn main() {
     info!("{:?}", cli.input);
 
     let file_contents = convert_file_to_string(&cli.input);
-    let mut parsed_file = parse_file(file_contents);
+    let renderer = Renderer::new(cli.input.display().to_string(), &file_contents, cli.color.enabled());
+    let mut diagnostics = Diagnostics::new(cli.error_limit);
+    let mut warnings = Vec::new();
+    let mut parsed_file = parse_file(&file_contents, &cli, &mut diagnostics, &mut warnings);
+
+    // The checks need the names in the operands, which are replaced with addresses once processed
+    warnings.extend(analysis::check_lines(&parsed_file, cli.cpu));
+
+    let mut warning_options = WarningOptions::new(&cli.warnings);
+    warning_options.add_pragmas(&parsed_file);
+    for (warning, span) in warnings {
+        if warning_options.is_enabled(warning.kind(), span) {
+            diagnostics.push(Diagnostic::from_warning(warning, span, warning_options.as_errors));
+        }
+    }
 
     // If there is something for the out_file then use that, otherwise just generate the same file but replace the file extension
     let output_file = match cli.output {
@@ -82,35 +141,62 @@ fn main() {
         }
     };
 
-    if cli.command.is_some() {
-        match cli.command.unwrap() {
-            Commands::Exe => {
-                let bytes = process_lines(&mut parsed_file);
+    // Lines with errors are left out, but the rest are still checked so every error is reported at once
+    let mut object = None;
+    if !diagnostics.limit_reached() {
+        let options = ObjectOptions { case_sensitive_symbols: cli.case_sensitive_symbols, auto_import: cli.auto_import };
+        let result = match cli.command {
+            Some(Commands::Exe) => process_lines(&mut parsed_file, cli.cpu).map(|_| None),
+            None => process_file(&mut parsed_file, &cli.input, cli.cpu, options).map(Some),
+        };
+
+        match result {
+            Ok(out_file) => object = out_file,
+            Err(errors) => {
+                for error in errors {
+                    diagnostics.push(Diagnostic::from(error));
+                }
             }
         }
     }
 
-    //let _ = process_file(&mut parsed_file, &cli.input, &output_file);
+    diagnostics.sort();
+    eprint!("{}", diagnostics.render(&renderer));
+
+    if diagnostics.has_errors() {
+        std::process::exit(1);
+    }
+
+    // Only written once nothing went wrong, so a failed build never leaves an object file behind that
+    // was assembled without the lines that had errors
+    if let Some(object) = object {
+        write_object_file_to_contents(object, &output_file);
+    }
 }
 
-fn convert_file_to_string(file_path: &PathBuf) -> String {
+fn convert_file_to_string(file_path: &Path) -> String {
     fs::read_to_string(file_path).unwrap()
 }
 
-fn parse_file(file_contents: String) -> Vec<Line> {
-    let mut scanner = Scanner::new(&file_contents);
+/// Parses every line of the file. Lines with errors are skipped after the error is recorded, and any
+/// warnings found while parsing are added to the warnings
+fn parse_file(file_contents: &str, cli: &Cli, diagnostics: &mut Diagnostics, warnings: &mut Vec<(Warning, Span)>) -> Vec<Line> {
+    let mut scanner = Scanner::new(file_contents);
+    scanner.cpu = cli.cpu;
+    scanner.case_sensitive_symbols = cli.case_sensitive_symbols;
     let mut line_list = Vec::new();
-    while !scanner.is_done() {
-        let line = match scanner.line() {
-            Ok(line) => line,
+    while !scanner.is_done() && !diagnostics.limit_reached() {
+        match scanner.next_line() {
+            Ok(line) => line_list.push(line),
             Err(e) => {
-                eprintln!("{} at line {}", e, scanner.lines + 1);
-                std::process::exit(1);
+                diagnostics.push(Diagnostic::from(e));
+                scanner.recover();
             }
         };
-        line_list.push(line);
     }
 
+    warnings.append(&mut scanner.warnings);
+
     line_list
 }
 
@@ -1,219 +1,468 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, path::Path};
 
-use chrono::Utc;
-use forge_lib::{line::{Line, MainComponent, Labels}, object::{OutFile, Header, Contents}, write_object_file_to_contents, label::LabelMetaData, directive::{Directive, ByteArgs, WordArgs}, expression::evaluate_expression, operand::Operand, address::{AddressMode, AddressModeGeneric}, mnemonic::OPCODES_TO_BYTES};
+use forge_lib::{
+    address::{AddressMode, AddressSize},
+    cpu::{Cpu, CpuState},
+    directive::Directive,
+    error::ForgeError,
+    expression::BinaryOp,
+    instruction::Instruction,
+    label::{anonymous_label_index, anonymous_label_name, local_label_name, LabelMetaData},
+    line::{Labels, Line, MainComponent},
+    object::{Contents, Header, ObjectExpression, OutFile, CODE_SEGMENT, ZEROPAGE_SEGMENT},
+    operand::Operand,
+};
 use tracing::debug;
 
-use crate::error::ParseError;
+use crate::{
+    codegen::{generate, SymbolResolver},
+    linkage::{fold_linked_names, symbol_comments, Linkage},
+    error::{ParseError, SpannedError},
+};
 
-pub fn process_file(lines: &mut Vec<Line>, file_name: &PathBuf, out_file: &PathBuf) -> Result<(), ParseError> {
+/// How the symbols a file shares with other files are written to its object file
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ObjectOptions {
+    /// Unless set, the names shared with other files are written in lowercase so they match however
+    /// other files spell them
+    pub case_sensitive_symbols: bool,
+    /// Imports names that are used but not defined, instead of reporting them
+    pub auto_import: bool,
+}
+
+/// Resolves the labels and constants in the lines and assembles them into an object file. Every error
+/// found is returned. Writing the file is left to the caller, which may have found errors of its own
+pub fn process_file(lines: &mut [Line], file_name: &Path, cpu: Cpu, options: ObjectOptions) -> Result<OutFile, Vec<SpannedError>> {
     let mut constant_map: HashMap<String, u16> = HashMap::new();
     let mut label_map: HashMap<String, LabelMetaData> = HashMap::new();
-    let _starting_address: u16 = 0;
-    let mut offset_tracker: u16 = 0;
-    let _line_num: u16 = 1;
-
-    // Go through and resolve all constants and labels
-    for line in lines.iter() {
-        resolve_labels_and_constants(
-            line,
-            &mut constant_map,
-            &mut label_map,
-            &mut offset_tracker,
-        );
 
+    // Each stage still runs when an earlier one found errors, so they are all reported at once
+    let mut errors = Vec::new();
+    let linkage = Linkage::new(lines);
+    if let Err(resolve_errors) = resolve_lines(lines, &mut constant_map, &mut label_map, &linkage, cpu) {
+        add_errors(&mut errors, resolve_errors);
     }
+    let (segments, generate_errors) = generate(lines, &constant_map, &label_map, cpu);
+    add_errors(&mut errors, generate_errors);
+    let linked = linkage.symbols_and_imports(&constant_map, &label_map, &segments, options.auto_import);
 
-    // Now serialize the out file
-    let data = OutFile {
-        header: Header {
-            magic_number: String::from("rust6502forge"),
-            timestamp: Utc::now(),
-            version: semver::Version::new(0, 1, 0),
-            file_name: file_name.to_str().unwrap().to_string()
-        },
-        contents: Contents {
-            label_map,
-            constant_map,
-            parsed_contents: lines.to_vec()
+    let (symbols, imports) = match linked {
+        Ok(linked) if errors.is_empty() => linked,
+        Ok(_) => return Err(errors),
+        Err(linkage_errors) => {
+            add_errors(&mut errors, linkage_errors);
+            return Err(errors);
         }
     };
+    let comments = symbol_comments(lines, &label_map);
+
+    let mut contents = Contents { segments, symbols, imports, comments };
+    if !options.case_sensitive_symbols {
+        fold_linked_names(&mut contents);
+    }
+
+    // Now serialize the out file
+    let data = OutFile { header: Header::new(file_name.to_str().unwrap()), contents };
 
     debug!("{:?}", data);
 
-    write_object_file_to_contents(data, out_file);
+    Ok(data)
+}
 
-    Ok(())
+/// Adds the errors from a stage to the ones found so far. A statement an earlier stage already
+/// reported an error for is usually reported again by the later ones, so those are left out
+fn add_errors(errors: &mut Vec<SpannedError>, found: Vec<SpannedError>) {
+    let found: Vec<SpannedError> = found.into_iter().filter(|error| !errors.iter().any(|seen| seen.span == error.span)).collect();
+    errors.extend(found);
+}
+
+/// Goes through the lines working out the value of every label and constant. Operands using a
+/// constant that is already known are replaced by its value, so the instruction is sized the same
+/// way it is assembled
+pub fn resolve_lines(
+    lines: &mut [Line],
+    constant_map: &mut HashMap<String, u16>,
+    label_map: &mut HashMap<String, LabelMetaData>,
+    linkage: &Linkage,
+    cpu: Cpu,
+) -> Result<(), Vec<SpannedError>> {
+    let mut resolve_state = ResolveState::new(lines, cpu);
+    let mut errors = Vec::new();
+
+    for line in lines.iter_mut() {
+        if let Err(error) = resolve_labels_and_constants(line, constant_map, label_map, &mut resolve_state, linkage) {
+            errors.push(error);
+        }
+    }
+
+    debug!("{:?}", label_map);
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(errors),
+    }
+}
+
+/// The location counter of each segment, in the order the segments are first used. Code goes in the
+/// CODE segment until another one is chosen
+pub struct LocationCounters {
+    counters: Vec<(String, u16)>,
+    /// The index of the segment being assembled into
+    current: usize,
+}
+
+impl LocationCounters {
+    pub fn new() -> Self {
+        Self { counters: vec![(String::from(CODE_SEGMENT), 0)], current: 0 }
+    }
+
+    /// Carries on from where the segment with the given name was left, starting it if it is new
+    pub fn switch(&mut self, name: &str) {
+        self.current = match self.index(name) {
+            Some(index) => index,
+            None => {
+                self.counters.push((name.to_string(), 0));
+                self.counters.len() - 1
+            }
+        };
+    }
+
+    /// Returns the index of the segment with the given name, if it has been used yet
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.counters.iter().position(|(segment, _)| segment == name)
+    }
+
+    /// The index of the segment being assembled into
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// The offset of the next byte from the start of the current segment
+    pub fn offset(&self) -> u16 {
+        self.counters[self.current].1
+    }
+
+    pub fn advance(&mut self, size: u16) {
+        self.counters[self.current].1 += size;
+    }
+}
+
+impl Default for LocationCounters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Numbers the anonymous labels in the order they are defined, so references to them can be given
+/// the name of the label they point to
+pub struct AnonymousLabels {
+    /// The number defined so far
+    defined: u16,
+    /// The number in the whole file
+    total: u16,
+}
+
+impl AnonymousLabels {
+    pub fn new(lines: &[Line]) -> Self {
+        let total = lines.iter().filter(|line| line.label == Some(Labels::Anonymous)).count();
+        Self { defined: 0, total: total as u16 }
+    }
+}
+
+/// What is kept track of from one line to the next while resolving the labels and constants
+pub struct ResolveState {
+    pub counters: LocationCounters,
+    pub cpu: CpuState,
+    pub anonymous: AnonymousLabels,
+    /// The global label local labels are stored under
+    pub scope: String,
+}
+
+impl ResolveState {
+    pub fn new(lines: &[Line], cpu: Cpu) -> Self {
+        Self {
+            counters: LocationCounters::new(),
+            cpu: CpuState::new(cpu),
+            anonymous: AnonymousLabels::new(lines),
+            scope: String::new(),
+        }
+    }
 }
 
 pub fn resolve_labels_and_constants(
-    line: &Line,
+    line: &mut Line,
     constant_map: &mut HashMap<String, u16>,
     label_map: &mut HashMap<String, LabelMetaData>,
-    offset_tracker: &mut u16,
-) {
+    resolve_state: &mut ResolveState,
+    linkage: &Linkage,
+) -> Result<(), SpannedError> {
+    let ResolveState { counters, cpu: state, anonymous, scope } = resolve_state;
+    let span = line.label_span().unwrap_or(line.span);
+    let mut result = Ok(());
+
     // Check if there is a constant
-    let line = line.clone();
-    if line.constant.is_some() {
-        let (constant, value) = line.constant.unwrap();
-        constant_map.insert(constant, value);
+    if let Some((constant, value)) = &line.constant {
+        constant_map.insert(constant.clone(), *value);
     }
 
     // If there is a label, then check where we are and insert it
-    if line.label.is_some() {
-        let (is_local, label) = match line.label.unwrap() {
+    if let Some(label) = &line.label {
+        let (is_local, label) = match label {
             Labels::Label(label) => {
-                (false, label)
+                *scope = label.clone();
+                (false, label.clone())
             }
             Labels::LocalLabel(label) => {
-                (true, label)
+                (true, local_label_name(scope, label))
+            }
+            Labels::Anonymous => {
+                anonymous.defined += 1;
+                (true, anonymous_label_name(anonymous.defined - 1))
             }
         };
 
-        label_map.insert(label.clone(), LabelMetaData { offset: offset_tracker.clone(), is_local });
+        // Local labels are stored under the global label they follow, so they only have to be unique
+        // there. The first definition is kept and the line is still sized so later labels are right
+        match label_map.get(&label) {
+            Some(first) => {
+                let error = ParseError::DuplicateLabel { name: label, first: first.span };
+                result = Err(SpannedError::new(error, span));
+            }
+            None => {
+                label_map.insert(label.clone(), LabelMetaData { segment: counters.current, offset: counters.offset(), is_local, span });
+            }
+        }
     }
 
-    // Now get the size of either the directive or instruction
-    if line.main_component.is_some() {
-        let main_component = line.main_component.unwrap();
-        match main_component {
-            MainComponent::Directive(directive) => {
-                *offset_tracker += directive.size() as u16;
+    // A reference to an anonymous label becomes a reference to the label by name. An anonymous label on
+    // the same line comes before the instruction, so `: BNE :-` branches to itself
+    for main_component in &mut line.main_components {
+        let MainComponent::Instruction(instruction) = main_component else { continue };
+        let Some(Operand::AnonymousLabel(offset)) = instruction.operand else { continue };
+
+        match anonymous_label_index(anonymous.defined, anonymous.total, offset) {
+            Some(index) => {
+                let address_mode = AddressMode::ZeroPageOrAbsoluteIdent(anonymous_label_name(index));
+                instruction.operand = Some(Operand::AddressMode(address_mode));
             }
-            MainComponent::Instruction(instruction) => {
-                *offset_tracker += instruction.size() as u16;
+            None => {
+                let error = SpannedError::new(ParseError::AnonymousLabelNotFound { offset }, instruction.span);
+                result = result.and(Err(error));
+            }
+        }
+    }
+
+    // Now get the size of each directive and instruction
+    for main_component in &mut line.main_components {
+        if let MainComponent::Instruction(instruction) = main_component {
+            if let Err(error) = fold_constant_operand(instruction, constant_map, label_map, counters.index(ZEROPAGE_SEGMENT), linkage) {
+                result = result.and(Err(SpannedError::new(ParseError::Assembly { error }, instruction.span)));
             }
         }
+
+        match main_component {
+            MainComponent::Directive(Directive::SEGMENT(name), _) => counters.switch(name),
+            MainComponent::Directive(directive, _) => counters.advance(directive.size() as u16),
+            MainComponent::Instruction(instruction) => counters.advance(instruction.size(state) as u16),
+        }
+
+        // The size of later instructions can depend on this one (e.g. REP #$20 on the 65816)
+        state.update(main_component);
     }
+
+    result
 }
 
-pub fn process_lines(lines: &mut Vec<Line>) -> Vec<u8> {
+/// Assembles the lines into a final executable. Every error found is returned rather than just the first
+pub fn process_lines(lines: &mut [Line], cpu: Cpu) -> Result<Vec<u8>, Vec<SpannedError>> {
     let mut constant_map: HashMap<String, u16> = HashMap::new();
     let mut label_map: HashMap<String, LabelMetaData> = HashMap::new();
-    let mut starting_address: u16 = 0;
-    let mut offset_tracker: u16 = 0;
-    let _line_num: u16 = 1;
 
-    // Go through and resolve all constants and labels
-    for line in lines.iter() {
-        resolve_labels_and_constants(
-            line,
-            &mut constant_map,
-            &mut label_map,
-            &mut offset_tracker,
-        );
+    let mut errors = Vec::new();
+    let linkage = Linkage::new(lines);
+    if let Err(resolve_errors) = resolve_lines(lines, &mut constant_map, &mut label_map, &linkage, cpu) {
+        add_errors(&mut errors, resolve_errors);
+    }
+    let (segments, generate_errors) = generate(lines, &constant_map, &label_map, cpu);
+    add_errors(&mut errors, generate_errors);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    // Without a linker to place them, the segments are put one after another
+    Ok(segments.into_iter().flat_map(|segment| segment.bytes).collect())
+}
+
+/// Replaces an operand using constants that are already known with the address mode for its value,
+/// so a constant that fits in a byte is given zero page addressing. An operand using a label already
+/// defined in the zero page segment or a symbol imported with `.importzp` is given a zero page address
+/// size, since its value is only known once the segments are placed
+fn fold_constant_operand(
+    instruction: &mut Instruction,
+    constant_map: &HashMap<String, u16>,
+    label_map: &HashMap<String, LabelMetaData>,
+    zero_page_segment: Option<usize>,
+    linkage: &Linkage,
+) -> Result<(), ForgeError> {
+    let symbols = SymbolResolver { constant_map, label_map };
+    let constant = |name: &String| match symbols.symbol(name) {
+        ObjectExpression::Number(value) => Some(value as u16),
+        _ => None,
+    };
+
+    let address_mode = match &instruction.operand {
+        Some(Operand::Expression(expression)) => symbols.constant_value(expression).map(|value| match value {
+            0..=0xFF => AddressMode::ZeroPage(value as u8),
+            _ => AddressMode::Absolute(value),
+        }),
+        Some(Operand::AddressMode(address_mode)) => match address_mode {
+            AddressMode::ZeroPageOrAbsoluteIdent(name) => constant(name).map(|value| match value {
+                0..=0xFF => AddressMode::ZeroPage(value as u8),
+                _ => AddressMode::Absolute(value),
+            }),
+            AddressMode::ZeroPageOrAbsoluteXIdent(name) => constant(name).map(|value| match value {
+                0..=0xFF => AddressMode::ZeroPageX(value as u8),
+                _ => AddressMode::AbsoluteX(value),
+            }),
+            AddressMode::ZeroPageOrAbsoluteYIdent(name) => constant(name).map(|value| match value {
+                0..=0xFF => AddressMode::ZeroPageY(value as u8),
+                _ => AddressMode::AbsoluteY(value),
+            }),
+            AddressMode::IndexedIndirectXIdent(name) => constant(name).map(|value| match value {
+                0..=0xFF => AddressMode::IndexedIndirectX(value as u8),
+                _ => AddressMode::AbsoluteIndexedIndirect(value),
+            }),
+            // There is no 16-bit form of (ptr),Y, so a larger value is left to fail when assembled
+            AddressMode::IndirectIndexYIdent(name) => constant(name)
+                .filter(|value| *value <= 0xFF)
+                .map(|value| AddressMode::IndirectIndexY(value as u8)),
+            AddressMode::IndirectIdent(name) => constant(name).map(|value| match value {
+                0..=0xFF => AddressMode::ZeroPageIndirect(value as u8),
+                _ => AddressMode::Indirect(value),
+            }),
+            AddressMode::ImmediateIdent(name) => constant(name).map(|value| match value {
+                0..=0xFF => AddressMode::Immediate(value as u8),
+                _ => AddressMode::ImmediateWord(value),
+            }),
+            AddressMode::ImmediateExpression(expression) => symbols.constant_value(expression).map(|value| match value {
+                0..=0xFF => AddressMode::Immediate(value as u8),
+                _ => AddressMode::ImmediateWord(value),
+            }),
+            _ => None,
+        },
+        _ => None,
+    };
 
+    if let Some(address_mode) = address_mode {
+        let address_mode = match instruction.address_size {
+            Some(size) => address_mode.with_size(size)?,
+            None => address_mode,
+        };
+        instruction.operand = Some(Operand::AddressMode(address_mode));
+        return Ok(());
     }
 
-    debug!("{:?}", label_map);
+    if instruction.address_size.is_some() {
+        return Ok(());
+    }
 
-    for line in lines.iter_mut() {
-        let _result = resolve_expressions(line, &mut constant_map, &mut label_map);
-    }
-
-    for line in lines.iter() {
-        match line.main_component.clone() {
-            Some(component) => {
-                match component {
-                    MainComponent::Directive(directive) => {
-
-                    }
-                    MainComponent::Instruction(instruction) => {
-                        let gen_operand = if instruction.operand.is_some() {
-                            match instruction.operand.unwrap() {
-                                Operand::Expression(expression) => {
-                                    let value = evaluate_expression(&expression, &constant_map);
-                                    if value <= 0xFF {
-                                        AddressModeGeneric::ZeroPage
-                                    } else {
-                                        AddressModeGeneric::Absolute
-                                    }
-                                }
-                                Operand::LocalLabel(_) => {
-                                    AddressModeGeneric::Absolute
-                                }
-                                Operand::AddressMode(addr_mode) => {
-                                    addr_mode.to_generic(&label_map, &constant_map).unwrap()
-                                }
-                            }
-                        } else {
-                            AddressModeGeneric::Implied
-                        };
-
-                        let opcode = OPCODES_TO_BYTES.get(&(instruction.mnemonic, gen_operand.clone()));
-                        debug!("({:?}, {:?}): {:?}", instruction.mnemonic, gen_operand, opcode);
-                    }
-                }
-            }
-            _ => {}
-        }
+    let address = match &instruction.operand {
+        Some(Operand::Expression(expression)) => symbols.expression(expression),
+        Some(Operand::AddressMode(
+            AddressMode::ZeroPageOrAbsoluteIdent(name)
+            | AddressMode::ZeroPageOrAbsoluteXIdent(name)
+            | AddressMode::ZeroPageOrAbsoluteYIdent(name)
+            | AddressMode::IndirectIdent(name),
+        )) => symbols.symbol(name),
+        _ => return Ok(()),
+    };
+
+    if in_zero_page(&address, zero_page_segment, linkage) {
+        instruction.address_size = Some(AddressSize::ZeroPage);
     }
 
-    vec![0x00]
+    Ok(())
 }
 
-pub fn resolve_expressions(line: &mut Line, constant_map: &mut HashMap<String, u16>, _label_map: &mut HashMap<String, LabelMetaData>) -> Result<(), ParseError> {
-    // Expressions could be found at operands or directives
-    if let Some(main_component) = &mut line.main_component {
-        match main_component {
-            MainComponent::Directive(directive) => {
-                match directive {
-                    Directive::BYTE(args_list) => {
-                        for arg in args_list.iter_mut() {
-                            let taken_arg = std::mem::take(arg);
-                            match taken_arg {
-                                ByteArgs::Expression(expression) => {
-                                    debug!("Found an expression in a BYTE directive. Should update it");
-                                    let value = evaluate_expression(&expression, constant_map);
-                                    if value <= 0xFF {
-                                        *arg = ByteArgs::Value(value as u8);
-                                    } else {
-                                        return Err(ParseError::ValueTooLarge)
-                                    }
-                                }
-                                _ => {
-                                    *arg = taken_arg;
-                                }
-                            };
-                        }
-                    }
-                    Directive::WORD(args_list) => {
-                        for arg in args_list {
-                            let taken_arg = std::mem::take(arg);
-                            match taken_arg {
-                                WordArgs::Expression(expr) => {
-                                    let value = evaluate_expression(&expr, constant_map);
-                                    *arg = WordArgs::Value(value);
-                                }
-                                _ => {
-                                    *arg = taken_arg;
-                                }
-                            };
-                        }
-                    }
-                    _ => {}
-                }
-            },
-            MainComponent::Instruction(instruction) => {
-                if let Some(operand) = &instruction.operand {
-                    match operand {
-                        Operand::Expression(expression) => {
-                            let value = evaluate_expression(&expression, constant_map);
-                            // Here we could look at the value and determine whether or not to use absolute, for now, assume absolute
-                            let address_mode = if value <= 0xFF {
-                                AddressMode::ZeroPage(value as u8)
-                            } else {
-                                AddressMode::Absolute(value)
-                            };
-
-                            instruction.operand = Some(Operand::AddressMode(address_mode));
-                        }
-                        _ => {}
-                    }
-                }
-            }
-        }
+/// Returns true if the value is a zero page address from a label or import, such as `var` or `var + 1`
+fn in_zero_page(expression: &ObjectExpression, zero_page_segment: Option<usize>, linkage: &Linkage) -> bool {
+    match expression {
+        ObjectExpression::SegmentOffset { segment, .. } => Some(*segment) == zero_page_segment,
+        ObjectExpression::Symbol(name) => linkage.is_zero_page_import(name),
+        ObjectExpression::Binary(op, left, right) => match (op, &**left, &**right) {
+            (BinaryOp::Add, ObjectExpression::Number(_), other)
+            | (BinaryOp::Add | BinaryOp::Subtract, other, ObjectExpression::Number(_)) => in_zero_page(other, zero_page_segment, linkage),
+            _ => false,
+        },
+        _ => false,
     }
+}
 
-    Ok(())
-}
\ No newline at end of file
+/// The lines of a source file once their labels and constants are resolved, for the tests of the
+/// passes that come after
+#[cfg(test)]
+pub(crate) struct Resolved {
+    pub lines: Vec<Line>,
+    pub linkage: Linkage,
+    pub constant_map: HashMap<String, u16>,
+    pub label_map: HashMap<String, LabelMetaData>,
+}
+
+/// Parses the source, which has to have no errors in it
+#[cfg(test)]
+pub(crate) fn scan(source: &str) -> Vec<Line> {
+    let mut scanner = crate::scanner::Scanner::new(source);
+    let mut lines = Vec::new();
+    while !scanner.is_done() {
+        lines.push(scanner.next_line().unwrap());
+    }
+
+    lines
+}
+
+/// Parses the source and resolves its labels and constants for the 6502
+#[cfg(test)]
+pub(crate) fn resolve(source: &str) -> Resolved {
+    let mut lines = scan(source);
+    let linkage = Linkage::new(&lines);
+    let mut constant_map = HashMap::new();
+    let mut label_map = HashMap::new();
+    resolve_lines(&mut lines, &mut constant_map, &mut label_map, &linkage, Cpu::Mos6502).unwrap();
+
+    Resolved { lines, linkage, constant_map, label_map }
+}
+
+#[cfg(test)]
+mod process_tests {
+    use std::path::Path;
+
+    use forge_lib::cpu::Cpu;
+
+    use crate::error::SpannedError;
+
+    use super::{process_file, process_lines, scan, ObjectOptions};
+
+    /// A duplicate label found while resolving, an immediate found too large by codegen and a name
+    /// found undefined by the linkage checks
+    const SOURCE: &str = "main:\n  NOP\nmain:\n  LDA #$1234\n  JMP nowhere\n";
+
+    fn messages(errors: Vec<SpannedError>) -> Vec<String> {
+        errors.into_iter().map(|error| error.to_string()).collect()
+    }
+
+    #[test]
+    fn test_errors_from_every_stage() {
+        let errors = process_file(&mut scan(SOURCE), Path::new("test.asm"), Cpu::Mos6502, ObjectOptions::default()).unwrap_err();
+        assert_eq!(messages(errors), vec![
+            "3:1: Label main is defined more than once",
+            "4:3: LDA only takes an 8-bit immediate on the 6502",
+            "5:3: nowhere is not defined in this file. Import it with .import or assemble with --auto-import",
+        ]);
+
+        let errors = process_lines(&mut scan(SOURCE), Cpu::Mos6502).unwrap_err();
+        assert_eq!(messages(errors), vec![
+            "3:1: Label main is defined more than once",
+            "4:3: LDA only takes an 8-bit immediate on the 6502",
+        ]);
+    }
+}
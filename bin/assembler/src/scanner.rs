@@ -1,8 +1,8 @@
-use std::fmt;
+use std::{collections::HashMap, fmt};
 
-use forge_lib::{address::AddressMode, mnemonic::Mnemonic, operand::Operand, instruction::Instruction, directive::{DirectiveName, Directive}};
+use forge_lib::{line::Line, address::AddressMode, mnemonic::Mnemonic, operand::Operand, instruction::Instruction, directive::{DirectiveName, Directive}, cpu::Cpu, span::Span};
 
-use crate::error::ParseError;
+use crate::{error::{ParseError, SpannedError}, lexer::{Lexeme, Lexer, TokenKind}, warning::Warning};
 
 pub mod address;
 pub mod directive;
@@ -13,10 +13,31 @@ pub mod mnemonic;
 
 type TokenResult = Result<Option<Token>, ParseError>;
 
-pub struct Scanner {
-    input: Vec<char>,
+/// Parses the source a line at a time. The lexer is only run over the line being parsed, so the
+/// parsers can backtrack over its tokens without the whole file being held as tokens
+pub struct Scanner<'a> {
+    source: &'a str,
+    lexer: Lexer<'a>,
+    /// The tokens of the current line, along with the newlines that end it
+    tokens: Vec<Lexeme>,
+    /// The first token of the next line, which had to be read to find the end of the current one
+    pending: Option<Lexeme>,
+    /// The index of the next token in the current line
     cursor: usize,
     pub lines: u32,
+    /// The CPU instructions are currently being checked against. Updated by the `.cpu` directive
+    pub cpu: Cpu,
+    /// Warnings found so far along with where they were found
+    pub warnings: Vec<(Warning, Span)>,
+    /// Whether an SED has been seen without a CLD after it
+    decimal_flag: bool,
+    /// Whether labels and constants that differ only in case are different symbols
+    pub case_sensitive_symbols: bool,
+    /// The first spelling of each symbol seen, keyed by its name in lower case. Symbols are given
+    /// that spelling so they match however they are written
+    symbol_spellings: HashMap<String, String>,
+    /// The tokens the error being returned is about, for errors about more than the token at one position
+    error_span: Option<Span>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -25,8 +46,10 @@ pub enum Token {
     Mnemonic(Mnemonic),
     Newline,
     Whitespace,
+    AddressU24(u32),
     AddressU16(u16),
     AddressU8(u8),
+    LiteralU16(u16),
     LiteralU8(u8),
     AddressMode(AddressMode),
     Operand(Operand),
@@ -34,6 +57,7 @@ pub enum Token {
     Identifier(String),
     Label(String),
     LocalLabel(String),
+    AnonymousLabel,
     Constant(String, u16),
     DirectiveName(DirectiveName),
     Directive(Directive),
@@ -46,7 +70,7 @@ impl fmt::Display for Token {
                 write!(f, "Comment: {}", string)
             }
             Token::Mnemonic(mnemonic) => {
-                write!(f, "Mnemonic: {}", mnemonic.to_string())
+                write!(f, "Mnemonic: {}", mnemonic)
             }
             Token::Newline => {
                 write!(f, "Newline token")
@@ -54,12 +78,18 @@ impl fmt::Display for Token {
             Token::Whitespace => {
                 write!(f, "Whitespace token")
             }
+            Token::AddressU24(val) => {
+                write!(f, "U24 Address: ${:06X}", val)
+            }
             Token::AddressU16(val) => {
                 write!(f, "U16 Address: ${:04X}", val)
             }
             Token::AddressU8(val) => {
                 write!(f, "U8 Address: ${:02X}", val)
             }
+            Token::LiteralU16(val) => {
+                write!(f, "U16 Literal: #${:04X}", val)
+            }
             Token::LiteralU8(val) => {
                 write!(f, "U8 Literal: #${:02X}", val)
             }
@@ -78,8 +108,10 @@ impl Token {
         match self {
             Token::Mnemonic(_) => "Mnemonic",
             Token::AddressMode(_) => "Address Mode",
+            Token::AddressU24(_) => "U24 Address",
             Token::AddressU16(_) => "U16 Address",
             Token::AddressU8(_) => "U8 Address",
+            Token::LiteralU16(_) => "Literal U16",
             Token::LiteralU8(_) => "Literal U8",
             Token::Comment(_) => "Comment",
             Token::Whitespace => "Whitespace",
@@ -92,16 +124,103 @@ impl Token {
             Token::DirectiveName(_) => "Directive Name",
             Token::Directive(_) => "Directive",
             Token::LocalLabel(_) => "Local Label",
+            Token::AnonymousLabel => "Anonymous Label",
         }
     }
 }
 
-impl Scanner {
-    pub fn new(input: &str) -> Self {
-        Self {
-            input: input.chars().collect(),
+impl<'a> Scanner<'a> {
+    pub fn new(input: &'a str) -> Self {
+        let mut scanner = Self {
+            source: input,
+            lexer: Lexer::new(input, 0),
+            tokens: Vec::new(),
+            pending: None,
             cursor: 0,
             lines: 0,
+            cpu: Cpu::default(),
+            warnings: Vec::new(),
+            decimal_flag: false,
+            case_sensitive_symbols: false,
+            symbol_spellings: HashMap::new(),
+            error_span: None,
+        };
+        scanner.load_line();
+
+        scanner
+    }
+
+    /// Reads the tokens of the next line from the lexer. Any empty lines straight after it are
+    /// included, since they are counted as part of the line
+    fn load_line(&mut self) {
+        self.tokens.clear();
+        self.cursor = 0;
+
+        while let Some(lexeme) = self.pending.take().or_else(|| self.lexer.next()) {
+            let ends_line = lexeme.kind == TokenKind::Newline;
+            self.tokens.push(lexeme);
+            if ends_line {
+                break;
+            }
+        }
+
+        for lexeme in self.lexer.by_ref() {
+            if lexeme.kind != TokenKind::Newline || lexeme.spaced {
+                self.pending = Some(lexeme);
+                break;
+            }
+            self.tokens.push(lexeme);
+        }
+    }
+
+    /// Parses the next line. Any error is given the span it was found at, which is either the
+    /// tokens recorded with error_from, the position the error recorded or wherever the cursor stopped
+    pub fn next_line(&mut self) -> Result<Line, SpannedError> {
+        self.error_span = None;
+        self.line().map_err(|error| {
+            let span = match (self.error_span.take(), &error) {
+                (Some(span), _) => span,
+                (None, ParseError::ExpectedNewline) => self.rest_of_line_span(),
+                (None, _) => self.span_at(error.position().unwrap_or(self.cursor)),
+            };
+            SpannedError::new(error, span)
+        })
+    }
+
+    /// Records that the error is about the tokens from the given position up to the cursor, such as a
+    /// whole operand or argument, and returns it
+    pub fn error_from(&mut self, start: usize, error: ParseError) -> ParseError {
+        self.error_span = Some(self.span_from(start));
+        error
+    }
+
+    /// Returns the span of the tokens left on the line before any comment, which is what could not
+    /// be parsed when a newline was expected
+    fn rest_of_line_span(&self) -> Span {
+        let rest = &self.tokens[self.cursor.min(self.tokens.len())..];
+        let length = rest
+            .iter()
+            .position(|lexeme| matches!(lexeme.kind, TokenKind::Comment | TokenKind::Newline))
+            .unwrap_or(rest.len());
+
+        match (rest[..length].first(), rest[..length].last()) {
+            (Some(first), Some(last)) => first.span.join(last.span),
+            _ => self.span_at(self.cursor),
+        }
+    }
+
+    /// Skips the rest of the line an error was found on so parsing can carry on from the next one
+    pub fn recover(&mut self) {
+        while let Some(lexeme) = self.tokens.get(self.cursor) {
+            self.cursor += 1;
+            if lexeme.kind == TokenKind::Newline {
+                self.lines += 1;
+                break;
+            }
+        }
+
+        if self.cursor == self.tokens.len() {
+            self.load_line();
         }
     }
 
@@ -124,15 +243,49 @@ impl Scanner {
         self.cursor
     }
 
-    /// Returns the next character without advancing the cursor
+    /// Returns the span from the token at the given position up to the cursor
+    pub fn span_from(&self, start: usize) -> Span {
+        match (self.tokens.get(start), self.cursor > start) {
+            (Some(first), true) => first.span.join(self.tokens[self.cursor - 1].span),
+            _ => Span { length: 0, ..self.span_at(start) },
+        }
+    }
+
+    /// Returns the span of the token at the given position. Used for errors, where there is no node
+    /// to take the span from. Past the last token this is the empty span at the end of the line
+    pub fn span_at(&self, position: usize) -> Span {
+        match (self.tokens.get(position), self.tokens.last()) {
+            (Some(lexeme), _) if lexeme.kind != TokenKind::Newline => lexeme.span,
+            (Some(lexeme), _) => Span { length: 0, ..lexeme.span },
+            (None, Some(last)) => Span { column: last.span.column + last.span.length, length: 0, ..last.span },
+            (None, None) => Span::new(0, self.lines + 1, 1, 0),
+        }
+    }
+
+    /// Returns the next token without advancing the cursor
     /// AKA "Lookahead"
-    pub fn peek(&self) -> Option<char> {
-        self.input.get(self.cursor).cloned()
+    pub fn peek(&self) -> Option<&Lexeme> {
+        self.tokens.get(self.cursor)
+    }
+
+    /// Returns the kind of the next token, if there is one
+    pub fn peek_kind(&self) -> Option<TokenKind> {
+        self.peek().map(|lexeme| lexeme.kind)
+    }
+
+    /// Returns the text of the next token, or an empty string at the end of the input
+    pub fn peek_text(&self) -> &'a str {
+        self.peek().map_or("", |lexeme| self.text(lexeme))
+    }
+
+    /// Returns the source text a token was read from
+    pub fn text(&self, lexeme: &Lexeme) -> &'a str {
+        &self.source[lexeme.start..lexeme.end]
     }
 
     /// Returns true if further progress is not possible.
     pub fn is_done(&self) -> bool {
-        self.cursor == self.input.len()
+        self.cursor == self.tokens.len() && self.pending.is_none()
     }
 
     /// Moves the cursor to the next position
@@ -140,10 +293,10 @@ impl Scanner {
         self.cursor += 1;
     }
 
-    /// Checks if the next character is the given character. If so it will consume
-    /// the character and return true. Otherwise return false
-    pub fn consume_char(&mut self, c: char) -> bool {
-        if self.peek() == Some(c) {
+    /// Checks if the next token is of the given kind. If so it will consume the token and return
+    /// true. Otherwise return false
+    pub fn consume(&mut self, kind: TokenKind) -> bool {
+        if self.peek_kind() == Some(kind) {
             self.next();
             true
         } else {
@@ -151,35 +304,25 @@ impl Scanner {
         }
     }
 
-    pub fn peek_chars(&self, s: &str) -> bool {
-        let end_pos = self.cursor + s.len();
-
-        // First check if the end position is in bounds
-        if end_pos > self.input.len() {
-            return false;
+    /// Like consume, but only if there is no whitespace before the token
+    pub fn consume_adjacent(&mut self, kind: TokenKind) -> bool {
+        match self.peek() {
+            Some(lexeme) if lexeme.kind == kind && !lexeme.spaced => {
+                self.next();
+                true
+            }
+            _ => false,
         }
-
-        // Check if the substring of the input from cursor to end_pos
-        // matches the string s
-        let upcoming_chars: String = self.input[self.cursor..end_pos].iter().collect();
-
-        &upcoming_chars == s
     }
 
-    pub fn consume_chars(&mut self, num: usize) -> bool {
-        let end_pos = self.cursor + num;
-
-        // First check if the end position is in bounds
-        if end_pos > self.input.len() {
-            return false;
-        }
-
-        // Now just move the cursor however many
-        for _ in 0..num {
+    /// Consumes an identifier matching the given name in any case, such as the X of an index
+    pub fn consume_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek_kind() == Some(TokenKind::Identifier) && self.peek_text().eq_ignore_ascii_case(keyword) {
             self.next();
+            true
+        } else {
+            false
         }
-
-        true
     }
 
     /// Parses a comment if applicable. EBNF is defined as
@@ -188,17 +331,10 @@ impl Scanner {
     ///
     /// See assembler.ebnf line 36
     fn comment(&mut self) -> TokenResult {
-        if self.peek() == Some(';') {
-            let start_pos = self.cursor;
-            while let Some(c) = self.peek() {
-                if c == '\n' {
-                    break;
-                }
-                self.next();
-            }
-            Ok(Some(Token::Comment(
-                self.input[start_pos..self.cursor].iter().collect(),
-            )))
+        if self.peek_kind() == Some(TokenKind::Comment) {
+            let comment = self.peek_text().to_string();
+            self.next();
+            Ok(Some(Token::Comment(comment)))
         } else {
             Ok(None)
         }
@@ -208,10 +344,10 @@ impl Scanner {
     ///
     /// newline = ? newline character(s) ?
     ///
-    /// For now we are only accepting \n. See assembler.ebnf line 42
+    /// Any of \n, \r\n or \r is accepted
     fn newline(&mut self) -> TokenResult {
-        match self.peek() {
-            Some('\n') => {
+        match self.peek_kind() {
+            Some(TokenKind::Newline) => {
                 self.next();
                 Ok(Some(Token::Newline))
             }
@@ -223,96 +359,50 @@ impl Scanner {
     /// Attempts to consume a newline character. If it was successful then return true
     /// and if it didn't then return false
     pub fn consume_newline(&mut self) -> bool {
-        match self.newline() {
-            Ok(token) => match token {
-                Some(Token::Newline) => true,
-                _ => false,
-            },
-            Err(_) => false,
-        }
-    }
-
-    /// Parses a whitespace character (space or a tab) if applicable. EBNF is defined as
-    ///
-    /// whitespace = { ? space or tab character ? }
-    ///
-    /// See assembler.ebnf line 44
-    fn whitespace(&mut self) -> TokenResult {
-        match self.peek() {
-            Some(' ') | Some('\t') => {
-                self.next();
-                Ok(Some(Token::Whitespace))
-            }
-            Some(_) => Ok(None),
-            None => Err(ParseError::UnexpectedEndOfInput),
-        }
-    }
-
-    /// Consumes 0 or more whitespace
-    pub fn consume_all_whitespace(&mut self) {
-        while let Ok(Some(_)) = self.whitespace() {}
-    }
-
-    /// Consumes 1 or more whitespaces
-    /// Returns true if it consumed at least 1 whitespace and false otherwise
-    pub fn _consume_all_whitespace1(&mut self) -> bool {
-        let mut count = 0;
-        while let Ok(Some(_)) = self.whitespace() {
-            count += 1;
-        }
-
-        if count > 0 {
-            true
-        } else {
-            false
-        }
+        matches!(self.newline(), Ok(Some(Token::Newline)))
     }
 
     /// Attempts to parse a constant (or identifier). The grammar is defined as
     ///
     /// identifier = letter {letter | digit | "_"}
+    ///
+    /// Unless symbols are case sensitive, the identifier is given the spelling it was first seen with
     pub fn identifier(&mut self) -> TokenResult {
-        let start_pos = self.cursor;
-
-        // First we need to check that we have a letter
-        match self.peek() {
-            Some(c) => {
-                if !c.is_alphabetic() {
-                    return Ok(None);
-                }
-            }
-            None => return Ok(None),
+        if self.peek_kind() != Some(TokenKind::Identifier) {
+            return Ok(None);
         }
 
-        // Move the cursor
+        let text = self.peek_text();
         self.next();
 
-        // Now go through and consume until we don't hit a letter, number, or _
-        while let Some(c) = self.peek() {
-            if !(c.is_alphanumeric() || c == '_') {
-                break;
-            }
-
-            // Move the cursor forward
-            self.next();
+        if self.case_sensitive_symbols {
+            return Ok(Some(Token::Identifier(text.to_string())));
         }
 
-        // Convert the characters to a String
-        Ok(Some(Token::Identifier(
-            self.input[start_pos..self.cursor].iter().collect(),
-        )))
+        let identifier = self
+            .symbol_spellings
+            .entry(text.to_ascii_lowercase())
+            .or_insert_with(|| text.to_string());
+
+        Ok(Some(Token::Identifier(identifier.clone())))
     }
 
     /// Attempts to parse a label. The grammar is defined as
     ///
-    /// label = identifier ":"
+    /// label = identifier ":" | ":"
     pub fn label(&mut self) -> TokenResult {
         let start_pos = self.cursor;
-        let mut is_local = false;
 
-        // Check if it begins with an @
-        if self.consume_char('@') {
-            is_local = true
+        // A colon on its own is an anonymous label
+        if self.consume(TokenKind::Colon) {
+            return Ok(Some(Token::AnonymousLabel));
+        }
+
+        // Check if it begins with an @. The name has to follow straight after it
+        let is_local = self.consume(TokenKind::At);
+        if is_local && self.peek().is_some_and(|lexeme| lexeme.spaced) {
+            self.cursor = start_pos;
+            return Ok(None);
         }
 
         // Get the label name
@@ -325,7 +415,7 @@ impl Scanner {
         };
 
         // Now consume the :
-        if !self.consume_char(':') {
+        if !self.consume_adjacent(TokenKind::Colon) {
             self.cursor = start_pos;
             return Ok(None);
         }
@@ -376,7 +466,7 @@ mod scanner_tests {
 
     #[test]
     fn test_parse_newline_no_error_on_non_newline() {
-        let mut scanner = Scanner::new(" ");
+        let mut scanner = Scanner::new("A");
         let result = scanner.newline();
 
         assert!(result.is_ok());
@@ -386,8 +476,8 @@ mod scanner_tests {
     #[test]
     fn test_parse_newline_unexpected_end_of_input_error() {
         let mut scanner = Scanner::new("\n");
-        // Increase the cursor by one since this will be an error
-        scanner.cursor += 1;
+        // The first newline is fine, but there is nothing after it
+        assert!(scanner.consume_newline());
         let result = scanner.newline();
 
         assert!(result.is_err());
@@ -396,82 +486,55 @@ mod scanner_tests {
     #[test]
     fn test_coonsume_newline() {
         let mut scanner = Scanner::new("\n");
-        assert_eq!(scanner.consume_newline(), true);
+        assert!(scanner.consume_newline());
 
         let mut scanner = Scanner::new("");
-        assert_eq!(scanner.consume_newline(), false);
+        assert!(!scanner.consume_newline());
     }
 
     #[test]
-    fn test_parse_whitespace_space() {
-        let mut scanner = Scanner::new(" ");
-        let result = scanner.whitespace();
+    fn test_parse_identifier_success() {
+        let mut scanner = Scanner::new("PPUCONSTANT");
+        let result = scanner.identifier();
 
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), Some(Token::Whitespace));
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::Identifier(String::from("PPUCONSTANT")))
+        )
     }
 
     #[test]
-    fn test_parse_whitespace_tab() {
-        let mut scanner = Scanner::new("\t");
-        let result = scanner.whitespace();
+    fn test_parse_label_success() {
+        let mut scanner = Scanner::new("START:");
+        let result = scanner.label();
 
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), Some(Token::Whitespace));
+        assert_eq!(result.unwrap(), Some(Token::Label(String::from("START"))));
     }
 
     #[test]
-    fn test_parse_whitespace_non_whitespace() {
-        let mut scanner = Scanner::new("A");
-        let result = scanner.whitespace();
+    fn test_parse_label_spaced_colon() {
+        let mut scanner = Scanner::new("START :");
+        let result = scanner.label();
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), None);
+        assert_eq!(scanner.cursor, 0);
     }
 
     #[test]
-    fn test_parse_whitespace_end_of_input_error() {
-        let mut scanner = Scanner::new(" ");
-        scanner.cursor += 1;
-        let result = scanner.whitespace();
-
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_parse_consume_whitespace1_one_space() {
-        let mut scanner = Scanner::new(" ");
-        let result = scanner._consume_all_whitespace1();
-
-        assert!(result);
-    }
-
-    #[test]
-    fn test_parse_consume_whitespace1_no_space() {
-        let mut scanner = Scanner::new("nospacesarehere");
-        let result = scanner._consume_all_whitespace1();
-
-        assert!(!result);
-    }
-
-    #[test]
-    fn test_parse_identifier_success() {
-        let mut scanner = Scanner::new("PPUCONSTANT");
-        let result = scanner.identifier();
+    fn test_parse_local_label() {
+        let mut scanner = Scanner::new("@loop:");
+        let result = scanner.label();
 
         assert!(result.is_ok());
-        assert_eq!(
-            result.unwrap(),
-            Some(Token::Identifier(String::from("PPUCONSTANT")))
-        )
-    }
+        assert_eq!(result.unwrap(), Some(Token::LocalLabel(String::from("loop"))));
 
-    #[test]
-    fn test_parse_label_success() {
-        let mut scanner = Scanner::new("START:");
+        let mut scanner = Scanner::new("@ loop:");
         let result = scanner.label();
 
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), Some(Token::Label(String::from("START"))));
+        assert_eq!(result.unwrap(), None);
     }
 }
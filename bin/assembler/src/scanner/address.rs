@@ -1,19 +1,19 @@
-use crate::error::ParseError;
+use crate::{error::ParseError, lexer::TokenKind};
 
 use super::{Scanner, Token, TokenResult};
 
 pub mod modes;
 
-pub fn parse_hex16_with_position(s: &str, position: usize) -> Result<u16, ParseError> {
-    u16::from_str_radix(s, 16).map_err(|_| ParseError::ParseIntError {
-        msg: format!("failed to convert {} to a u16", s),
+pub fn parse_hex24_with_position(s: &str, position: usize) -> Result<u32, ParseError> {
+    u32::from_str_radix(s, 16).map_err(|_| ParseError::ParseIntError {
+        msg: format!("failed to convert {} to a u24", s),
         position,
     })
 }
 
-pub fn parse_hex8_with_position(s: &str, position: usize) -> Result<u8, ParseError> {
-    u8::from_str_radix(s, 16).map_err(|_| ParseError::ParseIntError {
-        msg: format!("failed to convert {} to a u8", s),
+pub fn parse_hex16_with_position(s: &str, position: usize) -> Result<u16, ParseError> {
+    u16::from_str_radix(s, 16).map_err(|_| ParseError::ParseIntError {
+        msg: format!("failed to convert {} to a u16", s),
         position,
     })
 }
@@ -25,123 +25,91 @@ pub fn parse_bin16_with_position(s: &str, position: usize) -> Result<u16, ParseE
     })
 }
 
-impl Scanner {
-    /// Parses a u16 hex address ($0000) if applicable. EBNF is defined as
-    ///
-    /// address_u16 = "$" hex_digit hex_digit hex_digit hex_digit
-    ///
-    /// See assembler.ebnf line 28
-    pub fn address_u16(&mut self) -> TokenResult {
-        let start_pos = self.cursor;
-        let hex_size = 4;
-
-        if !self.consume_char('$') {
-            self.cursor = start_pos;
+impl Scanner<'_> {
+    /// Reads a hex number with exactly the given number of digits. Returns None if the next token
+    /// is not a hex number or has fewer digits. When strict, a bad digit or too many digits is an
+    /// error, otherwise the number is just not taken
+    pub(super) fn hex_number(&mut self, digits: usize, strict: bool) -> Result<Option<u32>, ParseError> {
+        if self.peek_kind() != Some(TokenKind::HexNumber) {
             return Ok(None);
         }
 
-        // Try to collect exactly four hex digits
-        let mut count = 0;
-        while let Some(c) = self.peek() {
-            if !c.is_ascii_hexdigit() {
-                // At this point we determined that we should be parsing a u16 and found an invalid digit. If this ok in the future then ignore
-                if count != hex_size {
-                    return Err(ParseError::ParseIntError {
-                        msg: format!("found invalid character: {}", c),
-                        position: self.cursor,
-                    });
-                }
-                break;
-            }
-            self.next();
-            count += 1;
-
-            // We can break early if we already collected four hex digits
-            if count == hex_size {
-                break;
-            }
+        let hex = &self.peek_text()[1..];
+        if let Some(c) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return match strict {
+                true => Err(ParseError::ParseIntError { msg: format!("found invalid character: {}", c), position: self.cursor }),
+                false => Ok(None),
+            };
         }
 
-        if let Some(c) = self.peek() {
-            if c.is_ascii_hexdigit() {
-                return Err(ParseError::TooManyDigits {
-                    msg: format!("literal u8 has too many digits"),
-                    position: self.cursor,
-                });
-            }
+        if hex.len() > digits && strict {
+            return Err(ParseError::TooManyDigits {
+                msg: format!("expected {} hex digits but found {}", digits, hex.len()),
+                position: self.cursor,
+            });
         }
 
-        // Check to see if received four hex digits
-        if count != hex_size {
-            self.cursor = start_pos; // Reset the cursor
+        if hex.len() != digits {
             return Ok(None);
         }
 
-        // Collet the address into a string
-        let address: String = self.input[(start_pos + 1)..self.cursor].iter().collect();
-        match parse_hex16_with_position(&address, self.cursor) {
-            Ok(val) => Ok(Some(Token::AddressU16(val))),
-            Err(e) => Err(e),
-        }
+        let value = parse_hex24_with_position(hex, self.cursor)?;
+        self.next();
+
+        Ok(Some(value))
     }
 
-    /// Parses a u8 hex address ($00) if applicable. EBNF is defined as
-    ///
-    /// address_u8 = "$" hex_digit hex_digit;
-    ///
-    /// See assembler.ebnf line 30
-    pub fn address_u8(&mut self) -> TokenResult {
+    /// Reads a # followed straight away by a hex number with the given number of digits
+    fn hex_literal(&mut self, digits: usize, strict: bool) -> Result<Option<u32>, ParseError> {
         let start_pos = self.cursor;
-        let hex_size = 2;
 
-        if !self.consume_char('$') {
+        if !(self.consume(TokenKind::Hash) && self.peek().is_some_and(|lexeme| !lexeme.spaced)) {
             self.cursor = start_pos;
             return Ok(None);
         }
 
-        // Try to collect exactly two hex digits
-        let mut count = 0;
-        while let Some(c) = self.peek() {
-            if !c.is_ascii_hexdigit() {
-                // At this point we determined that we should be parsing a u16 and found an invalid digit. If this ok in the future then ignore
-                if count != hex_size {
-                    return Err(ParseError::ParseIntError {
-                        msg: format!("found invalid character: {}", c),
-                        position: self.cursor,
-                    });
-                }
-                break;
-            }
-            self.next();
-            count += 1;
-
-            // We can break early if we already collected two hex digits
-            if count == hex_size {
-                break;
+        match self.hex_number(digits, strict) {
+            Ok(Some(value)) => Ok(Some(value)),
+            result => {
+                self.cursor = start_pos;
+                result
             }
         }
+    }
 
-        if let Some(c) = self.peek() {
-            if c.is_ascii_hexdigit() {
-                return Err(ParseError::TooManyDigits {
-                    msg: format!("literal u8 has too many digits"),
-                    position: self.cursor,
-                });
-            }
-        }
+    /// Parses a 65816 long hex address ($000000) if applicable. Unlike the shorter addresses, a
+    /// different number of digits is not an error since it is most likely one of those instead.
+    /// EBNF is defined as
+    ///
+    /// address_u24 = "$" hex_digit hex_digit hex_digit hex_digit hex_digit hex_digit
+    pub fn address_u24(&mut self) -> TokenResult {
+        Ok(self.hex_number(6, false)?.map(Token::AddressU24))
+    }
 
-        // Check to see if received two hex digits
-        if count != hex_size {
-            self.cursor = start_pos; // Reset the cursor
-            return Ok(None);
-        }
+    /// Parses a u16 hex literal (#$0000) used by the 65816 16-bit immediates if applicable.
+    /// EBNF is defined as
+    ///
+    /// literal_u16 = "#$" hex_digit hex_digit hex_digit hex_digit;
+    pub fn literal_u16(&mut self) -> TokenResult {
+        Ok(self.hex_literal(4, false)?.map(|value| Token::LiteralU16(value as u16)))
+    }
 
-        // Collet the address into a string
-        let address: String = self.input[(start_pos + 1)..self.cursor].iter().collect();
-        match parse_hex8_with_position(&address, self.cursor) {
-            Ok(val) => Ok(Some(Token::AddressU8(val))),
-            Err(e) => Err(e),
-        }
+    /// Parses a u16 hex address ($0000) if applicable. EBNF is defined as
+    ///
+    /// address_u16 = "$" hex_digit hex_digit hex_digit hex_digit
+    ///
+    /// See assembler.ebnf line 28
+    pub fn address_u16(&mut self) -> TokenResult {
+        Ok(self.hex_number(4, true)?.map(|value| Token::AddressU16(value as u16)))
+    }
+
+    /// Parses a u8 hex address ($00) if applicable. EBNF is defined as
+    ///
+    /// address_u8 = "$" hex_digit hex_digit;
+    ///
+    /// See assembler.ebnf line 30
+    pub fn address_u8(&mut self) -> TokenResult {
+        Ok(self.hex_number(2, true)?.map(|value| Token::AddressU8(value as u8)))
     }
 
     /// Parses a u8 hex literal (#$00) if applicable. EBNF is defined as
@@ -150,72 +118,46 @@ impl Scanner {
     ///
     /// See assembler.ebnf line 29
     pub fn literal_u8(&mut self) -> TokenResult {
+        Ok(self.hex_literal(2, true)?.map(|value| Token::LiteralU8(value as u8)))
+    }
+
+    pub fn address_modes(&mut self) -> TokenResult {
         let start_pos = self.cursor;
-        let hex_size = 2;
 
-        if !(self.consume_char('#') && self.consume_char('$')) {
-            self.cursor = start_pos;
-            return Ok(None);
+        // Test indexed indirect X
+        if let Some(token) = self.attempt_parser(Self::indexed_indirect_x_mode)? {
+            return Ok(Some(token));
         }
 
-        // Try to collect exactly two hex digits
-        let mut count = 0;
-        while let Some(c) = self.peek() {
-            if !c.is_ascii_hexdigit() {
-                // At this point we determined that we should be parsing a u16 and found an invalid digit. If this ok in the future then ignore
-                if count != hex_size {
-                    return Err(ParseError::ParseIntError {
-                        msg: format!("found invalid character: {}", c),
-                        position: self.cursor,
-                    });
-                }
-                break;
-            }
-            self.next();
-            count += 1;
-
-            // We can break early if we already collected two hex digits
-            if count == hex_size {
-                break;
-            }
-        }
+        // Reset back
+        self.cursor = start_pos;
 
-        if let Some(c) = self.peek() {
-            if c.is_ascii_hexdigit() {
-                return Err(ParseError::TooManyDigits {
-                    msg: format!("literal u8 has too many digits"),
-                    position: self.cursor,
-                });
-            }
+        // Test indirect index y
+        if let Some(token) = self.attempt_parser(Self::indirect_index_y_mode)? {
+            return Ok(Some(token));
         }
 
-        // Check to see if received two hex digits
-        if count != hex_size {
-            self.cursor = start_pos; // Reset the cursor
-            return Ok(None);
-        }
+        // Reset back
+        self.cursor = start_pos;
 
-        // Collet the address into a string
-        let address: String = self.input[(start_pos + 2)..self.cursor].iter().collect();
-        match parse_hex8_with_position(&address, self.cursor) {
-            Ok(val) => Ok(Some(Token::LiteralU8(val))),
-            Err(e) => Err(e),
+        // Test absolute indexed indirect ($0000,X)
+        if let Some(token) = self.attempt_parser(Self::absolute_indexed_indirect_mode)? {
+            return Ok(Some(token));
         }
-    }
 
-    pub fn address_modes(&mut self) -> TokenResult {
-        let start_pos = self.cursor;
+        // Reset back
+        self.cursor = start_pos;
 
-        // Test indexed indirect X
-        if let Some(token) = self.attempt_parser(Self::indexed_indirect_x_mode)? {
+        // Test indirect ($00) or ($0000)
+        if let Some(token) = self.attempt_parser(Self::indirect_mode)? {
             return Ok(Some(token));
         }
 
         // Reset back
         self.cursor = start_pos;
 
-        // Test indirect index y
-        if let Some(token) = self.attempt_parser(Self::indirect_index_y_mode)? {
+        // Test stack relative $00,S
+        if let Some(token) = self.attempt_parser(Self::stack_relative_mode)? {
             return Ok(Some(token));
         }
 
@@ -385,7 +327,7 @@ pub mod address_test {
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), Some(Token::AddressU8(0x44)));
-        assert_eq!(scanner.cursor, 3);
+        assert_eq!(scanner.cursor, 1);
     }
 
     #[test]
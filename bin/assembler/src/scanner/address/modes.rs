@@ -2,42 +2,54 @@ use forge_lib::{address::AddressMode, expression::ExpressionNode};
 
 use crate::{
     error::ParseError,
+    lexer::TokenKind,
     scanner::{Scanner, Token, TokenResult},
 };
 
-impl Scanner {
+impl Scanner<'_> {
     pub fn immediate_mode(&mut self) -> TokenResult {
         let start_pos = self.cursor;
 
-        // Grab a literal u8 (#$00)
-        let address = self.literal_u8()?;
+        // Grab a literal u8 (#$00), or a literal u16 (#$0000) for the 65816 if there were too many digits
+        let address = match self.literal_u8() {
+            Err(ParseError::TooManyDigits { .. }) => {
+                self.cursor = start_pos;
+                self.literal_u16()?
+            }
+            result => result?,
+        };
 
         // If it was successful to parse, then get the address and return the address mode
         match address {
             Some(Token::LiteralU8(val)) => {
                 Ok(Some(Token::AddressMode(AddressMode::Immediate(val))))
             }
+            Some(Token::LiteralU16(val)) => {
+                Ok(Some(Token::AddressMode(AddressMode::ImmediateWord(val))))
+            }
             Some(_) => Err(ParseError::ExpectedLiteralU8),
             None => {
                 // Check if there is an identifier then
                 self.cursor = start_pos;
 
-                if !self.consume_char('#') {
+                if !self.consume(TokenKind::Hash) {
                     self.cursor = start_pos;
                     return Ok(None)
                 }
 
                 match self.expression()? {
-                    Some(ExpressionNode::Identifier(ident)) => {
+                    Some(ExpressionNode::Identifier(ident, _)) => {
                         Ok(Some(Token::AddressMode(AddressMode::ImmediateIdent(ident))))
                     }
-                    Some(ExpressionNode::ScopedReference(scoped_ref)) => {
+                    Some(ExpressionNode::ScopedReference(scoped_ref, _)) => {
                         Ok(Some(Token::AddressMode(AddressMode::ImmediateScopedRef(scoped_ref))))
                     }
-                    Some(_) => Err(ParseError::ExpectedLiteralU8),
+                    Some(expression) => {
+                        Ok(Some(Token::AddressMode(AddressMode::ImmediateExpression(expression))))
+                    }
                     None => {
                         self.cursor = start_pos;
-                        return Ok(None);
+                        Ok(None)
                     }
                 }
             }
@@ -54,16 +66,16 @@ impl Scanner {
             Some(Token::AddressU8(val)) => Ok(Some(Token::AddressMode(AddressMode::ZeroPage(val)))),
             Some(_) => Err(ParseError::ExpectedAddressU8),
             None => match self.expression()? {
-                Some(ExpressionNode::Identifier(ident)) => Ok(Some(Token::AddressMode(
+                Some(ExpressionNode::Identifier(ident, _)) => Ok(Some(Token::AddressMode(
                     AddressMode::ZeroPageOrAbsoluteIdent(ident),
                 ))),
-                Some(ExpressionNode::ScopedReference(scoped_ref)) => {
+                Some(ExpressionNode::ScopedReference(scoped_ref, _)) => {
                     Ok(Some(Token::AddressMode(AddressMode::ZeroPageOrAbsoluteScopedRef(scoped_ref))))
                 },
                 Some(_) => Err(ParseError::ExpectedAddressU8),
                 None => {
                     self.cursor = start_pos;
-                    return Ok(None);
+                    Ok(None)
                 }
             },
         }
@@ -83,10 +95,10 @@ impl Scanner {
             Some(Token::AddressU8(val)) => AddressMode::ZeroPageX(val),
             Some(_) => return Err(ParseError::ExpectedAddressU8),
             None => match self.expression()? {
-                Some(ExpressionNode::Identifier(ident)) => {
+                Some(ExpressionNode::Identifier(ident, _)) => {
                     AddressMode::ZeroPageOrAbsoluteXIdent(ident)
                 },
-                Some(ExpressionNode::ScopedReference(scoped_ref)) => {
+                Some(ExpressionNode::ScopedReference(scoped_ref, _)) => {
                     AddressMode::ZeroPageOrAbsoluteXScopedRef(scoped_ref)
                 },
                 Some(_) => return Err(ParseError::ExpectedAddressU8),
@@ -97,20 +109,14 @@ impl Scanner {
             },
         };
 
-        // Now consume any number of whitespaces
-        self.consume_all_whitespace();
-
         // The next character is a comma ,
-        if !self.consume_char(',') {
+        if !self.consume(TokenKind::Comma) {
             self.cursor = start_pos;
             return Ok(None);
         }
 
-        // Consume any number of whitespaces
-        self.consume_all_whitespace();
-
         // Now consume an upper or lowercase X
-        if !self.consume_char('X') && !self.consume_char('x') {
+        if !self.consume_keyword("X") {
             self.cursor = start_pos;
             return Ok(None);
         }
@@ -132,10 +138,10 @@ impl Scanner {
             Some(Token::AddressU8(val)) => AddressMode::ZeroPageY(val),
             Some(_) => return Err(ParseError::ExpectedAddressU8),
             None => match self.expression()? {
-                Some(ExpressionNode::Identifier(ident)) => {
+                Some(ExpressionNode::Identifier(ident, _)) => {
                     AddressMode::ZeroPageOrAbsoluteYIdent(ident)
                 },
-                Some(ExpressionNode::ScopedReference(scoped_ref)) => {
+                Some(ExpressionNode::ScopedReference(scoped_ref, _)) => {
                     AddressMode::ZeroPageOrAbsoluteYScopedRef(scoped_ref)
                 },
                 Some(_) => return Err(ParseError::ExpectedAddressU8),
@@ -146,20 +152,14 @@ impl Scanner {
             },
         };
 
-        // Now consume any number of whitespaces
-        self.consume_all_whitespace();
-
         // The next character is a comma ,
-        if !self.consume_char(',') {
+        if !self.consume(TokenKind::Comma) {
             self.cursor = start_pos;
             return Ok(None);
         }
 
-        // Consume any number of whitespaces
-        self.consume_all_whitespace();
-
         // Now consume an upper or lowercase X
-        if !self.consume_char('Y') && !self.consume_char('y') {
+        if !self.consume_keyword("Y") {
             self.cursor = start_pos;
             return Ok(None);
         }
@@ -179,15 +179,15 @@ impl Scanner {
             }
             Some(_) => Err(ParseError::ExpectedAddressU8),
             None => match self.expression()? {
-                Some(ExpressionNode::Identifier(ident)) =>
-                    return Ok(Some(Token::AddressMode(AddressMode::ZeroPageOrAbsoluteIdent(ident)))),
-                Some(ExpressionNode::ScopedReference(scoped_ref)) => {
-                    return Ok(Some(Token::AddressMode(AddressMode::ZeroPageOrAbsoluteScopedRef(scoped_ref))))
+                Some(ExpressionNode::Identifier(ident, _)) =>
+                    Ok(Some(Token::AddressMode(AddressMode::ZeroPageOrAbsoluteIdent(ident)))),
+                Some(ExpressionNode::ScopedReference(scoped_ref, _)) => {
+                    Ok(Some(Token::AddressMode(AddressMode::ZeroPageOrAbsoluteScopedRef(scoped_ref))))
                 },
-                Some(_) => return Err(ParseError::ExpectedAddressU8),
+                Some(_) => Err(ParseError::ExpectedAddressU8),
                 None => {
                     self.cursor = start_pos;
-                    return Ok(None);
+                    Ok(None)
                 }
             },
         }
@@ -207,10 +207,10 @@ impl Scanner {
             Some(Token::AddressU16(val)) => AddressMode::AbsoluteX(val),
             Some(_) => return Err(ParseError::ExpectedAddressU16),
             None => match self.expression()? {
-                Some(ExpressionNode::Identifier(ident)) => {
+                Some(ExpressionNode::Identifier(ident, _)) => {
                     AddressMode::ZeroPageOrAbsoluteXIdent(ident)
                 },
-                Some(ExpressionNode::ScopedReference(scoped_ref)) => {
+                Some(ExpressionNode::ScopedReference(scoped_ref, _)) => {
                     AddressMode::ZeroPageOrAbsoluteXScopedRef(scoped_ref)
                 },
                 Some(_) => return Err(ParseError::ExpectedAddressU8),
@@ -221,20 +221,14 @@ impl Scanner {
             },
         };
 
-        // Now consume any number of whitespaces
-        self.consume_all_whitespace();
-
         // The next character is a comma ,
-        if !self.consume_char(',') {
+        if !self.consume(TokenKind::Comma) {
             self.cursor = start_pos;
             return Ok(None);
         }
 
-        // Consume any number of whitespaces
-        self.consume_all_whitespace();
-
         // Now consume an upper or lowercase X
-        if !self.consume_char('X') && !self.consume_char('x') {
+        if !self.consume_keyword("X") {
             self.cursor = start_pos;
             return Ok(None);
         }
@@ -256,10 +250,10 @@ impl Scanner {
             Some(Token::AddressU16(val)) => AddressMode::AbsoluteY(val),
             Some(_) => return Err(ParseError::ExpectedAddressU16),
             None => match self.expression()? {
-                Some(ExpressionNode::Identifier(ident)) => {
+                Some(ExpressionNode::Identifier(ident, _)) => {
                     AddressMode::ZeroPageOrAbsoluteYIdent(ident)
                 },
-                Some(ExpressionNode::ScopedReference(scoped_ref)) => {
+                Some(ExpressionNode::ScopedReference(scoped_ref, _)) => {
                     AddressMode::ZeroPageOrAbsoluteYScopedRef(scoped_ref)
                 },
                 Some(_) => return Err(ParseError::ExpectedAddressU8),
@@ -270,20 +264,14 @@ impl Scanner {
             },
         };
 
-        // Now consume any number of whitespaces
-        self.consume_all_whitespace();
-
         // The next character is a comma ,
-        if !self.consume_char(',') {
+        if !self.consume(TokenKind::Comma) {
             self.cursor = start_pos;
             return Ok(None);
         }
 
-        // Consume any number of whitespaces
-        self.consume_all_whitespace();
-
         // Now consume an upper or lowercase Y
-        if !self.consume_char('Y') && !self.consume_char('y') {
+        if !self.consume_keyword("Y") {
             self.cursor = start_pos;
             return Ok(None);
         }
@@ -298,13 +286,10 @@ impl Scanner {
         let start_pos = self.cursor;
 
         // Check to see if we have a (
-        if !self.consume_char('(') {
+        if !self.consume(TokenKind::LeftParen) {
             return Ok(None);
         }
 
-        // Consume any whitespaces
-        self.consume_all_whitespace();
-
         // Grab an address u8 ($00)
         let address = self.address_u8()?;
 
@@ -313,10 +298,10 @@ impl Scanner {
             Some(Token::AddressU8(val)) => AddressMode::IndexedIndirectX(val),
             Some(_) => return Err(ParseError::ExpectedAddressU8),
             None => match self.expression()? {
-                Some(ExpressionNode::Identifier(ident)) => {
+                Some(ExpressionNode::Identifier(ident, _)) => {
                     AddressMode::IndexedIndirectXIdent(ident)
                 },
-                Some(ExpressionNode::ScopedReference(scoped_ref)) => {
+                Some(ExpressionNode::ScopedReference(scoped_ref, _)) => {
                     AddressMode::IndexedIndirectXScopedRef(scoped_ref)
                 }
                 _ => {
@@ -326,29 +311,20 @@ impl Scanner {
             }
         };
 
-        // Consume any number of whitespaces
-        self.consume_all_whitespace();
-
         // Consume a comma
-        if !self.consume_char(',') {
+        if !self.consume(TokenKind::Comma) {
             self.cursor = start_pos;
             return Ok(None);
         }
 
-        // Consume any number of whitespaces
-        self.consume_all_whitespace();
-
         // Consume a lower case or upper case X
-        if !self.consume_char('X') && !self.consume_char('x') {
+        if !self.consume_keyword("X") {
             self.cursor = start_pos;
             return Ok(None);
         }
 
-        // Consume any number of whitespaces
-        self.consume_all_whitespace();
-
         // Consume a )
-        if !self.consume_char(')') {
+        if !self.consume(TokenKind::RightParen) {
             self.cursor = start_pos;
             return Ok(None);
         }
@@ -363,13 +339,10 @@ impl Scanner {
         let start_pos = self.cursor;
 
         // Check to see if we have a (
-        if !self.consume_char('(') {
+        if !self.consume(TokenKind::LeftParen) {
             return Ok(None);
         }
 
-        // Consume any whitespaces
-        self.consume_all_whitespace();
-
         // Grab an address u8 ($00)
         let address = self.address_u8()?;
 
@@ -378,10 +351,10 @@ impl Scanner {
             Some(Token::AddressU8(val)) => AddressMode::IndirectIndexY(val),
             Some(_) => return Err(ParseError::ExpectedAddressU8),
             None => match self.expression()? {
-                Some(ExpressionNode::Identifier(ident)) => {
+                Some(ExpressionNode::Identifier(ident, _)) => {
                     AddressMode::IndirectIndexYIdent(ident)
                 },
-                Some(ExpressionNode::ScopedReference(scoped_ref)) => {
+                Some(ExpressionNode::ScopedReference(scoped_ref, _)) => {
                     AddressMode::IndirectIndexYScopedRef(scoped_ref)
                 }
                 _ => {
@@ -391,29 +364,128 @@ impl Scanner {
             }
         };
 
-        // Consume any number of whitespaces
-        self.consume_all_whitespace();
+        // Consume a )
+        if !self.consume(TokenKind::RightParen) {
+            self.cursor = start_pos;
+            return Ok(None);
+        }
+
+        // Consume a comma
+        if !self.consume(TokenKind::Comma) {
+            self.cursor = start_pos;
+            return Ok(None);
+        }
+
+        // Consume a lower case or upper case X
+        if !self.consume_keyword("Y") {
+            self.cursor = start_pos;
+            return Ok(None);
+        }
+
+        Ok(Some(Token::AddressMode(value)))
+    }
+
+    /// Parses into indirect addressing mode. A u8 address is the 65C02 zero page indirect mode and
+    /// a u16 address is the indirect mode used by JMP. The EBNF is defined as
+    ///
+    /// indirect_mode = "(" [whitespace] (address_u8 | address_u16 | identifier) [whitespace] ")";
+    pub fn indirect_mode(&mut self) -> TokenResult {
+        let start_pos = self.cursor;
+
+        // Check to see if we have a (
+        if !self.consume(TokenKind::LeftParen) {
+            return Ok(None);
+        }
+
+        // Try a u8 address first, then fall back to a u16 address or an identifier
+        let address_pos = self.cursor;
+        let value = match self.attempt_parser(Self::address_u8)? {
+            Some(Token::AddressU8(val)) => AddressMode::ZeroPageIndirect(val),
+            Some(_) => return Err(ParseError::ExpectedAddressU8),
+            None => {
+                self.cursor = address_pos;
+                match self.address_u16()? {
+                    Some(Token::AddressU16(val)) => AddressMode::Indirect(val),
+                    Some(_) => return Err(ParseError::ExpectedAddressU16),
+                    None => match self.expression()? {
+                        Some(ExpressionNode::Identifier(ident, _)) => {
+                            AddressMode::IndirectIdent(ident)
+                        },
+                        Some(ExpressionNode::ScopedReference(scoped_ref, _)) => {
+                            AddressMode::IndirectScopedRef(scoped_ref)
+                        }
+                        _ => {
+                            self.cursor = start_pos;
+                            return Ok(None)
+                        }
+                    }
+                }
+            }
+        };
 
         // Consume a )
-        if !self.consume_char(')') {
+        if !self.consume(TokenKind::RightParen) {
             self.cursor = start_pos;
             return Ok(None);
         }
 
-        // Consume any number of whitespaces
-        self.consume_all_whitespace();
+        // If an index or operator follows then this was either (zp),Y or a parenthesized expression
+        if let Some(
+            TokenKind::Comma
+            | TokenKind::Plus
+            | TokenKind::Minus
+            | TokenKind::Pipe
+            | TokenKind::Ampersand
+            | TokenKind::Star
+            | TokenKind::Slash
+            | TokenKind::Less
+            | TokenKind::Greater
+            | TokenKind::ShiftLeft
+            | TokenKind::ShiftRight,
+        ) = self.peek_kind()
+        {
+            self.cursor = start_pos;
+            return Ok(None);
+        }
+
+        Ok(Some(Token::AddressMode(value)))
+    }
+
+    /// Parses into the 65C02 absolute indexed indirect mode used by JMP. The EBNF is defined as
+    ///
+    /// absolute_indexed_indirect_mode = "(" [whitespace] address_u16 [whitespace] "," [whitespace] "X" [whitespace] ")";
+    pub fn absolute_indexed_indirect_mode(&mut self) -> TokenResult {
+        let start_pos = self.cursor;
+
+        // Check to see if we have a (
+        if !self.consume(TokenKind::LeftParen) {
+            return Ok(None);
+        }
+
+        // Grab an address u16 ($0000)
+        let value = match self.address_u16()? {
+            Some(Token::AddressU16(val)) => AddressMode::AbsoluteIndexedIndirect(val),
+            Some(_) => return Err(ParseError::ExpectedAddressU16),
+            None => {
+                self.cursor = start_pos;
+                return Ok(None);
+            }
+        };
 
         // Consume a comma
-        if !self.consume_char(',') {
+        if !self.consume(TokenKind::Comma) {
             self.cursor = start_pos;
             return Ok(None);
         }
 
-        // Consume any number of whitespaces
-        self.consume_all_whitespace();
-
         // Consume a lower case or upper case X
-        if !self.consume_char('Y') && !self.consume_char('y') {
+        if !self.consume_keyword("X") {
+            self.cursor = start_pos;
+            return Ok(None);
+        }
+
+        // Consume a )
+        if !self.consume(TokenKind::RightParen) {
             self.cursor = start_pos;
             return Ok(None);
         }
@@ -421,21 +493,274 @@ impl Scanner {
         Ok(Some(Token::AddressMode(value)))
     }
 
-    /// Parses into accumulator mode. The EBNF is defined as
+    /// Parses the zero page address and branch target of the Rockwell branch on bit instructions.
+    /// The EBNF is defined as
+    ///
+    /// zero_page_relative_mode = expression [whitespace] "," [whitespace] expression;
+    pub fn zero_page_relative_mode(&mut self) -> TokenResult {
+        let start_pos = self.cursor;
+
+        // Grab the zero page address
+        let zero_page = match self.expression()? {
+            Some(expression) => expression,
+            None => {
+                self.cursor = start_pos;
+                return Ok(None);
+            }
+        };
+
+        // Consume a comma
+        if !self.consume(TokenKind::Comma) {
+            self.cursor = start_pos;
+            return Ok(None);
+        }
+
+        // Grab the branch target
+        let target = match self.expression()? {
+            Some(expression) => expression,
+            None => {
+                self.cursor = start_pos;
+                return Ok(None);
+            }
+        };
+
+        Ok(Some(Token::AddressMode(AddressMode::ZeroPageRelative(zero_page, target))))
+    }
+
+    /// Parses into accumulator mode. The A must not be the start of a scoped reference. The EBNF is defined as
     ///
     /// accumalator_mode = "A";
-    fn _accumulator_mode(&mut self) -> TokenResult {
-        if !self.consume_char('A') {
+    pub fn accumulator_mode(&mut self) -> TokenResult {
+        let start_pos = self.cursor;
+
+        if !self.consume_keyword("A") {
+            return Ok(None);
+        }
+
+        if self.consume_adjacent(TokenKind::Colon) || self.consume_adjacent(TokenKind::DoubleColon) {
+            self.cursor = start_pos;
             return Ok(None);
         }
 
         Ok(Some(Token::AddressMode(AddressMode::Accumulator)))
     }
+
+    /// Parses into the 65816 absolute long mode. The EBNF is defined as
+    ///
+    /// absolute_long_mode = address_u24;
+    pub fn absolute_long_mode(&mut self) -> TokenResult {
+        let start_pos = self.cursor;
+
+        match self.address_u24()? {
+            Some(Token::AddressU24(val)) => Ok(Some(Token::AddressMode(AddressMode::AbsoluteLong(val)))),
+            Some(_) => Err(ParseError::ExpectedAddressU24),
+            None => {
+                self.cursor = start_pos;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Parses into the 65816 absolute long X mode. The EBNF is defined as
+    ///
+    /// absolute_long_x_mode = address_u24 [whitespace] "," [whitespace] "X";
+    pub fn absolute_long_x_mode(&mut self) -> TokenResult {
+        let start_pos = self.cursor;
+
+        let value = match self.address_u24()? {
+            Some(Token::AddressU24(val)) => AddressMode::AbsoluteLongX(val),
+            Some(_) => return Err(ParseError::ExpectedAddressU24),
+            None => {
+                self.cursor = start_pos;
+                return Ok(None);
+            }
+        };
+
+        // Consume a comma
+        if !self.consume(TokenKind::Comma) {
+            self.cursor = start_pos;
+            return Ok(None);
+        }
+
+        // Consume a lower case or upper case X
+        if !self.consume_keyword("X") {
+            self.cursor = start_pos;
+            return Ok(None);
+        }
+
+        Ok(Some(Token::AddressMode(value)))
+    }
+
+    /// Parses into the 65816 stack relative mode. The EBNF is defined as
+    ///
+    /// stack_relative_mode = address_u8 [whitespace] "," [whitespace] "S";
+    pub fn stack_relative_mode(&mut self) -> TokenResult {
+        let start_pos = self.cursor;
+
+        let value = match self.address_u8()? {
+            Some(Token::AddressU8(val)) => AddressMode::StackRelative(val),
+            Some(_) => return Err(ParseError::ExpectedAddressU8),
+            None => {
+                self.cursor = start_pos;
+                return Ok(None);
+            }
+        };
+
+        // Consume a comma
+        if !self.consume(TokenKind::Comma) {
+            self.cursor = start_pos;
+            return Ok(None);
+        }
+
+        // Consume a lower case or upper case S
+        if !self.consume_keyword("S") {
+            self.cursor = start_pos;
+            return Ok(None);
+        }
+
+        Ok(Some(Token::AddressMode(value)))
+    }
+
+    /// Parses into the 65816 stack relative indirect Y mode. The EBNF is defined as
+    ///
+    /// stack_relative_indirect_y_mode = "(" [whitespace] stack_relative_mode [whitespace] ")" [whitespace] "," [whitespace] "Y";
+    pub fn stack_relative_indirect_y_mode(&mut self) -> TokenResult {
+        let start_pos = self.cursor;
+
+        // Check to see if we have a (
+        if !self.consume(TokenKind::LeftParen) {
+            return Ok(None);
+        }
+
+        let value = match self.stack_relative_mode()? {
+            Some(Token::AddressMode(AddressMode::StackRelative(val))) => AddressMode::StackRelativeIndirectY(val),
+            _ => {
+                self.cursor = start_pos;
+                return Ok(None);
+            }
+        };
+
+        // Consume a )
+        if !self.consume(TokenKind::RightParen) {
+            self.cursor = start_pos;
+            return Ok(None);
+        }
+
+        // Consume a comma
+        if !self.consume(TokenKind::Comma) {
+            self.cursor = start_pos;
+            return Ok(None);
+        }
+
+        // Consume a lower case or upper case Y
+        if !self.consume_keyword("Y") {
+            self.cursor = start_pos;
+            return Ok(None);
+        }
+
+        Ok(Some(Token::AddressMode(value)))
+    }
+
+    /// Parses into one of the 65816 indirect long modes, which use square brackets. A u8 address is
+    /// zero page indirect long (optionally indexed by Y) and a u16 address is the absolute indirect
+    /// long mode used by JML. The EBNF is defined as
+    ///
+    /// indirect_long_mode = "[" [whitespace] (address_u8 | address_u16) [whitespace] "]" [[whitespace] "," [whitespace] "Y"];
+    pub fn indirect_long_mode(&mut self) -> TokenResult {
+        let start_pos = self.cursor;
+
+        // Check to see if we have a [
+        if !self.consume(TokenKind::LeftBracket) {
+            return Ok(None);
+        }
+
+        // Try a u8 address first, then fall back to a u16 address
+        let address_pos = self.cursor;
+        let value = match self.attempt_parser(Self::address_u8)? {
+            Some(Token::AddressU8(val)) => AddressMode::ZeroPageIndirectLong(val),
+            Some(_) => return Err(ParseError::ExpectedAddressU8),
+            None => {
+                self.cursor = address_pos;
+                match self.address_u16()? {
+                    Some(Token::AddressU16(val)) => AddressMode::AbsoluteIndirectLong(val),
+                    Some(_) => return Err(ParseError::ExpectedAddressU16),
+                    None => {
+                        self.cursor = start_pos;
+                        return Ok(None);
+                    }
+                }
+            }
+        };
+
+        // Consume a ]
+        if !self.consume(TokenKind::RightBracket) {
+            self.cursor = start_pos;
+            return Ok(None);
+        }
+
+        // Only the zero page form can be indexed by Y
+        let end_pos = self.cursor;
+        if let (AddressMode::ZeroPageIndirectLong(val), true) = (&value, self.consume(TokenKind::Comma)) {
+            let val = *val;
+
+            if !self.consume_keyword("Y") {
+                self.cursor = start_pos;
+                return Ok(None);
+            }
+
+            return Ok(Some(Token::AddressMode(AddressMode::ZeroPageIndirectLongY(val))));
+        }
+        self.cursor = end_pos;
+
+        Ok(Some(Token::AddressMode(value)))
+    }
+
+    /// Parses the source and destination banks of the 65816 block move instructions. Both banks
+    /// may be written with or without a #. The EBNF is defined as
+    ///
+    /// block_move_mode = ["#"] address_u8 [whitespace] "," [whitespace] ["#"] address_u8;
+    pub fn block_move_mode(&mut self) -> TokenResult {
+        let start_pos = self.cursor;
+
+        let source = match self.block_move_bank()? {
+            Some(bank) => bank,
+            None => {
+                self.cursor = start_pos;
+                return Ok(None);
+            }
+        };
+
+        // Consume a comma
+        if !self.consume(TokenKind::Comma) {
+            self.cursor = start_pos;
+            return Ok(None);
+        }
+
+        let destination = match self.block_move_bank()? {
+            Some(bank) => bank,
+            None => {
+                self.cursor = start_pos;
+                return Ok(None);
+            }
+        };
+
+        Ok(Some(Token::AddressMode(AddressMode::BlockMove(source, destination))))
+    }
+
+    fn block_move_bank(&mut self) -> Result<Option<u8>, ParseError> {
+        self.consume(TokenKind::Hash);
+
+        match self.address_u8()? {
+            Some(Token::AddressU8(val)) => Ok(Some(val)),
+            Some(_) => Err(ParseError::ExpectedAddressU8),
+            None => Ok(None),
+        }
+    }
 }
 
 #[cfg(test)]
 pub mod address_modes_tests {
-    use forge_lib::address::AddressMode;
+    use forge_lib::{address::AddressMode, expression::ExpressionNode, span::Span};
 
     use crate::scanner::{Scanner, Token};
 
@@ -462,7 +787,8 @@ pub mod address_modes_tests {
 
     #[test]
     fn test_parse_immediate_addressing_non_immediate() {
-        let mut scanner = Scanner::new("#$4321");
+        // Four digits is a 16-bit immediate for the 65816, but nothing takes five
+        let mut scanner = Scanner::new("#$43210");
         let result = scanner.immediate_mode();
 
         assert!(result.is_err());
@@ -677,12 +1003,206 @@ pub mod address_modes_tests {
     #[test]
     fn test_parse_accumulator_addressing_success() {
         let mut scanner = Scanner::new("A");
-        let result = scanner._accumulator_mode();
+        let result = scanner.accumulator_mode();
 
         assert!(result.is_ok());
         assert_eq!(
             result.unwrap(),
             Some(Token::AddressMode(AddressMode::Accumulator))
         );
+
+        let mut scanner = Scanner::new("ADDR");
+        let result = scanner.accumulator_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_indirect_addressing_success() {
+        let mut scanner = Scanner::new("($44)");
+        let result = scanner.indirect_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::AddressMode(AddressMode::ZeroPageIndirect(0x44)))
+        );
+
+        let mut scanner = Scanner::new("( $FFFC )");
+        let result = scanner.indirect_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::AddressMode(AddressMode::Indirect(0xFFFC)))
+        );
+
+        let mut scanner = Scanner::new("(vector)");
+        let result = scanner.indirect_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::AddressMode(AddressMode::IndirectIdent(String::from("vector"))))
+        );
+    }
+
+    #[test]
+    fn test_parse_indirect_addressing_non_indirect() {
+        let mut scanner = Scanner::new("($44),Y");
+        let result = scanner.indirect_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), None);
+        assert_eq!(scanner.cursor, 0);
+
+        let mut scanner = Scanner::new("(base) + 1");
+        let result = scanner.indirect_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_absolute_indexed_indirect_addressing_success() {
+        let mut scanner = Scanner::new("($1234,X)");
+        let result = scanner.absolute_indexed_indirect_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::AddressMode(AddressMode::AbsoluteIndexedIndirect(0x1234)))
+        );
+    }
+
+    #[test]
+    fn test_parse_zero_page_relative_addressing_success() {
+        let mut scanner = Scanner::new("$12, loop");
+        let result = scanner.zero_page_relative_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::AddressMode(AddressMode::ZeroPageRelative(
+                ExpressionNode::Number(0x12, Span::default()),
+                ExpressionNode::Identifier(String::from("loop"), Span::default())
+            )))
+        );
+    }
+
+    #[test]
+    fn test_parse_immediate_word_addressing_success() {
+        let mut scanner = Scanner::new("#$1234");
+        let result = scanner.immediate_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::AddressMode(AddressMode::ImmediateWord(0x1234)))
+        );
+    }
+
+    #[test]
+    fn test_parse_absolute_long_addressing_success() {
+        let mut scanner = Scanner::new("$7E1234");
+        let result = scanner.absolute_long_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::AddressMode(AddressMode::AbsoluteLong(0x7E1234)))
+        );
+
+        let mut scanner = Scanner::new("$7E1234, x");
+        let result = scanner.absolute_long_x_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::AddressMode(AddressMode::AbsoluteLongX(0x7E1234)))
+        );
+    }
+
+    #[test]
+    fn test_parse_absolute_long_addressing_fail() {
+        let mut scanner = Scanner::new("$1234");
+        let result = scanner.absolute_long_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), None);
+        assert_eq!(scanner.cursor, 0);
+    }
+
+    #[test]
+    fn test_parse_stack_relative_addressing_success() {
+        let mut scanner = Scanner::new("$03,S");
+        let result = scanner.stack_relative_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::AddressMode(AddressMode::StackRelative(0x03)))
+        );
+
+        let mut scanner = Scanner::new("( $03 , s ) , y");
+        let result = scanner.stack_relative_indirect_y_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::AddressMode(AddressMode::StackRelativeIndirectY(0x03)))
+        );
+    }
+
+    #[test]
+    fn test_parse_indirect_long_addressing_success() {
+        let mut scanner = Scanner::new("[$10]");
+        let result = scanner.indirect_long_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::AddressMode(AddressMode::ZeroPageIndirectLong(0x10)))
+        );
+
+        let mut scanner = Scanner::new("[$10],Y");
+        let result = scanner.indirect_long_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::AddressMode(AddressMode::ZeroPageIndirectLongY(0x10)))
+        );
+
+        let mut scanner = Scanner::new("[$1234]");
+        let result = scanner.indirect_long_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::AddressMode(AddressMode::AbsoluteIndirectLong(0x1234)))
+        );
+    }
+
+    #[test]
+    fn test_parse_block_move_addressing_success() {
+        let mut scanner = Scanner::new("$7E, $7F");
+        let result = scanner.block_move_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::AddressMode(AddressMode::BlockMove(0x7E, 0x7F)))
+        );
+
+        let mut scanner = Scanner::new("#$00,#$01");
+        let result = scanner.block_move_mode();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::AddressMode(AddressMode::BlockMove(0x00, 0x01)))
+        );
     }
 }
@@ -1,41 +1,29 @@
-use std::collections::HashSet;
+use std::str::FromStr;
 
 use forge_lib::{
-    directive::{ByteArgs, Directive, DirectiveName, WordArgs},
+    cpu::Cpu,
+    directive::{ByteArgs, Directive, DirectiveName, FarAddrArgs, Feature, WordArgs},
     expression::ExpressionNode,
+    object::{BSS_SEGMENT, CODE_SEGMENT, DATA_SEGMENT, RODATA_SEGMENT, ZEROPAGE_SEGMENT},
 };
 
-use crate::error::ParseError;
+use crate::{error::ParseError, lexer::TokenKind, warning::WarningKind};
 
-use super::{Scanner, Token, TokenResult};
+use super::{mnemonic::with_uppercase, Scanner, Token, TokenResult};
 
-impl Scanner {
+impl Scanner<'_> {
     pub fn directive_list(&mut self) -> TokenResult {
-        let directives: HashSet<&str> = [
-            "WORD", "ORG", "BYTE", "SEGMENT", "INCLUDE", "PROC", "ENDPROC", "ENUM", "ENDENUM",
-            "MACRO", "ENDMACRO", "SCOPE", "ENDSCOPE", "ADDR", "CODE"
-        ]
-        .iter()
-        .cloned()
-        .collect();
-
-        let start_pos = self.cursor;
-
-        while let Some(c) = self.peek() {
-            if !(c.is_ascii_uppercase() || c.is_ascii_lowercase()) {
-                break;
-            }
-            self.next();
+        // Directive names are letters, except the register width directives which end in a number
+        if self.peek_kind() != Some(TokenKind::Identifier) {
+            return Ok(None);
         }
 
-        let directive: String = self.input[start_pos..self.cursor].iter().collect();
-        let directive = directive.to_ascii_uppercase();
-
-        if directives.contains(directive.as_str()) {
-            Ok(Some(Token::DirectiveName(directive.into())))
-        } else {
-            self.cursor = start_pos;
-            Ok(None)
+        match with_uppercase(self.peek_text(), DirectiveName::from_name) {
+            Some(directive) => {
+                self.next();
+                Ok(Some(Token::DirectiveName(directive)))
+            }
+            None => Ok(None),
         }
     }
 
@@ -54,7 +42,7 @@ impl Scanner {
         if let Some(expression) = self.expression()? {
             match expression {
                 // If the expression is JUST an identifier, then it is likely just an ident anyways so skip
-                ExpressionNode::Identifier(_) => {}
+                ExpressionNode::Identifier(..) => {}
                 _ => return Ok(Some(WordArgs::Expression(expression))),
             }
         }
@@ -63,11 +51,8 @@ impl Scanner {
         self.cursor = start_pos;
 
         // Try an identifier
-        if let Some(token) = self.identifier()? {
-            match token {
-                Token::Identifier(ident) => return Ok(Some(WordArgs::Identifier(ident))),
-                _ => {}
-            }
+        if let Some(Token::Identifier(ident)) = self.identifier()? {
+            return Ok(Some(WordArgs::Identifier(ident)));
         }
 
         // Reset the cursor back
@@ -76,6 +61,29 @@ impl Scanner {
         Err(ParseError::ValidArgNotFound)
     }
 
+    /// Parses an argument to `.faraddr`, which can also be a 24-bit number written with six hex digits
+    pub fn directive_args_far_address(&mut self) -> Result<Option<FarAddrArgs>, ParseError> {
+        if let Some(value) = self.hex_number(6, false)? {
+            return Ok(Some(FarAddrArgs::Value(value)));
+        }
+
+        Ok(self.directive_args_word()?.map(FarAddrArgs::from))
+    }
+
+    /// Parses a list of symbol names separated by commas
+    pub fn directive_args_names(&mut self) -> Result<Vec<String>, ParseError> {
+        let mut names = Vec::new();
+        while let Some(Token::Identifier(name)) = self.identifier()? {
+            names.push(name);
+
+            if !self.consume(TokenKind::Comma) {
+                break;
+            }
+        }
+
+        Ok(names)
+    }
+
     pub fn directive_args_byte(&mut self) -> Result<Option<ByteArgs>, ParseError> {
         let start_pos = self.cursor;
 
@@ -84,7 +92,7 @@ impl Scanner {
             if number <= 0xFF {
                 return Ok(Some(ByteArgs::Value(number as u8)));
             } else {
-                return Err(ParseError::ValueTooLarge);
+                return Err(self.error_from(start_pos, ParseError::ValueTooLarge));
             }
         }
 
@@ -95,7 +103,7 @@ impl Scanner {
         if let Some(expression) = self.expression()? {
             match expression {
                 // If the expression is JUST an identifier, then it is likely just an ident anyways so skip
-                ExpressionNode::Identifier(_) => {}
+                ExpressionNode::Identifier(..) => {}
                 _ => return Ok(Some(ByteArgs::Expression(expression))),
             }
         }
@@ -104,11 +112,8 @@ impl Scanner {
         self.cursor = start_pos;
 
         // Try an identifier
-        if let Some(token) = self.identifier()? {
-            match token {
-                Token::Identifier(ident) => return Ok(Some(ByteArgs::Identifier(ident))),
-                _ => {}
-            }
+        if let Some(Token::Identifier(ident)) = self.identifier()? {
+            return Ok(Some(ByteArgs::Identifier(ident)));
         }
 
         // Reset the cursor back
@@ -117,20 +122,15 @@ impl Scanner {
         Err(ParseError::ValidArgNotFound)
     }
 
+    /// Parses a file name in double quotes, returning the name without them
     pub fn directive_args_include(&mut self) -> Result<Option<String>, ParseError> {
-        let start_pos = self.cursor;
-
-        // Now go through and consume until we don't hit a letter, number,
-        while let Some(c) = self.peek() {
-            if !(c.is_alphanumeric() || c == '_' || c == '.' || c == '/' || c == '\\') {
-                break;
-            }
-
-            // Move the cursor forward
-            self.next();
+        let text = self.peek_text();
+        if self.peek_kind() != Some(TokenKind::String) || text.len() < 2 || !text.ends_with('"') {
+            return Ok(None);
         }
+        self.next();
 
-        Ok(Some(self.input[start_pos..self.cursor].iter().collect()))
+        Ok(Some(text[1..text.len() - 1].to_string()))
     }
 
     pub fn directive_args_org(&mut self) -> Result<Option<u16>, ParseError> {
@@ -147,33 +147,87 @@ impl Scanner {
         Err(ParseError::ValidArgNotFound)
     }
 
+    /// Parses a name made of letters and digits, optionally wrapped in quotes (e.g. 65C02 or "65C02")
+    pub fn directive_args_name(&mut self) -> Result<String, ParseError> {
+        let text = self.peek_text();
+        let name = match self.peek_kind() {
+            Some(TokenKind::Identifier | TokenKind::DecimalNumber) => text,
+            Some(TokenKind::String) if text.len() > 2 && text.ends_with('"') => &text[1..text.len() - 1],
+            _ => return Err(ParseError::ValidArgNotFound),
+        };
+
+        if !name.bytes().all(|byte| byte.is_ascii_alphanumeric()) {
+            return Err(ParseError::ValidArgNotFound);
+        }
+        self.next();
+
+        Ok(name.to_string())
+    }
+
+    pub fn directive_args_cpu(&mut self) -> Result<Option<Cpu>, ParseError> {
+        let name = self.directive_args_name()?;
+
+        match Cpu::from_str(&name) {
+            Ok(cpu) => Ok(Some(cpu)),
+            Err(_) => Err(ParseError::UnknownCpu { name }),
+        }
+    }
+
+    pub fn directive_args_feature(&mut self) -> Result<Option<Feature>, ParseError> {
+        let name = self.directive_args_name()?;
+
+        match Feature::from_str(&name) {
+            Ok(feature) => Ok(Some(feature)),
+            Err(_) => Err(ParseError::UnknownFeature { name }),
+        }
+    }
+
+    /// Parses the arguments to `.warning`, which are `on` or `off` followed by a warning name or `all`
+    pub fn directive_args_warning(&mut self) -> Result<Option<(bool, String)>, ParseError> {
+        let enabled = if self.consume_keyword("on") {
+            true
+        } else if self.consume_keyword("off") {
+            false
+        } else {
+            return Err(ParseError::ValidArgNotFound);
+        };
+
+        // Warning names are words joined by dashes, which the lexer splits up
+        let mut name = String::new();
+        while let Some(lexeme) = self.peek() {
+            let joined = name.is_empty() || !lexeme.spaced;
+            if !joined || !matches!(lexeme.kind, TokenKind::Identifier | TokenKind::Minus) {
+                break;
+            }
+            name.push_str(&self.peek_text().to_ascii_lowercase());
+            self.next();
+        }
+
+        if name != "all" && WarningKind::from_str(&name).is_err() {
+            return Err(ParseError::UnknownWarning { name });
+        }
+
+        Ok(Some((enabled, name)))
+    }
+
     pub fn directive(&mut self) -> TokenResult {
         let start_pos = self.cursor;
 
-        // First try to parse out a dot
-        if !self.consume_char('.') {
+        // First try to parse out a dot, with the name straight after it
+        if !self.consume(TokenKind::Dot) || self.peek().is_some_and(|lexeme| lexeme.spaced) {
             self.cursor = start_pos;
             return Ok(None);
         }
 
         // Now try to get a directive name
         let directive_name = match self.attempt_parser(Self::directive_list)? {
-            Some(token) => match token {
-                Token::DirectiveName(name) => name,
-                _ => {
-                    self.cursor = start_pos;
-                    return Ok(None);
-                }
-            },
-            None => {
+            Some(Token::DirectiveName(name)) => name,
+            _ => {
                 self.cursor = start_pos;
                 return Ok(None);
             }
         };
 
-        // Consume any whitespace
-        self.consume_all_whitespace();
-
         // Now depending on the name parse out the instructions
         let directive = match directive_name {
             DirectiveName::ORG => match self.directive_args_org()? {
@@ -198,17 +252,11 @@ impl Scanner {
                 };
                 byte_args.push(arg);
                 loop {
-                    // Now consume any whitespace
-                    self.consume_all_whitespace();
-
                     // Consume a ,
-                    if !self.consume_char(',') {
+                    if !self.consume(TokenKind::Comma) {
                         break;
                     }
 
-                    // Now consume any whitespace
-                    self.consume_all_whitespace();
-
                     let arg = match self.directive_args_byte()? {
                         Some(arg) => arg,
                         None => break,
@@ -234,17 +282,11 @@ impl Scanner {
                 };
                 word_args.push(arg);
                 loop {
-                    // Now consume any whitespace
-                    self.consume_all_whitespace();
-
                     // Consume a ,
-                    if !self.consume_char(',') {
+                    if !self.consume(TokenKind::Comma) {
                         break;
                     }
 
-                    // Consume any whitespace after the comma
-                    self.consume_all_whitespace();
-
                     let arg = match self.directive_args_word()? {
                         Some(arg) => arg,
                         None => break,
@@ -256,59 +298,30 @@ impl Scanner {
                 Directive::WORD(word_args)
             }
             DirectiveName::SEGMENT => {
-                // Consume any whitespace
-                self.consume_all_whitespace();
-
-                // Consume a "
-                if !self.consume_char('"') {
-                    self.cursor = start_pos;
-                    return Ok(None);
-                }
-
-                // Get an identifier
-                let ident = match self.identifier()? {
-                    Some(Token::Identifier(ident)) => ident,
+                // The segment name is an identifier in double quotes
+                let ident = match self.directive_args_include()? {
+                    Some(ident) if ident.starts_with(|c: char| c.is_ascii_alphabetic())
+                        && ident.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => ident,
                     _ => {
                         self.cursor = start_pos;
                         return Ok(None);
                     }
                 };
 
-                // Consume a "
-                if !self.consume_char('"') {
-                    self.cursor = start_pos;
-                    return Ok(None);
-                }
-
                 Directive::SEGMENT(ident)
             }
             DirectiveName::INCLUDE => {
-                self.consume_all_whitespace();
-
-                // Consume a "
-                if !self.consume_char('"') {
-                    self.cursor = start_pos;
-                    return Ok(None);
-                }
-
-                // Get an identifier
                 let inc_file = match self.directive_args_include()? {
                     Some(file) => file,
-                    None => return Ok(None),
+                    None => {
+                        self.cursor = start_pos;
+                        return Ok(None);
+                    }
                 };
 
-                // Consume a "
-                if !self.consume_char('"') {
-                    self.cursor = start_pos;
-                    return Ok(None);
-                }
-
                 Directive::INCLUDE(inc_file)
             }
             DirectiveName::PROC => {
-                // Consume any whitespace
-                self.consume_all_whitespace();
-
                 // Get an identifier
                 let ident = match self.identifier()? {
                     Some(Token::Identifier(ident)) => ident,
@@ -322,9 +335,6 @@ impl Scanner {
             }
             DirectiveName::ENDPROC => Directive::ENDPROC,
             DirectiveName::ENUM => {
-                // Consume any whitespace
-                self.consume_all_whitespace();
-
                 // Get an identifier
                 let ident = match self.identifier()? {
                     Some(Token::Identifier(ident)) => ident,
@@ -337,9 +347,6 @@ impl Scanner {
                 Directive::ENUM(ident)
             }
             DirectiveName::SCOPE => {
-                // Consume any whitespace
-                self.consume_all_whitespace();
-
                 // Get an identifier
                 let ident = match self.identifier()? {
                     Some(Token::Identifier(ident)) => ident,
@@ -354,9 +361,6 @@ impl Scanner {
             DirectiveName::ENDSCOPE => Directive::ENDSCOPE,
             DirectiveName::ENDENUM => Directive::ENDENUM,
             DirectiveName::MACRO => {
-                // Consume any whitespace
-                self.consume_all_whitespace();
-
                 // Get an identifier
                 let ident = match self.identifier()? {
                     Some(Token::Identifier(ident)) => ident,
@@ -369,45 +373,138 @@ impl Scanner {
                 Directive::MACRO(ident)
             }
             DirectiveName::ENDMACRO => Directive::ENDMACRO,
-            DirectiveName::CODE => {
-                Directive::SEGMENT(String::from("CODE"))
-            },
+            DirectiveName::CODE => Directive::SEGMENT(String::from(CODE_SEGMENT)),
+            DirectiveName::RODATA => Directive::SEGMENT(String::from(RODATA_SEGMENT)),
+            DirectiveName::DATA => Directive::SEGMENT(String::from(DATA_SEGMENT)),
+            DirectiveName::BSS => Directive::SEGMENT(String::from(BSS_SEGMENT)),
+            DirectiveName::ZEROPAGE => Directive::SEGMENT(String::from(ZEROPAGE_SEGMENT)),
             DirectiveName::ADDR => {
-                // The bytes could be in a list. First attempt to get something
-                let mut word_args = Vec::new();
+                // The addresses could be in a list. First attempt to get something
+                let mut addr_args = Vec::new();
 
                 // Need at least one arg
                 let arg = match self.directive_args_word()? {
                     Some(arg) => arg,
                     None => {
                         return Err(ParseError::DirectiveWithNoArg {
-                            directive: String::from("WORD"),
+                            directive: String::from("ADDR"),
                         })
                     }
                 };
-                word_args.push(arg);
+                addr_args.push(arg);
                 loop {
-                    // Now consume any whitespace
-                    self.consume_all_whitespace();
-
                     // Consume a ,
-                    if !self.consume_char(',') {
+                    if !self.consume(TokenKind::Comma) {
                         break;
                     }
 
-                    // Consume any whitespace after the comma
-                    self.consume_all_whitespace();
-
                     let arg = match self.directive_args_word()? {
                         Some(arg) => arg,
                         None => break,
                     };
 
-                    word_args.push(arg);
+                    addr_args.push(arg);
                 }
 
-                Directive::WORD(word_args)
+                Directive::ADDR(addr_args)
             }
+            DirectiveName::FARADDR => {
+                let mut addr_args = Vec::new();
+
+                // Need at least one arg
+                let arg = match self.directive_args_far_address()? {
+                    Some(arg) => arg,
+                    None => {
+                        return Err(ParseError::DirectiveWithNoArg {
+                            directive: String::from("FARADDR"),
+                        })
+                    }
+                };
+                addr_args.push(arg);
+                loop {
+                    // Consume a ,
+                    if !self.consume(TokenKind::Comma) {
+                        break;
+                    }
+
+                    let arg = match self.directive_args_far_address()? {
+                        Some(arg) => arg,
+                        None => break,
+                    };
+
+                    addr_args.push(arg);
+                }
+
+                Directive::FARADDR(addr_args)
+            }
+            DirectiveName::EXPORT | DirectiveName::EXPORTZP | DirectiveName::IMPORT | DirectiveName::IMPORTZP | DirectiveName::GLOBAL => {
+                let names = self.directive_args_names()?;
+                if names.is_empty() {
+                    return Err(ParseError::DirectiveWithNoArg {
+                        directive: format!("{:?}", directive_name),
+                    });
+                }
+
+                match directive_name {
+                    DirectiveName::EXPORT => Directive::EXPORT { names, zero_page: false },
+                    DirectiveName::EXPORTZP => Directive::EXPORT { names, zero_page: true },
+                    DirectiveName::IMPORT => Directive::IMPORT { names, zero_page: false },
+                    DirectiveName::IMPORTZP => Directive::IMPORT { names, zero_page: true },
+                    _ => Directive::GLOBAL(names),
+                }
+            }
+            DirectiveName::CPU => {
+                let cpu = match self.directive_args_cpu()? {
+                    Some(cpu) => cpu,
+                    None => {
+                        return Err(ParseError::DirectiveWithNoArg {
+                            directive: String::from("CPU"),
+                        })
+                    }
+                };
+
+                // Every instruction from here on is checked against the new CPU
+                self.cpu = cpu;
+
+                Directive::CPU(cpu)
+            }
+            DirectiveName::FEATURE => {
+                let feature_pos = self.cursor;
+                let feature = match self.directive_args_feature()? {
+                    Some(feature) => feature,
+                    None => {
+                        return Err(ParseError::DirectiveWithNoArg {
+                            directive: String::from("FEATURE"),
+                        })
+                    }
+                };
+
+                match feature {
+                    Feature::Illegal => {
+                        self.cpu = match self.cpu.with_illegal_opcodes() {
+                            Some(cpu) => cpu,
+                            None => {
+                                let error = ParseError::UnsupportedFeature { feature, cpu: self.cpu };
+                                return Err(self.error_from(feature_pos, error));
+                            }
+                        };
+                    }
+                }
+
+                Directive::FEATURE(feature)
+            }
+            DirectiveName::A8 => Directive::A8,
+            DirectiveName::A16 => Directive::A16,
+            DirectiveName::I8 => Directive::I8,
+            DirectiveName::I16 => Directive::I16,
+            DirectiveName::WARNING => match self.directive_args_warning()? {
+                Some((enabled, name)) => Directive::WARNING { enabled, name },
+                None => {
+                    return Err(ParseError::DirectiveWithNoArg {
+                        directive: String::from("WARNING"),
+                    })
+                }
+            },
         };
 
         Ok(Some(Token::Directive(directive)))
@@ -417,8 +514,10 @@ impl Scanner {
 #[cfg(test)]
 mod directive_test {
     use forge_lib::{
-        directive::{ByteArgs, Directive, DirectiveName, WordArgs},
+        cpu::Cpu,
+        directive::{ByteArgs, Directive, DirectiveName, FarAddrArgs, Feature, WordArgs},
         expression::{BinaryOp, ExpressionNode},
+        span::Span,
     };
 
     use crate::{
@@ -502,20 +601,24 @@ mod directive_test {
                         Box::new(ExpressionNode::Parenthesized(Box::new(
                             ExpressionNode::BinOp(
                                 BinaryOp::And,
-                                Box::new(ExpressionNode::Identifier("mapper".to_string())),
-                                Box::new(ExpressionNode::Number(0x0F))
+                                Box::new(ExpressionNode::Identifier("mapper".to_string(), Span::default())),
+                                Box::new(ExpressionNode::Number(0x0F, Span::default())),
+                                Span::default(),
                             )
-                        ))),
-                        Box::new(ExpressionNode::Number(4))
+                        ), Span::default())),
+                        Box::new(ExpressionNode::Number(4, Span::default())),
+                        Span::default(),
                     )
-                ))),
+                ), Span::default())),
                 Box::new(ExpressionNode::Parenthesized(Box::new(
                     ExpressionNode::BinOp(
                         BinaryOp::And,
-                        Box::new(ExpressionNode::Identifier("mirroring".to_string())),
-                        Box::new(ExpressionNode::Number(1))
+                        Box::new(ExpressionNode::Identifier("mirroring".to_string(), Span::default())),
+                        Box::new(ExpressionNode::Number(1, Span::default())),
+                        Span::default(),
                     )
-                )))
+                ), Span::default())),
+                Span::default(),
             )))
         );
     }
@@ -566,20 +669,24 @@ mod directive_test {
                         Box::new(ExpressionNode::Parenthesized(Box::new(
                             ExpressionNode::BinOp(
                                 BinaryOp::And,
-                                Box::new(ExpressionNode::Identifier("mapper".to_string())),
-                                Box::new(ExpressionNode::Number(0x0F))
+                                Box::new(ExpressionNode::Identifier("mapper".to_string(), Span::default())),
+                                Box::new(ExpressionNode::Number(0x0F, Span::default())),
+                                Span::default(),
                             )
-                        ))),
-                        Box::new(ExpressionNode::Number(4))
+                        ), Span::default())),
+                        Box::new(ExpressionNode::Number(4, Span::default())),
+                        Span::default(),
                     )
-                ))),
+                ), Span::default())),
                 Box::new(ExpressionNode::Parenthesized(Box::new(
                     ExpressionNode::BinOp(
                         BinaryOp::And,
-                        Box::new(ExpressionNode::Identifier("mirroring".to_string())),
-                        Box::new(ExpressionNode::Number(1))
+                        Box::new(ExpressionNode::Identifier("mirroring".to_string(), Span::default())),
+                        Box::new(ExpressionNode::Number(1, Span::default())),
+                        Span::default(),
                     )
-                )))
+                ), Span::default())),
+                Span::default(),
             )))
         );
 
@@ -654,4 +761,166 @@ mod directive_test {
             Some(Token::Directive(Directive::SCOPE(String::from("Player"))))
         );
     }
+
+    #[test]
+    fn test_parse_directive_segment_shorthands() {
+        let shorthands = [
+            (".code", "CODE"),
+            (".RODATA", "RODATA"),
+            (".data", "DATA"),
+            (".bss", "BSS"),
+            (".zeropage", "ZEROPAGE"),
+        ];
+
+        for (source, segment) in shorthands {
+            let mut scanner = Scanner::new(source);
+            assert_eq!(
+                scanner.directive(),
+                Ok(Some(Token::Directive(Directive::SEGMENT(String::from(segment)))))
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_directive_addr() {
+        let mut scanner = Scanner::new(".addr nmi, reset, $FFFA");
+        assert_eq!(
+            scanner.directive(),
+            Ok(Some(Token::Directive(Directive::ADDR(vec![
+                WordArgs::Identifier(String::from("nmi")),
+                WordArgs::Identifier(String::from("reset")),
+                WordArgs::Value(0xFFFA),
+            ]))))
+        );
+
+        let mut scanner = Scanner::new(".faraddr handler, $018000, $8000");
+        assert_eq!(
+            scanner.directive(),
+            Ok(Some(Token::Directive(Directive::FARADDR(vec![
+                FarAddrArgs::Identifier(String::from("handler")),
+                FarAddrArgs::Value(0x018000),
+                FarAddrArgs::Value(0x8000),
+            ]))))
+        );
+
+        let mut scanner = Scanner::new(".addr");
+        assert_eq!(scanner.directive(), Err(ParseError::ValidArgNotFound));
+    }
+
+    #[test]
+    fn test_parse_directive_linkage() {
+        let mut scanner = Scanner::new(".export reset, nmi");
+        assert_eq!(
+            scanner.directive(),
+            Ok(Some(Token::Directive(Directive::EXPORT {
+                names: vec![String::from("reset"), String::from("nmi")],
+                zero_page: false,
+            })))
+        );
+
+        let mut scanner = Scanner::new(".importzp ptr");
+        assert_eq!(
+            scanner.directive(),
+            Ok(Some(Token::Directive(Directive::IMPORT { names: vec![String::from("ptr")], zero_page: true })))
+        );
+
+        let mut scanner = Scanner::new(".global main");
+        assert_eq!(
+            scanner.directive(),
+            Ok(Some(Token::Directive(Directive::GLOBAL(vec![String::from("main")]))))
+        );
+
+        let mut scanner = Scanner::new(".import");
+        assert_eq!(
+            scanner.directive(),
+            Err(ParseError::DirectiveWithNoArg { directive: String::from("IMPORT") })
+        );
+    }
+
+    #[test]
+    fn test_parse_directive_cpu() {
+        let mut scanner = Scanner::new(".cpu \"65c02\"");
+        let result = scanner.directive();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::Directive(Directive::CPU(Cpu::Wdc65C02)))
+        );
+        assert_eq!(scanner.cpu, Cpu::Wdc65C02);
+
+        let mut scanner = Scanner::new(".CPU 6502");
+        let result = scanner.directive();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::Directive(Directive::CPU(Cpu::Mos6502)))
+        );
+
+        let mut scanner = Scanner::new(".cpu \"z80\"");
+        let result = scanner.directive();
+
+        assert_eq!(
+            result,
+            Err(ParseError::UnknownCpu { name: String::from("z80") })
+        );
+    }
+
+    #[test]
+    fn test_parse_directive_feature_illegal() {
+        let mut scanner = Scanner::new(".feature illegal");
+        let result = scanner.directive();
+
+        assert_eq!(
+            result,
+            Ok(Some(Token::Directive(Directive::FEATURE(Feature::Illegal))))
+        );
+        assert_eq!(scanner.cpu, Cpu::Mos6502X);
+
+        let mut scanner = Scanner::new(".feature illegal");
+        scanner.cpu = Cpu::Wdc65C02;
+        let result = scanner.directive();
+
+        assert_eq!(
+            result,
+            Err(ParseError::UnsupportedFeature { feature: Feature::Illegal, cpu: Cpu::Wdc65C02 })
+        );
+    }
+
+    #[test]
+    fn test_parse_directive_register_widths() {
+        let mut scanner = Scanner::new(".a16");
+        assert_eq!(scanner.directive(), Ok(Some(Token::Directive(Directive::A16))));
+
+        let mut scanner = Scanner::new(".A8");
+        assert_eq!(scanner.directive(), Ok(Some(Token::Directive(Directive::A8))));
+
+        let mut scanner = Scanner::new(".i16");
+        assert_eq!(scanner.directive(), Ok(Some(Token::Directive(Directive::I16))));
+
+        let mut scanner = Scanner::new(".i8");
+        assert_eq!(scanner.directive(), Ok(Some(Token::Directive(Directive::I8))));
+    }
+
+    #[test]
+    fn test_parse_directive_warning() {
+        let mut scanner = Scanner::new(".warning off unused-symbol");
+        assert_eq!(
+            scanner.directive(),
+            Ok(Some(Token::Directive(Directive::WARNING { enabled: false, name: String::from("unused-symbol") })))
+        );
+
+        let mut scanner = Scanner::new(".WARNING On ALL");
+        assert_eq!(
+            scanner.directive(),
+            Ok(Some(Token::Directive(Directive::WARNING { enabled: true, name: String::from("all") })))
+        );
+
+        let mut scanner = Scanner::new(".warning off unused-symbols");
+        assert_eq!(
+            scanner.directive(),
+            Err(ParseError::UnknownWarning { name: String::from("unused-symbols") })
+        );
+    }
 }
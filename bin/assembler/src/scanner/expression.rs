@@ -1,20 +1,20 @@
-use forge_lib::expression::{BinaryOp, ExpressionNode};
+use forge_lib::expression::{BinaryOp, ExpressionNode, UnaryOp};
 
-use crate::error::ParseError;
+use crate::{error::ParseError, lexer::TokenKind};
 
 use super::{
     address::{parse_bin16_with_position, parse_hex16_with_position},
     Scanner, Token,
 };
 
-impl Scanner {
+impl Scanner<'_> {
     pub fn parse_scoped_reference(&mut self) -> Result<Option<ExpressionNode>, ParseError> {
+        let start_pos = self.cursor;
         let mut references = Vec::new();
 
         if let Some(Token::Identifier(id)) = self.identifier()? {
             references.push(id);
-            while self.peek_chars("::") {
-                self.consume_chars(2); // Consume "::"
+            while self.consume_adjacent(TokenKind::DoubleColon) {
                 if let Some(Token::Identifier(id)) = self.identifier()? {
                     references.push(id);
                 } else {
@@ -22,10 +22,11 @@ impl Scanner {
                 }
             }
 
+            let span = self.span_from(start_pos);
             if references.len() > 1 {
-                return Ok(Some(ExpressionNode::ScopedReference(references)));
+                return Ok(Some(ExpressionNode::ScopedReference(references, span)));
             } else {
-                return Ok(Some(ExpressionNode::Identifier(references[0].clone())));
+                return Ok(Some(ExpressionNode::Identifier(references.swap_remove(0), span)));
             }
         }
 
@@ -33,131 +34,78 @@ impl Scanner {
     }
 
     pub fn high_precedence_operator(&mut self) -> Result<Option<BinaryOp>, ParseError> {
-        let start_pos = self.cursor;
+        let op = match self.peek_kind() {
+            Some(TokenKind::Star) => BinaryOp::Multiply,
+            Some(TokenKind::Slash) => BinaryOp::Divide,
+            Some(TokenKind::ShiftLeft) => BinaryOp::ShiftLeft,
+            Some(TokenKind::ShiftRight) => BinaryOp::ShiftRight,
+            _ => return Ok(None),
+        };
+        self.next();
 
-        match self.peek() {
-            Some('*') => {
-                self.next();
-                Ok(Some(BinaryOp::Multiply))
-            }
-            Some('/') => {
-                self.next();
-                Ok(Some(BinaryOp::Divide))
-            }
-            Some('<') => {
-                self.next(); // Consume <
-                if let Some('<') = self.peek() {
-                    self.next(); // consume second '<'
-                    Ok(Some(BinaryOp::ShiftLeft))
-                } else {
-                    self.cursor = start_pos;
-                    Ok(None)
-                }
-            }
-            Some('>') => {
-                self.next();
-                if let Some('>') = self.peek() {
-                    self.next();
-                    Ok(Some(BinaryOp::ShiftRight))
-                } else {
-                    self.cursor = start_pos;
-                    Ok(None)
-                }
-            }
-            _ => Ok(None),
-        }
+        Ok(Some(op))
     }
 
     pub fn low_precedence_operator(&mut self) -> Result<Option<BinaryOp>, ParseError> {
-        match self.peek() {
-            Some('+') => {
-                self.next();
-                Ok(Some(BinaryOp::Add))
-            }
-            Some('-') => {
-                self.next();
-                Ok(Some(BinaryOp::Subtract))
-            }
-            Some('|') => {
-                self.next();
-                Ok(Some(BinaryOp::Or))
-            }
-            Some('&') => {
-                self.next();
-                Ok(Some(BinaryOp::And))
-            }
-            _ => Ok(None),
-        }
+        let op = match self.peek_kind() {
+            Some(TokenKind::Plus) => BinaryOp::Add,
+            Some(TokenKind::Minus) => BinaryOp::Subtract,
+            Some(TokenKind::Pipe) => BinaryOp::Or,
+            Some(TokenKind::Ampersand) => BinaryOp::And,
+            _ => return Ok(None),
+        };
+        self.next();
+
+        Ok(Some(op))
     }
 
     pub fn number(&mut self) -> Result<Option<u16>, ParseError> {
-        let _start_pos = self.cursor;
-
-        if let Some(c) = self.peek() {
-            match c {
-                // Hex number
-                '$' => {
-                    self.next();
-
-                    // Now parse until there is no more hex digits
-                    let parse_pos = self.cursor;
-                    while let Some(c) = self.peek() {
-                        if !c.is_ascii_hexdigit() {
-                            break;
-                        }
-                        self.next();
-                    }
-
-                    let value: String = self.input[parse_pos..self.cursor].iter().collect();
-                    let number = parse_hex16_with_position(&value, self.cursor)?;
-
-                    // Convert to a string
-                    Ok(Some(number))
-                }
-                // Binary
-                '%' => {
-                    self.next();
-
-                    // Now parse until there is no more binary digits
-                    let parse_pos = self.cursor;
-                    while let Some(_) = self.peek() {
-                        if !self.consume_char('0') && !self.consume_char('1') {
-                            break;
-                        }
-                    }
-
-                    let value: String = self.input[parse_pos..self.cursor].iter().collect();
-                    let number = parse_bin16_with_position(&value, self.cursor)?;
-                    Ok(Some(number))
-                }
-                // Decimal
-                char if char.is_digit(10) => {
-                    // Now parse until there are no more base 10 digits
-                    let parse_pos = self.cursor;
-                    while let Some(c) = self.peek() {
-                        if !c.is_digit(10) {
-                            break;
-                        }
-                        self.next();
-                    }
-
-                    let value: String = self.input[parse_pos..self.cursor].iter().collect();
-                    let number = value
-                        .parse::<u16>()
-                        .map_err(|_| ParseError::ParseIntError {
-                            msg: format!("failed to convert {} to base 10", value),
-                            position: self.cursor,
-                        })?;
-                    Ok(Some(number))
-                }
-                _ => Ok(None),
-            }
-        } else {
-            Ok(None)
+        let text = self.peek_text();
+        let radix = match self.peek_kind() {
+            Some(TokenKind::HexNumber) => 16,
+            Some(TokenKind::BinaryNumber) => 2,
+            Some(TokenKind::DecimalNumber) => 10,
+            _ => return Ok(None),
+        };
+
+        // The lexer takes any letters or digits after a number, so point out the first bad one
+        if let Some(c) = text.chars().skip(1).find(|c| !c.is_digit(radix)) {
+            return Err(ParseError::ParseIntError { msg: format!("found invalid character: {}", c), position: self.cursor });
         }
+
+        let number = match radix {
+            16 => parse_hex16_with_position(&text[1..], self.cursor)?,
+            2 => parse_bin16_with_position(&text[1..], self.cursor)?,
+            _ => text.parse::<u16>().map_err(|_| ParseError::ParseIntError {
+                msg: format!("failed to convert {} to base 10", text),
+                position: self.cursor,
+            })?,
+        };
+        self.next();
+
+        Ok(Some(number))
     }
 
     pub fn expression(&mut self) -> Result<Option<ExpressionNode>, ParseError> {
+        // A < or > at the start takes the low or high byte of everything after it
+        let start_pos = self.cursor;
+        let unary_op = match self.peek_kind() {
+            Some(TokenKind::Less) => Some(UnaryOp::LowByte),
+            Some(TokenKind::Greater) => Some(UnaryOp::HighByte),
+            _ => None,
+        };
+
+        if let Some(op) = unary_op {
+            self.next();
+            return match self.expression()? {
+                Some(expr) => Ok(Some(ExpressionNode::Unary(op, Box::new(expr), self.span_from(start_pos)))),
+                None => {
+                    self.cursor = start_pos;
+                    Ok(None)
+                }
+            };
+        }
+
         let mut left = match self.term()? {
             Some(left) => left,
             None => return Ok(None),
@@ -168,7 +116,8 @@ impl Scanner {
                 Some(right) => right,
                 None => return Ok(None),
             };
-            left = ExpressionNode::BinOp(op, Box::new(left), Box::new(right));
+            let span = left.span().join(right.span());
+            left = ExpressionNode::BinOp(op, Box::new(left), Box::new(right), span);
         }
 
         Ok(Some(left))
@@ -185,46 +134,32 @@ impl Scanner {
                 Some(right) => right,
                 None => return Ok(None),
             };
-            left = ExpressionNode::BinOp(op, Box::new(left), Box::new(right))
+            let span = left.span().join(right.span());
+            left = ExpressionNode::BinOp(op, Box::new(left), Box::new(right), span)
         }
 
         Ok(Some(left))
     }
 
     fn factor(&mut self) -> Result<Option<ExpressionNode>, ParseError> {
-        // Consume all whitespaces
-        self.consume_all_whitespace();
-
+        let start_pos = self.cursor;
         let result = if let Some(num) = self.number()? {
-            ExpressionNode::Number(num)
+            ExpressionNode::Number(num, self.span_from(start_pos))
         } else if let Some(ref_expr) = self.parse_scoped_reference()? {
             ref_expr
-        } else if let Some(token) = self.identifier()? {
-            match token {
-                Token::Identifier(ident) => ExpressionNode::Identifier(ident),
-                _ => {
-                    return Err(ParseError::UnexpectedToken {
-                        expected: Token::Identifier("".to_string()),
-                        received: token,
-                        position: self.cursor,
-                    })
-                }
-            }
-        } else if self.consume_char('(') {
+        } else if self.consume(TokenKind::LeftParen) {
             let expr = match self.expression()? {
                 Some(expr) => expr,
                 None => return Ok(None),
             };
-            if !self.consume_char(')') {
+            if !self.consume(TokenKind::RightParen) {
                 return Err(ParseError::MissingClosingParenthesis);
             }
-            ExpressionNode::Parenthesized(Box::new(expr))
+            ExpressionNode::Parenthesized(Box::new(expr), self.span_from(start_pos))
         } else {
             return Ok(None);
         };
 
-        self.consume_all_whitespace();
-
         Ok(Some(result))
     }
 }
@@ -233,13 +168,22 @@ impl Scanner {
 mod expression_tests {
     use std::collections::HashMap;
 
-    use forge_lib::expression::evaluate_expression;
+    use forge_lib::span::Span;
 
-    use crate::scanner::{
-        expression::{BinaryOp, ExpressionNode},
-        Scanner,
+    use crate::{
+        codegen::SymbolResolver,
+        scanner::{
+            expression::{BinaryOp, ExpressionNode},
+            Scanner,
+        },
     };
 
+    /// Works out the value of an expression the way codegen does, when every name in it is a constant
+    fn evaluate(expression: &ExpressionNode, constant_map: &HashMap<String, u16>) -> u16 {
+        let label_map = HashMap::new();
+        SymbolResolver { constant_map, label_map: &label_map }.constant_value(expression).unwrap()
+    }
+
     #[test]
     fn test_parse_high_precedence_operators() {
         let mut scanner = Scanner::new("*/<<>>");
@@ -342,20 +286,24 @@ mod expression_tests {
                         Box::new(ExpressionNode::Parenthesized(Box::new(
                             ExpressionNode::BinOp(
                                 BinaryOp::And,
-                                Box::new(ExpressionNode::Identifier("mapper".to_string())),
-                                Box::new(ExpressionNode::Number(0x0F))
+                                Box::new(ExpressionNode::Identifier("mapper".to_string(), Span::default())),
+                                Box::new(ExpressionNode::Number(0x0F, Span::default())),
+                                Span::default(),
                             )
-                        ))),
-                        Box::new(ExpressionNode::Number(4))
+                        ), Span::default())),
+                        Box::new(ExpressionNode::Number(4, Span::default())),
+                        Span::default(),
                     )
-                ))),
+                ), Span::default())),
                 Box::new(ExpressionNode::Parenthesized(Box::new(
                     ExpressionNode::BinOp(
                         BinaryOp::And,
-                        Box::new(ExpressionNode::Identifier("mirroring".to_string())),
-                        Box::new(ExpressionNode::Number(1))
+                        Box::new(ExpressionNode::Identifier("mirroring".to_string(), Span::default())),
+                        Box::new(ExpressionNode::Number(1, Span::default())),
+                        Span::default(),
                     )
-                )))
+                ), Span::default())),
+                Span::default(),
             ))
         );
 
@@ -373,7 +321,7 @@ mod expression_tests {
 
         let constant_map: HashMap<String, u16> = HashMap::new();
 
-        let num = evaluate_expression(&expression, &constant_map);
+        let num = evaluate(&expression, &constant_map);
         assert_eq!(num, 6);
 
         let mut scanner = Scanner::new("((mapper & $0f) << 4) | (mirroring & 1)");
@@ -383,10 +331,23 @@ mod expression_tests {
         constant_map.insert(String::from("mapper"), 0);
         constant_map.insert(String::from("mirroring"), 1);
 
-        let num = evaluate_expression(&expression, &constant_map);
+        let num = evaluate(&expression, &constant_map);
         assert_eq!(num, 1);
     }
 
+    #[test]
+    fn test_eval_byte_operators() {
+        let constant_map = HashMap::from([(String::from("vector"), 0x1234)]);
+        let eval = |source| {
+            let expression = Scanner::new(source).expression().unwrap().unwrap();
+            evaluate(&expression, &constant_map)
+        };
+
+        assert_eq!(eval("<vector"), 0x34);
+        assert_eq!(eval(">vector"), 0x12);
+        assert_eq!(eval(">vector + $100"), 0x13);
+    }
+
     #[test]
     fn test_parse_expression_scopes() {
         let mut scanner = Scanner::new("Joypad::Down");
@@ -398,7 +359,7 @@ mod expression_tests {
             Some(ExpressionNode::ScopedReference(vec![
                 String::from("Joypad"),
                 String::from("Down")
-            ]))
+            ], Span::default()))
         );
 
         let mut scanner = Scanner::new("PPUSTATUS");
@@ -407,7 +368,7 @@ mod expression_tests {
         assert!(result.is_ok());
         assert_eq!(
             result.unwrap(),
-            Some(ExpressionNode::Identifier(String::from("PPUSTATUS")))
+            Some(ExpressionNode::Identifier(String::from("PPUSTATUS"), Span::default()))
         );
 
         let mut scanner = Scanner::new("Player::Joypad::Down");
@@ -420,7 +381,22 @@ mod expression_tests {
                 String::from("Player"),
                 String::from("Joypad"),
                 String::from("Down")
-            ]))
+            ], Span::default()))
         );
     }
+
+    #[test]
+    fn test_parse_expression_spans() {
+        let mut scanner = Scanner::new("(mapper & $0f) << 4");
+        let expression = scanner.expression().unwrap().unwrap();
+
+        assert_eq!(expression.span(), Span::new(0, 1, 1, 19));
+        match expression {
+            ExpressionNode::BinOp(_, left, right, _) => {
+                assert_eq!(left.span(), Span::new(0, 1, 1, 14));
+                assert_eq!(right.span(), Span::new(0, 1, 19, 1));
+            }
+            _ => panic!("expected a binary operation"),
+        }
+    }
 }
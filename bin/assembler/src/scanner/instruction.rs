@@ -1,13 +1,13 @@
-use forge_lib::{address::AddressMode, mnemonic::Mnemonic, operand::Operand, instruction::Instruction, expression::ExpressionNode};
+use forge_lib::{address::{AddressMode, AddressSize}, error::ForgeError, mnemonic::Mnemonic, operand::Operand, instruction::Instruction, expression::ExpressionNode, span::Span};
 
-use crate::error::ParseError;
+use crate::{error::ParseError, lexer::TokenKind, warning::Warning};
 
 use super::{
     Scanner, Token,
     TokenResult,
 };
 
-impl Scanner {
+impl Scanner<'_> {
     pub fn instruction(&mut self) -> TokenResult {
         let start_pos = self.cursor;
 
@@ -17,8 +17,8 @@ impl Scanner {
             Some(token) => {
                 self.cursor = start_pos;
                 return Err(ParseError::UnexpectedToken {
-                    expected: Token::Mnemonic(Mnemonic::ADC),
-                    received: token,
+                    expected: Box::new(Token::Mnemonic(Mnemonic::ADC)),
+                    received: Box::new(token),
                     position: self.cursor,
                 });
             }
@@ -28,49 +28,119 @@ impl Scanner {
             }
         };
 
-        // Consume whitespaces
-        self.consume_all_whitespace();
+        // An address size can be forced with a suffix on the mnemonic (LDA.a)
+        let mut address_size = self.address_size_suffix();
 
-        // Get operand
-        let operand = match self.operand()? {
+        // Get operand. The branch on bit and block move instructions take two operands
+        let operand_pos = self.cursor;
+        let operand = if mnemonic.is_bit_branch() {
+            self.zero_page_relative_mode()?
+                .map(|token| match token {
+                    Token::AddressMode(address_mode) => Token::Operand(Operand::AddressMode(address_mode)),
+                    token => token,
+                })
+        } else if mnemonic.is_block_move() {
+            self.block_move_mode()?
+                .map(|token| match token {
+                    Token::AddressMode(address_mode) => Token::Operand(Operand::AddressMode(address_mode)),
+                    token => token,
+                })
+        } else {
+            // Or with a prefix on the operand (a:label)
+            address_size = self.address_size_prefix().or(address_size);
+            self.operand()?
+        };
+
+        let operand = match operand {
             Some(Token::Operand(operand)) => Some(operand),
             Some(token) => {
                 self.cursor = start_pos;
                 return Err(ParseError::UnexpectedToken {
-                    expected: Token::Operand(Operand::AddressMode(AddressMode::Accumulator)),
-                    received: token,
+                    expected: Box::new(Token::Operand(Operand::AddressMode(AddressMode::Accumulator))),
+                    received: Box::new(token),
                     position: self.cursor,
                 });
             }
             None => None,
         };
 
+        // A numeric address is given the forced size straight away
+        let operand = match (operand, address_size) {
+            (Some(Operand::AddressMode(address_mode)), Some(size)) => match address_mode.with_size(size) {
+                Ok(address_mode) => Some(Operand::AddressMode(address_mode)),
+                Err(ForgeError::ZeroPageOutOfRange { value }) => {
+                    return Err(self.error_from(operand_pos, ParseError::ZeroPageOutOfRange { value }));
+                }
+                Err(error) => return Err(ParseError::Assembly { error }),
+            },
+            (operand, _) => operand,
+        };
+
         // Construct the instruction
-        let instruction = Instruction { mnemonic, operand };
+        let instruction = Instruction { mnemonic, operand, address_size, span: self.span_from(start_pos) };
 
         Ok(Some(Token::Instruction(instruction)))
     }
 
+    /// Warns about code that relies on decimal mode on a CPU without it. The decimal flag is
+    /// followed in source order, so an ADC is only flagged if an SED comes before it without a CLD
+    pub fn check_decimal_mode(&mut self, mnemonic: Mnemonic, span: Span) {
+        let cpu = self.cpu;
+
+        match mnemonic {
+            Mnemonic::SED => {
+                self.decimal_flag = true;
+                if !cpu.has_decimal_mode() {
+                    self.warnings.push((Warning::DecimalModeUnavailable { mnemonic, cpu }, span));
+                }
+            }
+            Mnemonic::CLD => {
+                if self.decimal_flag && !cpu.has_decimal_mode() {
+                    self.warnings.push((Warning::DecimalModeUnavailable { mnemonic, cpu }, span));
+                }
+                self.decimal_flag = false;
+            }
+            Mnemonic::ADC | Mnemonic::SBC if self.decimal_flag && !cpu.has_decimal_mode() => {
+                self.warnings.push((Warning::DecimalArithmetic { mnemonic, cpu }, span));
+            }
+            _ => {}
+        }
+    }
+
     pub fn operand(&mut self) -> TokenResult {
         let start_pos = self.cursor;
         let mut error: Option<ParseError> = None;
 
-        match self.indirect_index_y_mode() {
-            Ok(value) => match value {
-                Some(Token::AddressMode(addr_mode)) => {
-                    return Ok(Some(Token::Operand(Operand::AddressMode(addr_mode))))
-                },
-                _ => {}
+        // A lone A is the accumulator rather than an identifier
+        if let Some(Token::AddressMode(addr_mode)) = self.attempt_parser(Self::accumulator_mode)? {
+            return Ok(Some(Token::Operand(Operand::AddressMode(addr_mode))));
+        }
+
+        // The indirect modes look like a parenthesized expression so they need to be tried first, as do
+        // the 65816 long addresses which are too large to be parsed as a number
+        let indirect_parsers: [fn(&mut Self) -> TokenResult; 8] = [
+            Self::stack_relative_indirect_y_mode,
+            Self::indirect_index_y_mode,
+            Self::indexed_indirect_x_mode,
+            Self::absolute_indexed_indirect_mode,
+            Self::indirect_mode,
+            Self::indirect_long_mode,
+            Self::absolute_long_x_mode,
+            Self::absolute_long_mode,
+        ];
+        for parser in indirect_parsers {
+            if let Ok(Some(Token::AddressMode(addr_mode))) = parser(self) {
+                return Ok(Some(Token::Operand(Operand::AddressMode(addr_mode))));
             }
-            _ => {}
+            self.cursor = start_pos;
         }
 
         // Try the parser for an expression
         match self.expression() {
             Ok(result) => match result {
-                Some(ExpressionNode::Identifier(_)) => {},
-                Some(ExpressionNode::Number(_)) => {},
-                Some(ExpressionNode::ScopedReference(_)) => {},
+                Some(ExpressionNode::Identifier(..)) => {},
+                Some(ExpressionNode::Number(..)) => {},
+                Some(ExpressionNode::ScopedReference(..)) => {},
                 Some(expression) => {
                     return Ok(Some(Token::Operand(Operand::Expression(expression))))
                 }
@@ -88,7 +158,7 @@ impl Scanner {
 
         // Reset the cursor
         self.cursor = start_pos;
-    
+
         // Try the parser on an address mode first
         if let Some(token) = self.attempt_parser(Self::address_modes)? {
             match token {
@@ -97,8 +167,8 @@ impl Scanner {
                 }
                 _ => {
                     return Err(ParseError::UnexpectedToken {
-                        expected: Token::AddressMode(AddressMode::Accumulator),
-                        received: token,
+                        expected: Box::new(Token::AddressMode(AddressMode::Accumulator)),
+                        received: Box::new(token),
                         position: self.cursor,
                     })
                 }
@@ -108,7 +178,7 @@ impl Scanner {
         // Reset the cursor
         self.cursor = start_pos;
 
-        if self.consume_char('@') {
+        if self.consume(TokenKind::At) && self.peek().is_some_and(|lexeme| !lexeme.spaced) {
             if let Some(token) = self.attempt_parser(Self::identifier)? {
                 match token {
                     Token::Identifier(ident) => {
@@ -116,8 +186,8 @@ impl Scanner {
                     }
                     _ => {
                         return Err(ParseError::UnexpectedToken {
-                            expected: Token::AddressMode(AddressMode::Accumulator),
-                            received: token,
+                            expected: Box::new(Token::AddressMode(AddressMode::Accumulator)),
+                            received: Box::new(token),
                             position: self.cursor,
                         })                        
                     }
@@ -127,23 +197,94 @@ impl Scanner {
 
         self.cursor = start_pos;
 
+        if let Some(offset) = self.anonymous_reference() {
+            return Ok(Some(Token::Operand(Operand::AnonymousLabel(offset))));
+        }
+
         // If we encountered an error earlier and we are done attempting all parsers,
         // then return the error
-        if error.is_some() {
-            return Err(error.unwrap())
+        if let Some(error) = error {
+            return Err(error)
         }
 
         Ok(None)
     }
+
+    /// Parses an address size suffix straight after a mnemonic, such as the `.a` in `LDA.a`
+    fn address_size_suffix(&mut self) -> Option<AddressSize> {
+        let start_pos = self.cursor;
+
+        if self.consume_adjacent(TokenKind::Dot) && self.peek().is_some_and(|lexeme| !lexeme.spaced) {
+            if let Some(size) = AddressSize::from_letter(self.peek_text()) {
+                self.next();
+                return Some(size);
+            }
+        }
+
+        self.cursor = start_pos;
+        None
+    }
+
+    /// Parses an address size prefix at the start of an operand, such as the `z:` in `LDA z:label`.
+    /// There can be no spaces around the colon, otherwise it separates statements (`ASL a : CLC`)
+    fn address_size_prefix(&mut self) -> Option<AddressSize> {
+        let start_pos = self.cursor;
+
+        if self.peek_kind() == Some(TokenKind::Identifier) {
+            if let Some(size) = AddressSize::from_letter(self.peek_text()) {
+                self.next();
+                if self.consume_adjacent(TokenKind::Colon) && self.peek().is_some_and(|lexeme| !lexeme.spaced) {
+                    return Some(size);
+                }
+            }
+        }
+
+        self.cursor = start_pos;
+        None
+    }
+
+    /// Parses a reference to an anonymous label, which is a colon followed straight away by pluses
+    /// to look forward or minuses to look back. Returns how many labels away it is
+    fn anonymous_reference(&mut self) -> Option<i8> {
+        let start_pos = self.cursor;
+
+        if !self.consume(TokenKind::Colon) {
+            return None;
+        }
+
+        let direction = match self.peek_kind() {
+            Some(TokenKind::Plus) => TokenKind::Plus,
+            Some(TokenKind::Minus) => TokenKind::Minus,
+            _ => {
+                self.cursor = start_pos;
+                return None;
+            }
+        };
+
+        let mut count: i8 = 0;
+        while count < i8::MAX && self.consume_adjacent(direction) {
+            count += 1;
+        }
+
+        if count == 0 {
+            self.cursor = start_pos;
+            return None;
+        }
+
+        match direction {
+            TokenKind::Plus => Some(count),
+            _ => Some(-count),
+        }
+    }
 }
 
 #[cfg(test)]
 mod instruction_tests {
-    use forge_lib::{address::AddressMode, mnemonic::Mnemonic, expression::{ExpressionNode, BinaryOp}};
+    use forge_lib::{address::{AddressMode, AddressSize}, mnemonic::Mnemonic, expression::{ExpressionNode, BinaryOp}, cpu::Cpu, span::Span};
 
-    use crate::scanner::{
-        instruction::{Instruction, Operand},
-        Scanner, Token,
+    use crate::{
+        error::ParseError,
+        scanner::{instruction::{Instruction, Operand}, Scanner, Token},
     };
 
     #[test]
@@ -172,6 +313,20 @@ mod instruction_tests {
         )
     }
 
+    #[test]
+    fn test_parse_operand_anonymous_label() {
+        let operand = |source| Scanner::new(source).operand().unwrap();
+
+        assert_eq!(operand(":-"), Some(Token::Operand(Operand::AnonymousLabel(-1))));
+        assert_eq!(operand(":--"), Some(Token::Operand(Operand::AnonymousLabel(-2))));
+        assert_eq!(operand(":+"), Some(Token::Operand(Operand::AnonymousLabel(1))));
+        assert_eq!(operand(":++ ; forward"), Some(Token::Operand(Operand::AnonymousLabel(2))));
+
+        // The signs have to follow the colon straight away
+        assert_eq!(operand(": +"), None);
+        assert_eq!(operand(":"), None);
+    }
+
     #[test]
     fn test_parse_instruction_operand_address_mode() {
         let mut scanner = Scanner::new("sta ($44),y");
@@ -182,7 +337,9 @@ mod instruction_tests {
             result.unwrap(),
             Some(Token::Instruction(Instruction {
                 mnemonic: Mnemonic::STA,
-                operand: Some(Operand::AddressMode(AddressMode::IndirectIndexY(0x44)))
+                operand: Some(Operand::AddressMode(AddressMode::IndirectIndexY(0x44))),
+                address_size: None,
+                span: Span::default(),
             }))
         )
     }
@@ -197,7 +354,9 @@ mod instruction_tests {
             result.unwrap(),
             Some(Token::Instruction(Instruction {
                 mnemonic: Mnemonic::LDA,
-                operand: Some(Operand::AddressMode(AddressMode::ZeroPageOrAbsoluteIdent(String::from("PPUCONSTANT"))))
+                operand: Some(Operand::AddressMode(AddressMode::ZeroPageOrAbsoluteIdent(String::from("PPUCONSTANT")))),
+                address_size: None,
+                span: Span::default(),
             }))
         );
     }
@@ -214,13 +373,145 @@ mod instruction_tests {
                 mnemonic: Mnemonic::LDA,
                 operand: Some(Operand::Expression(ExpressionNode::BinOp(
                     BinaryOp::Add,
-                    Box::new(ExpressionNode::Identifier(String::from("PPUCONSTANT"))),
-                    Box::new(ExpressionNode::Number(1))
-                )))
+                    Box::new(ExpressionNode::Identifier(String::from("PPUCONSTANT"), Span::default())),
+                    Box::new(ExpressionNode::Number(1, Span::default())),
+                    Span::default(),
+                ))),
+                address_size: None,
+                span: Span::default(),
             }))
         )
     }
 
+    #[test]
+    fn test_parse_instruction_65c02_operands() {
+        let mut scanner = Scanner::new("INC A");
+        scanner.cpu = Cpu::Wdc65C02;
+        let result = scanner.instruction();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::Instruction(Instruction {
+                mnemonic: Mnemonic::INC,
+                operand: Some(Operand::AddressMode(AddressMode::Accumulator)),
+                address_size: None,
+                span: Span::default(),
+            }))
+        );
+
+        let mut scanner = Scanner::new("LDA ($44)");
+        scanner.cpu = Cpu::Wdc65C02;
+        let result = scanner.instruction();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::Instruction(Instruction {
+                mnemonic: Mnemonic::LDA,
+                operand: Some(Operand::AddressMode(AddressMode::ZeroPageIndirect(0x44))),
+                address_size: None,
+                span: Span::default(),
+            }))
+        );
+
+        let mut scanner = Scanner::new("JMP ($8000,X)");
+        scanner.cpu = Cpu::Wdc65C02;
+        let result = scanner.instruction();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::Instruction(Instruction {
+                mnemonic: Mnemonic::JMP,
+                operand: Some(Operand::AddressMode(AddressMode::AbsoluteIndexedIndirect(0x8000))),
+                address_size: None,
+                span: Span::default(),
+            }))
+        );
+
+        let mut scanner = Scanner::new("BBS7 $12, done");
+        scanner.cpu = Cpu::Wdc65C02;
+        let result = scanner.instruction();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::Instruction(Instruction {
+                mnemonic: Mnemonic::BBS7,
+                operand: Some(Operand::AddressMode(AddressMode::ZeroPageRelative(
+                    ExpressionNode::Number(0x12, Span::default()),
+                    ExpressionNode::Identifier(String::from("done"), Span::default())
+                ))),
+                address_size: None,
+                span: Span::default(),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_instruction_65816_operands() {
+        let mut scanner = Scanner::new("LDA $7E0000,X");
+        scanner.cpu = Cpu::Wdc65816;
+        let result = scanner.instruction();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::Instruction(Instruction {
+                mnemonic: Mnemonic::LDA,
+                operand: Some(Operand::AddressMode(AddressMode::AbsoluteLongX(0x7E0000))),
+                address_size: None,
+                span: Span::default(),
+            }))
+        );
+
+        let mut scanner = Scanner::new("STA ($01,S),Y");
+        scanner.cpu = Cpu::Wdc65816;
+        let result = scanner.instruction();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::Instruction(Instruction {
+                mnemonic: Mnemonic::STA,
+                operand: Some(Operand::AddressMode(AddressMode::StackRelativeIndirectY(0x01))),
+                address_size: None,
+                span: Span::default(),
+            }))
+        );
+
+        let mut scanner = Scanner::new("LDA $01,S");
+        scanner.cpu = Cpu::Wdc65816;
+        let result = scanner.instruction();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::Instruction(Instruction {
+                mnemonic: Mnemonic::LDA,
+                operand: Some(Operand::AddressMode(AddressMode::StackRelative(0x01))),
+                address_size: None,
+                span: Span::default(),
+            }))
+        );
+
+        let mut scanner = Scanner::new("MVN $7E,$7F");
+        scanner.cpu = Cpu::Wdc65816;
+        let result = scanner.instruction();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Token::Instruction(Instruction {
+                mnemonic: Mnemonic::MVN,
+                operand: Some(Operand::AddressMode(AddressMode::BlockMove(0x7E, 0x7F))),
+                address_size: None,
+                span: Span::default(),
+            }))
+        );
+    }
+
     #[test]
     fn test_parse_instruction_no_operand() {
         let mut scanner = Scanner::new("TAX");
@@ -231,8 +522,43 @@ mod instruction_tests {
             result.unwrap(),
             Some(Token::Instruction(Instruction {
                 mnemonic: Mnemonic::TAX,
-                operand: None
+                operand: None,
+                address_size: None,
+                span: Span::default(),
             }))
         );
     }
+
+    #[test]
+    fn test_parse_instruction_address_size() {
+        let instruction = |source| match Scanner::new(source).instruction().unwrap() {
+            Some(Token::Instruction(instruction)) => (instruction.operand.unwrap(), instruction.address_size),
+            token => panic!("expected an instruction but found {:?}", token),
+        };
+
+        assert_eq!(
+            instruction("LDA a:$10"),
+            (Operand::AddressMode(AddressMode::Absolute(0x10)), Some(AddressSize::Absolute))
+        );
+        assert_eq!(
+            instruction("lda.z $0010,x"),
+            (Operand::AddressMode(AddressMode::ZeroPageX(0x10)), Some(AddressSize::ZeroPage))
+        );
+        assert_eq!(
+            instruction("LDA f:label"),
+            (Operand::AddressMode(AddressMode::ZeroPageOrAbsoluteIdent(String::from("label"))), Some(AddressSize::Far))
+        );
+
+        // A spaced colon is the accumulator followed by another statement
+        assert_eq!(instruction("ASL a : CLC"), (Operand::AddressMode(AddressMode::Accumulator), None));
+    }
+
+    #[test]
+    fn test_parse_instruction_zero_page_out_of_range() {
+        let mut scanner = Scanner::new("LDA z:$1234");
+        assert_eq!(
+            scanner.instruction(),
+            Err(ParseError::ZeroPageOutOfRange { value: 0x1234 })
+        );
+    }
 }
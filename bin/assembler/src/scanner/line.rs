@@ -1,17 +1,14 @@
-use forge_lib::line::{Line, MainComponent, Labels};
+use forge_lib::{line::{Line, MainComponent, Labels}, mnemonic::Mnemonic};
 
-use crate::error::ParseError;
+use crate::{error::ParseError, lexer::TokenKind};
 
 use super::{
     Scanner, Token, TokenResult,
 };
 
-impl Scanner {
+impl Scanner<'_> {
     pub fn line(&mut self) -> Result<Line, ParseError> {
-        let _start_pos = self.cursor;
-
-        // Consume any whitespace
-        self.consume_all_whitespace();
+        let start_pos = self.cursor;
 
         // Try to parse a comment first. Anything after a comment is not needed
         let comment = match self.attempt_parser(Self::comment)? {
@@ -20,69 +17,41 @@ impl Scanner {
         };
 
         if comment.is_some() {
-            // Consume any whitespace
-            self.consume_all_whitespace();
-
-            // Try to consume 1 or more newlines if possible
-            let mut newline_count: u32 = 0;
-            while self.consume_newline() {
-                newline_count += 1;
-            }
-
-            // If there wasn't at least 1 newline and this is not the end of the file this is an error
-            // If there wasn't a newline but we are at the end of input then we are all set! (This means end of file)
-            if newline_count == 0 && !self.is_done() {
-                return Err(ParseError::ExpectedNewline);
-            }
-
-            // Add the number of newlines to the line tracker
-            self.lines += newline_count;
+            let span = self.span_from(start_pos);
+            let newline_count = self.end_line()?;
             return Ok(Line {
                 comment,
                 constant: None,
                 label: None,
-                main_component: None,
+                main_components: Vec::new(),
                 newlines: newline_count,
+                span,
             });
         }
 
         let constant = match self.attempt_parser(Self::constant)? {
             Some(Token::Constant(ident, value)) => Some((ident, value)),
-            Some(token) => return Err(ParseError::UnexpectedToken { expected: Token::Constant("".to_string(), 0), received: token, position: self.cursor }),
+            Some(token) => return Err(ParseError::UnexpectedToken { expected: Box::new(Token::Constant("".to_string(), 0)), received: Box::new(token), position: self.cursor }),
             None => None
         };
 
         if constant.is_some() {
-            // Consume any whitespace
-            self.consume_all_whitespace();
-
             let comment = match self.attempt_parser(Self::comment)? {
                 Some(Token::Comment(comment)) => Some(comment),
                 _ => None,
             };
 
-            // Try to consume 1 or more newlines if possible
-            let mut newline_count: u32 = 0;
-            while self.consume_newline() {
-                newline_count += 1;
-            }
-
-            // If there wasn't at least 1 newline and this is not the end of the file this is an error
-            // If there wasn't a newline but we are at the end of input then we are all set! (This means end of file)
-            if newline_count == 0 && !self.is_done() {
-                return Err(ParseError::ExpectedNewline);
-            }
-
-            // Add the number of newlines to the line tracker
-            self.lines += newline_count;
+            let span = self.span_from(start_pos);
+            let newline_count = self.end_line()?;
 
             return Ok(
                 Line {
                     comment,
                     constant,
                     label: None,
-                    main_component: None,
-                    newlines: newline_count
+                    main_components: Vec::new(),
+                    newlines: newline_count,
+                    span,
                 }
             )
         }
@@ -91,30 +60,72 @@ impl Scanner {
         let label = match self.attempt_parser(Self::label)? {
             Some(Token::Label(label)) => Some(Labels::Label(label)),
             Some(Token::LocalLabel(label)) => Some(Labels::LocalLabel(label)),
+            Some(Token::AnonymousLabel) => Some(Labels::Anonymous),
+            _ => None,
+        };
+
+        // Statements can follow each other on the same line with a colon between them
+        let mut main_components = Vec::new();
+        if let Some(main_component) = self.statement()? {
+            main_components.push(main_component);
+
+            while self.consume(TokenKind::Colon) {
+                match self.statement()? {
+                    Some(main_component) => main_components.push(main_component),
+                    None => return Err(ParseError::ExpectedStatement),
+                }
+            }
+        }
+
+        // Now check for a comment
+        let comment = match self.attempt_parser(Self::comment)? {
+            Some(Token::Comment(comment)) => Some(comment),
             _ => None,
         };
 
-        // Consume any whitespace
-        self.consume_all_whitespace();
+        let span = self.span_from(start_pos);
+        let newline_count = self.end_line()?;
 
-        // Check for either a directive or insturction
+        Ok(Line {
+            comment,
+            constant: None,
+            label,
+            main_components,
+            newlines: newline_count,
+            span,
+        })
+    }
+
+    /// Parses a single instruction or directive. The grammar is defined as
+    ///
+    /// statement = instruction | directive
+    fn statement(&mut self) -> Result<Option<MainComponent>, ParseError> {
+        let start_pos = self.cursor;
         let main_component = match self.attempt_parser(Self::instruction)? {
             Some(Token::Instruction(instruction)) => Some(MainComponent::Instruction(instruction)),
             _ => match self.attempt_parser(Self::directive)? {
-                Some(Token::Directive(directive)) => Some(MainComponent::Directive(directive)),
+                Some(Token::Directive(directive)) => Some(MainComponent::Directive(directive, self.span_from(start_pos))),
                 _ => None
             }
         };
 
-        // Consume any whitespace
-        self.consume_all_whitespace();
+        if let Some(MainComponent::Instruction(instruction)) = &main_component {
+            self.check_decimal_mode(instruction.mnemonic, instruction.span);
+        }
 
-        // Now check for a comment
-        let comment = match self.attempt_parser(Self::comment)? {
-            Some(Token::Comment(comment)) => Some(comment),
-            _ => None,
-        };
+        // A word where the instruction should be is most likely a misspelled mnemonic
+        if main_component.is_none() && self.peek_kind() == Some(TokenKind::Identifier) {
+            let name = self.peek_text().to_string();
+            let suggestion = Mnemonic::suggest(&name, self.cpu);
+            return Err(ParseError::UnknownMnemonic { name, suggestion, position: start_pos });
+        }
+
+        Ok(main_component)
+    }
 
+    /// Consumes the newlines ending a line and moves on to the tokens of the next one. Returns the
+    /// number of newlines consumed
+    fn end_line(&mut self) -> Result<u32, ParseError> {
         // Try to consume 1 or more newlines if possible
         let mut newline_count: u32 = 0;
         while self.consume_newline() {
@@ -123,20 +134,15 @@ impl Scanner {
 
         // If there wasn't at least 1 newline and this is not the end of the file this is an error
         // If there wasn't a newline but we are at the end of input then we are all set! (This means end of file)
-        if newline_count == 0 && !self.is_done() {
+        if newline_count == 0 && self.peek().is_some() {
             return Err(ParseError::ExpectedNewline);
         }
 
         // Add the number of newlines to the line tracker
         self.lines += newline_count;
+        self.load_line();
 
-        return Ok(Line {
-            comment,
-            constant: None,
-            label,
-            main_component,
-            newlines: newline_count,
-        });
+        Ok(newline_count)
     }
 
     pub fn constant(&mut self) -> TokenResult {
@@ -151,18 +157,12 @@ impl Scanner {
             }
         };
 
-        // Next consume any whitespace
-        self.consume_all_whitespace();
-
         // Now get an = sign
-        if !self.consume_char('=') {
+        if !self.consume(TokenKind::Equals) {
             self.cursor = start_pos;
             return Ok(None)
         }
 
-        // Next consume any whitespace
-        self.consume_all_whitespace();
-
         // Now get the number
         let number = match self.number()? {
             Some(number) => number,
@@ -178,12 +178,12 @@ impl Scanner {
 
 #[cfg(test)]
 mod line_tests {
-    use forge_lib::{address::AddressMode, mnemonic::Mnemonic, instruction::Instruction, operand::Operand, line::Labels};
+    use forge_lib::{address::AddressMode, mnemonic::Mnemonic, instruction::Instruction, operand::Operand, line::Labels, cpu::Cpu, span::Span};
 
-    use crate::scanner::{
+    use crate::{error::ParseError, warning::Warning, scanner::{
         line::{Line, MainComponent},
         Scanner, Token,
-    };
+    }};
 
     #[test]
     fn test_parse_line_comment_only() {
@@ -197,8 +197,9 @@ mod line_tests {
                 comment: Some(String::from("; This is a comment line with no newline!")),
                 constant: None,
                 label: None,
-                main_component: None,
-                newlines: 0
+                main_components: vec![],
+                newlines: 0,
+                span: Span::default(),
             }
         )
     }
@@ -215,11 +216,14 @@ mod line_tests {
                 comment: None,
                 constant: None,
                 label: None,
-                main_component: Some(MainComponent::Instruction(Instruction {
+                main_components: vec![MainComponent::Instruction(Instruction {
                     mnemonic: Mnemonic::STA,
-                    operand: Some(Operand::AddressMode(AddressMode::IndirectIndexY(0x00)))
-                })),
-                newlines: 0
+                    operand: Some(Operand::AddressMode(AddressMode::IndirectIndexY(0x00))),
+                    address_size: None,
+                    span: Span::default(),
+                })],
+                newlines: 0,
+                span: Span::default(),
             }
         )
     }
@@ -236,11 +240,14 @@ mod line_tests {
                 comment: Some(String::from("; Hey look! This is a comment")),
                 constant: None,
                 label: None,
-                main_component: Some(MainComponent::Instruction(Instruction {
+                main_components: vec![MainComponent::Instruction(Instruction {
                     mnemonic: Mnemonic::LDA,
-                    operand: Some(Operand::AddressMode(AddressMode::AbsoluteX(0x4400)))
-                })),
-                newlines: 0
+                    operand: Some(Operand::AddressMode(AddressMode::AbsoluteX(0x4400))),
+                    address_size: None,
+                    span: Span::default(),
+                })],
+                newlines: 0,
+                span: Span::default(),
             }
         )
     }
@@ -257,11 +264,14 @@ mod line_tests {
                 comment: Some(String::from("; Hey look! This is a comment")),
                 constant: None,
                 label: None,
-                main_component: Some(MainComponent::Instruction(Instruction {
+                main_components: vec![MainComponent::Instruction(Instruction {
                     mnemonic: Mnemonic::LDA,
-                    operand: Some(Operand::AddressMode(AddressMode::AbsoluteX(0x4400)))
-                })),
-                newlines: 2
+                    operand: Some(Operand::AddressMode(AddressMode::AbsoluteX(0x4400))),
+                    address_size: None,
+                    span: Span::default(),
+                })],
+                newlines: 2,
+                span: Span::default(),
             }
         );
         assert_eq!(scanner.lines, 2);
@@ -279,8 +289,9 @@ mod line_tests {
                 comment: None,
                 constant: None,
                 label: Some(Labels::Label(String::from("START"))),
-                main_component: None,
-                newlines: 0
+                main_components: vec![],
+                newlines: 0,
+                span: Span::default(),
             }
         )
     }
@@ -298,11 +309,14 @@ mod line_tests {
                 comment: Some(String::from("; Load the PPU into the accumulator")),
                 constant: None,
                 label: Some(Labels::Label(String::from("START"))),
-                main_component: Some(MainComponent::Instruction(Instruction {
+                main_components: vec![MainComponent::Instruction(Instruction {
                     mnemonic: Mnemonic::LDA,
-                    operand: Some(Operand::AddressMode(AddressMode::ZeroPageOrAbsoluteIdent(String::from("PPUCONSTANT"))))
-                })),
-                newlines: 0
+                    operand: Some(Operand::AddressMode(AddressMode::ZeroPageOrAbsoluteIdent(String::from("PPUCONSTANT")))),
+                    address_size: None,
+                    span: Span::default(),
+                })],
+                newlines: 0,
+                span: Span::default(),
             }
         );
     }
@@ -319,8 +333,9 @@ mod line_tests {
                 comment: None,
                 constant: Some((String::from("PPUCONSTANT"), 0x2000)),
                 label: None,
-                main_component: None,
-                newlines: 0
+                main_components: vec![],
+                newlines: 0,
+                span: Span::default(),
             }
         )
     }
@@ -345,4 +360,188 @@ mod line_tests {
             Some(Token::Constant(String::from("PPUCONSTANT"), 0b1000))
         )
     }
+
+    #[test]
+    fn test_parse_line_decimal_mode_warnings() {
+        let mut scanner = Scanner::new("ADC #$01\nSED\nADC #$01\nCLD\nSBC #$01\n");
+        scanner.cpu = Cpu::Ricoh2A03;
+        while !scanner.is_done() {
+            assert!(scanner.line().is_ok());
+        }
+
+        assert_eq!(
+            scanner.warnings,
+            vec![
+                (Warning::DecimalModeUnavailable { mnemonic: Mnemonic::SED, cpu: Cpu::Ricoh2A03 }, Span::new(0, 2, 1, 3)),
+                (Warning::DecimalArithmetic { mnemonic: Mnemonic::ADC, cpu: Cpu::Ricoh2A03 }, Span::new(0, 3, 1, 8)),
+                (Warning::DecimalModeUnavailable { mnemonic: Mnemonic::CLD, cpu: Cpu::Ricoh2A03 }, Span::new(0, 4, 1, 3)),
+            ]
+        );
+
+        // Decimal mode is fine on a regular 6502
+        let mut scanner = Scanner::new("SED\nADC #$01\nCLD\n");
+        while !scanner.is_done() {
+            assert!(scanner.line().is_ok());
+        }
+
+        assert!(scanner.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_parse_line_spans() {
+        let mut scanner = Scanner::new("; Header\n\nloop:  LDA (ptr), Y ; Load\n  .byte $01, $02\n");
+
+        let line = scanner.line().unwrap();
+        assert_eq!(line.span, Span::new(0, 1, 1, 8));
+
+        let line = scanner.line().unwrap();
+        assert_eq!(line.span, Span::new(0, 3, 1, 26));
+        assert_eq!(line.main_components[0].span(), Span::new(0, 3, 8, 12));
+
+        let line = scanner.line().unwrap();
+        assert_eq!(line.span, Span::new(0, 4, 3, 14));
+        assert_eq!(line.main_components[0].span(), Span::new(0, 4, 3, 14));
+    }
+
+    #[test]
+    fn test_parse_line_error_spans() {
+        let mut scanner = Scanner::new("NOP\n  LDA #$01 junk\n");
+        assert!(scanner.next_line().is_ok());
+
+        let error = scanner.next_line().unwrap_err();
+        assert_eq!(error.error, ParseError::ExpectedNewline);
+        assert_eq!(error.span, Span::new(0, 2, 12, 4));
+    }
+
+    #[test]
+    fn test_parse_line_unknown_mnemonic() {
+        let mut scanner = Scanner::new("  eqr #$01\n");
+        let error = scanner.next_line().unwrap_err();
+
+        assert_eq!(
+            error.error,
+            ParseError::UnknownMnemonic { name: String::from("eqr"), suggestion: Some(Mnemonic::EOR), position: 0 }
+        );
+        assert_eq!(error.span, Span::new(0, 1, 3, 3));
+
+        // No suggestion is made for mnemonics the CPU does not have
+        let mut scanner = Scanner::new("BBR #$01\n");
+        let result = scanner.line();
+
+        assert!(matches!(result, Err(ParseError::UnknownMnemonic { suggestion: None, .. })));
+    }
+
+    #[test]
+    fn test_parse_line_recover() {
+        let mut scanner = Scanner::new("LDA #$01 junk\nNOP\n  eqr\nBRK\n");
+
+        let mut lines = Vec::new();
+        let mut errors = Vec::new();
+        while !scanner.is_done() {
+            match scanner.next_line() {
+                Ok(line) => lines.push(line),
+                Err(error) => {
+                    errors.push(error.span);
+                    scanner.recover();
+                }
+            }
+        }
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].span, Span::new(0, 2, 1, 3));
+        assert_eq!(lines[1].span, Span::new(0, 4, 1, 3));
+        assert_eq!(errors, vec![Span::new(0, 1, 10, 4), Span::new(0, 3, 3, 3)]);
+    }
+
+    #[test]
+    fn test_parse_line_line_endings() {
+        let mut scanner = Scanner::new("NOP\r\nstart:\tlda $10,x\rBRK");
+
+        let first = scanner.next_line().unwrap();
+        assert_eq!(first.span, Span::new(0, 1, 1, 3));
+
+        let second = scanner.next_line().unwrap();
+        assert_eq!(second.label, Some(Labels::Label(String::from("start"))));
+        assert_eq!(
+            second.main_components,
+            vec![MainComponent::Instruction(Instruction {
+                mnemonic: Mnemonic::LDA,
+                operand: Some(Operand::AddressMode(AddressMode::ZeroPageX(0x10))),
+                address_size: None,
+                span: Span::default(),
+            })]
+        );
+        assert_eq!(second.span, Span::new(0, 2, 1, 16));
+
+        let third = scanner.next_line().unwrap();
+        assert_eq!(third.span, Span::new(0, 3, 1, 3));
+        assert!(scanner.is_done());
+    }
+
+    #[test]
+    fn test_parse_line_symbol_case() {
+        let reference = |line: Line| match line.main_components.into_iter().next() {
+            Some(MainComponent::Instruction(Instruction { operand: Some(Operand::AddressMode(mode)), .. })) => mode,
+            _ => panic!("expected an instruction with an operand"),
+        };
+
+        // Symbols take the spelling they were first written with
+        let mut scanner = Scanner::new("Loop: NOP\nLDA LOOP\n");
+        assert_eq!(scanner.next_line().unwrap().label, Some(Labels::Label(String::from("Loop"))));
+        assert_eq!(
+            reference(scanner.next_line().unwrap()),
+            AddressMode::ZeroPageOrAbsoluteIdent(String::from("Loop"))
+        );
+
+        let mut scanner = Scanner::new("Loop: NOP\nLDA LOOP\n");
+        scanner.case_sensitive_symbols = true;
+        assert!(scanner.next_line().is_ok());
+        assert_eq!(
+            reference(scanner.next_line().unwrap()),
+            AddressMode::ZeroPageOrAbsoluteIdent(String::from("LOOP"))
+        );
+    }
+
+    #[test]
+    fn test_parse_line_anonymous_label() {
+        let mut scanner = Scanner::new(":\tDEX\n:   BNE :--\n");
+
+        let first = scanner.next_line().unwrap();
+        assert_eq!(first.label, Some(Labels::Anonymous));
+        assert_eq!(first.label_span(), Some(Span::new(0, 1, 1, 1)));
+
+        let second = scanner.next_line().unwrap();
+        assert_eq!(second.label, Some(Labels::Anonymous));
+        assert_eq!(
+            second.main_components,
+            vec![MainComponent::Instruction(Instruction {
+                mnemonic: Mnemonic::BNE,
+                operand: Some(Operand::AnonymousLabel(-2)),
+                address_size: None,
+                span: Span::default(),
+            })]
+        );
+    }
+
+    #[test]
+    fn test_parse_line_multiple_statements() {
+        let mut scanner = Scanner::new("add: CLC : ADC #$01:STA $10 ; add one\n");
+        let line = scanner.next_line().unwrap();
+
+        let mnemonics: Vec<_> = line.main_components.iter().map(|main_component| match main_component {
+            MainComponent::Instruction(instruction) => instruction.mnemonic,
+            MainComponent::Directive(..) => panic!("expected an instruction"),
+        }).collect();
+        assert_eq!(mnemonics, vec![Mnemonic::CLC, Mnemonic::ADC, Mnemonic::STA]);
+        assert_eq!(line.label, Some(Labels::Label(String::from("add"))));
+        assert_eq!(line.comment, Some(String::from("; add one")));
+        assert_eq!(line.main_components[2].span(), Span::new(0, 1, 21, 7));
+
+        // Something has to follow the separator
+        let error = Scanner::new("NOP :\n").next_line().unwrap_err();
+        assert_eq!(error.error, ParseError::ExpectedStatement);
+
+        let error = Scanner::new("NOP : eqr\n").next_line().unwrap_err();
+        assert!(matches!(error.error, ParseError::UnknownMnemonic { suggestion: Some(Mnemonic::EOR), .. }));
+    }
 }
@@ -1,43 +1,48 @@
-use std::collections::HashSet;
+use forge_lib::mnemonic::Mnemonic;
 
-use crate::error::ParseError;
+use crate::{error::ParseError, lexer::TokenKind};
 
 use super::{Scanner, Token, TokenResult};
 
-impl Scanner {
+impl Scanner<'_> {
     pub fn mnemonic(&mut self) -> TokenResult {
-        let mnemonics: HashSet<&str> = [
-            "ADC", "AND", "ASL", "BCC", "BCS", "BEQ", "BIT", "BMI", "BNE", "BPL", "BRK", "BVC",
-            "BVS", "CLC", "CLD", "CLI", "CLV", "CMP", "CPX", "CPY", "DEC", "DEX", "DEY", "EQR",
-            "INC", "INX", "INY", "JMP", "JSR", "LDA", "LDX", "LDY", "LSR", "NOP", "ORA", "PHA",
-            "PHP", "PLA", "PLP", "ROL", "ROR", "RTI", "RTS", "SBC", "SEC", "SED", "SEI", "STA",
-            "STX", "STY", "TAX", "TAY", "TSX", "TXA", "TXS", "TYA"
-        ].iter().cloned().collect();
-
-        let start_pos = self.cursor;
-
-        while let Some(c) = self.peek() {
-            if !(c.is_ascii_uppercase() || c.is_ascii_lowercase()) {
-                break;
-            }
-            self.next();
+        // Mnemonics are letters, except the Rockwell bit instructions which end in the bit number
+        if self.peek_kind() != Some(TokenKind::Identifier) {
+            return Err(ParseError::ExpectedValidMnemonic);
         }
 
-        let mnemonic: String = self.input[start_pos..self.cursor].iter().collect();
-        let mnemonic = mnemonic.to_ascii_uppercase();
+        match with_uppercase(self.peek_text(), Mnemonic::from_name) {
+            Some(mnemonic) if mnemonic.is_supported_by(self.cpu) => {
+                self.next();
+                Ok(Some(Token::Mnemonic(mnemonic)))
+            }
+            Some(mnemonic) if mnemonic.is_undocumented() && self.cpu.with_illegal_opcodes().is_some() => {
+                let cpu = self.cpu.with_illegal_opcodes().unwrap_or(self.cpu);
+                Err(ParseError::UndocumentedMnemonic { mnemonic, cpu })
+            }
+            Some(mnemonic) => Err(ParseError::UnsupportedMnemonic { mnemonic, cpu: self.cpu }),
+            None => Err(ParseError::ExpectedValidMnemonic),
+        }
+    }
+}
 
-        if mnemonics.contains(mnemonic.as_str()) {
-            Ok(Some(Token::Mnemonic(mnemonic.into())))
-        } else {
-            self.cursor = start_pos;
-            Err(ParseError::ExpectedValidMnemonic)
+/// Calls the function with the name in upper case. Short names, which is every mnemonic and
+/// directive, are converted on the stack rather than allocating
+pub fn with_uppercase<R>(name: &str, f: impl FnOnce(&str) -> R) -> R {
+    let mut buffer = [0u8; 16];
+    match buffer.get_mut(..name.len()) {
+        Some(upper) => {
+            upper.copy_from_slice(name.as_bytes());
+            upper.make_ascii_uppercase();
+            f(std::str::from_utf8(upper).unwrap_or(name))
         }
+        None => f(&name.to_ascii_uppercase()),
     }
 }
 
 #[cfg(test)]
 mod mnemonic_tests {
-    use forge_lib::mnemonic::Mnemonic;
+    use forge_lib::{cpu::Cpu, mnemonic::Mnemonic};
 
     use super::*;
 
@@ -112,8 +117,8 @@ mod mnemonic_tests {
         let mut scanner = Scanner::new("DEY");
         assert_eq!(scanner.mnemonic().unwrap(), Some(Token::Mnemonic(Mnemonic::DEY)));
 
-        let mut scanner = Scanner::new("EQR");
-        assert_eq!(scanner.mnemonic().unwrap(), Some(Token::Mnemonic(Mnemonic::EQR)));
+        let mut scanner = Scanner::new("EOR");
+        assert_eq!(scanner.mnemonic().unwrap(), Some(Token::Mnemonic(Mnemonic::EOR)));
 
         let mut scanner = Scanner::new("INC");
         assert_eq!(scanner.mnemonic().unwrap(), Some(Token::Mnemonic(Mnemonic::INC)));
@@ -227,4 +232,51 @@ mod mnemonic_tests {
         let mut scanner = Scanner::new("noo");
         assert!(scanner.mnemonic().is_err());
     }
+
+    #[test]
+    fn test_parse_mnemonic_65c02() {
+        let mut scanner = Scanner::new("STZ");
+        scanner.cpu = Cpu::Wdc65C02;
+        assert_eq!(scanner.mnemonic().unwrap(), Some(Token::Mnemonic(Mnemonic::STZ)));
+
+        let mut scanner = Scanner::new("bbr3");
+        scanner.cpu = Cpu::Wdc65C02;
+        assert_eq!(scanner.mnemonic().unwrap(), Some(Token::Mnemonic(Mnemonic::BBR3)));
+    }
+
+    #[test]
+    fn test_parse_mnemonic_fail_65c02_on_nmos() {
+        let mut scanner = Scanner::new("PHX");
+        assert_eq!(
+            scanner.mnemonic(),
+            Err(ParseError::UnsupportedMnemonic { mnemonic: Mnemonic::PHX, cpu: Cpu::Mos6502 })
+        );
+        assert!(scanner.mnemonic().unwrap_err().is_fatal());
+    }
+
+    #[test]
+    fn test_parse_mnemonic_undocumented() {
+        let mut scanner = Scanner::new("LAX");
+        assert_eq!(
+            scanner.mnemonic(),
+            Err(ParseError::UndocumentedMnemonic { mnemonic: Mnemonic::LAX, cpu: Cpu::Mos6502X })
+        );
+
+        let mut scanner = Scanner::new("lax");
+        scanner.cpu = Cpu::Mos6502X;
+        assert_eq!(scanner.mnemonic().unwrap(), Some(Token::Mnemonic(Mnemonic::LAX)));
+
+        // Other common names for the same instruction
+        let mut scanner = Scanner::new("ISB");
+        scanner.cpu = Cpu::Mos6502X;
+        assert_eq!(scanner.mnemonic().unwrap(), Some(Token::Mnemonic(Mnemonic::ISC)));
+
+        // The undocumented opcodes do not exist on the 65C02
+        let mut scanner = Scanner::new("DCP");
+        scanner.cpu = Cpu::Wdc65C02;
+        assert_eq!(
+            scanner.mnemonic(),
+            Err(ParseError::UnsupportedMnemonic { mnemonic: Mnemonic::DCP, cpu: Cpu::Wdc65C02 })
+        );
+    }
 }
\ No newline at end of file
@@ -0,0 +1,196 @@
+use std::{collections::HashSet, fmt, str::FromStr};
+
+use forge_lib::{cpu::Cpu, directive::Directive, line::{Line, MainComponent}, mnemonic::Mnemonic, span::Span};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+
+/// The categories warnings are grouped into. These are the names used with `-W` and `.warning`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumString, Display, EnumIter)]
+#[strum(serialize_all = "kebab-case")]
+pub enum WarningKind {
+    DecimalMode,
+    UnusedSymbol,
+    UnreachableCode,
+    ZeroPageAbsolute,
+    PageCrossingBranch,
+    ShadowedSymbol,
+    ImplicitAccumulator,
+}
+
+impl WarningKind {
+    /// Returns true for the warnings that are almost always a mistake or dead code. The rest are a
+    /// matter of style and have to be turned on
+    pub fn enabled_by_default(&self) -> bool {
+        matches!(
+            self,
+            WarningKind::DecimalMode | WarningKind::UnusedSymbol | WarningKind::ShadowedSymbol | WarningKind::UnreachableCode
+        )
+    }
+}
+
+/// Problems in the source that do not stop it from being assembled
+#[derive(Debug, PartialEq, Clone)]
+pub enum Warning {
+    DecimalModeUnavailable { mnemonic: Mnemonic, cpu: Cpu },
+    DecimalArithmetic { mnemonic: Mnemonic, cpu: Cpu },
+    UnusedLabel { name: String },
+    UnusedConstant { name: String },
+    UnusedProc { name: String },
+    UnreachableCode { after: Mnemonic },
+    ZeroPageAbsolute { mnemonic: Mnemonic, address: u16 },
+    PageCrossingBranch { mnemonic: Mnemonic, target: String },
+    ShadowedSymbol { name: String, first: Span },
+    ImplicitAccumulator { mnemonic: Mnemonic },
+}
+
+impl Warning {
+    pub fn kind(&self) -> WarningKind {
+        match self {
+            Warning::DecimalModeUnavailable { .. } | Warning::DecimalArithmetic { .. } => WarningKind::DecimalMode,
+            Warning::UnusedLabel { .. } | Warning::UnusedConstant { .. } | Warning::UnusedProc { .. } => WarningKind::UnusedSymbol,
+            Warning::UnreachableCode { .. } => WarningKind::UnreachableCode,
+            Warning::ZeroPageAbsolute { .. } => WarningKind::ZeroPageAbsolute,
+            Warning::PageCrossingBranch { .. } => WarningKind::PageCrossingBranch,
+            Warning::ShadowedSymbol { .. } => WarningKind::ShadowedSymbol,
+            Warning::ImplicitAccumulator { .. } => WarningKind::ImplicitAccumulator,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::DecimalModeUnavailable { mnemonic: Mnemonic::SED, cpu } => {
+                write!(f, "SED has no effect on the {}, which has no decimal mode", cpu)
+            }
+            Warning::DecimalModeUnavailable { mnemonic, cpu } => {
+                write!(f, "{} after SED suggests decimal mode is relied on, but the {} has no decimal mode", mnemonic, cpu)
+            }
+            Warning::DecimalArithmetic { mnemonic, cpu } => {
+                write!(f, "{} after SED assumes decimal mode, but the {} always does binary arithmetic", mnemonic, cpu)
+            }
+            Warning::UnusedLabel { name } => {
+                write!(f, "Label {} is never used", name)
+            }
+            Warning::UnusedConstant { name } => {
+                write!(f, "Constant {} is never used", name)
+            }
+            Warning::UnusedProc { name } => {
+                write!(f, "Proc {} is never used", name)
+            }
+            Warning::UnreachableCode { after } => {
+                write!(f, "Unreachable code. Nothing after the {} jumps here since there is no label", after)
+            }
+            Warning::ZeroPageAbsolute { mnemonic, address } => {
+                write!(f, "${:04X} is in the zero page, so {} could use zero page addressing and save a byte", address, mnemonic)
+            }
+            Warning::PageCrossingBranch { mnemonic, target } => {
+                write!(f, "{} to {} crosses a page boundary and takes an extra cycle when taken", mnemonic, target)
+            }
+            Warning::ShadowedSymbol { name, .. } => {
+                write!(f, "{} shadows an earlier definition", name)
+            }
+            Warning::ImplicitAccumulator { mnemonic } => {
+                write!(f, "{} works on the accumulator here. Write {} A to make that explicit", mnemonic, mnemonic)
+            }
+        }
+    }
+}
+
+/// A `-W` flag from the command line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WarningFlag {
+    Enable(WarningKind),
+    Disable(WarningKind),
+    All,
+    Error,
+}
+
+impl FromStr for WarningFlag {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "all" => return Ok(WarningFlag::All),
+            "error" => return Ok(WarningFlag::Error),
+            _ => {}
+        }
+
+        let (name, enabled) = match value.strip_prefix("no-") {
+            Some(name) => (name, false),
+            None => (value, true),
+        };
+
+        match WarningKind::from_str(name) {
+            Ok(kind) if enabled => Ok(WarningFlag::Enable(kind)),
+            Ok(kind) => Ok(WarningFlag::Disable(kind)),
+            Err(_) => Err(format!("unknown warning {}. Expected one of: all, error, {}", name, warning_names())),
+        }
+    }
+}
+
+/// Returns the name of every warning category, separated by commas
+pub fn warning_names() -> String {
+    WarningKind::iter().map(|kind| kind.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// Decides which warnings are reported. Starts from the defaults, then the `-W` flags, then any
+/// `.warning` pragmas before the warning in the source
+pub struct WarningOptions {
+    enabled: HashSet<WarningKind>,
+    /// Whether warnings are reported as errors (-Werror)
+    pub as_errors: bool,
+    /// Where each pragma is along with the category it changes, or None for all of them
+    pragmas: Vec<(Span, Option<WarningKind>, bool)>,
+}
+
+impl WarningOptions {
+    pub fn new(flags: &[WarningFlag]) -> Self {
+        let mut options = Self {
+            enabled: WarningKind::iter().filter(WarningKind::enabled_by_default).collect(),
+            as_errors: false,
+            pragmas: Vec::new(),
+        };
+
+        for flag in flags {
+            match flag {
+                WarningFlag::Enable(kind) => {
+                    options.enabled.insert(*kind);
+                }
+                WarningFlag::Disable(kind) => {
+                    options.enabled.remove(kind);
+                }
+                WarningFlag::All => options.enabled.extend(WarningKind::iter()),
+                WarningFlag::Error => options.as_errors = true,
+            }
+        }
+
+        options
+    }
+
+    /// Picks up the `.warning` pragmas in the lines. The names have already been checked by the scanner
+    pub fn add_pragmas(&mut self, lines: &[Line]) {
+        for main_component in lines.iter().flat_map(|line| &line.main_components) {
+            if let MainComponent::Directive(Directive::WARNING { enabled, name }, span) = main_component {
+                let kind = WarningKind::from_str(name).ok();
+                self.pragmas.push((*span, kind, *enabled));
+            }
+        }
+    }
+
+    /// Returns true if a warning of the given kind found at the span should be reported
+    pub fn is_enabled(&self, kind: WarningKind, span: Span) -> bool {
+        let mut enabled = self.enabled.contains(&kind);
+
+        let applicable = self.pragmas.iter().filter(|(pragma_span, pragma_kind, _)| {
+            pragma_span.file_id == span.file_id
+                && pragma_span.line < span.line
+                && pragma_kind.is_none_or(|pragma_kind| pragma_kind == kind)
+        });
+        for (_, _, pragma_enabled) in applicable {
+            enabled = *pragma_enabled;
+        }
+
+        enabled
+    }
+}
@@ -1,3 +1,577 @@
-pub struct _Linker {
-    
-}
\ No newline at end of file
+use std::collections::{BTreeMap, HashMap};
+
+use forge_lib::{
+    error::ForgeError,
+    linker::{LinkerConfig, SegmentConfig, SegmentKind},
+    object::{ObjectExpression, OutFile, Relocation, SymbolValue},
+};
+
+/// A symbol exported by one of the files being linked
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Export {
+    /// The index of the file that defines it
+    pub file: usize,
+    pub value: SymbolValue,
+}
+
+/// Where one file's part of a segment is placed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Part {
+    pub file: usize,
+    /// The index of the segment in the file
+    pub segment: usize,
+    /// The address the code runs at
+    pub address: u32,
+    /// The address the bytes are stored at, which differs from the address when the segment has
+    /// a `run` memory area
+    pub load_address: u32,
+}
+
+/// A segment from the config along with the part of it each file gives
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedSegment {
+    /// The index of the segment in the config
+    pub config: usize,
+    pub address: u32,
+    pub load_address: u32,
+    pub size: u32,
+    pub parts: Vec<Part>,
+}
+
+/// The object files being linked, along with the symbols they share and where their code goes
+#[derive(Debug)]
+pub struct Linker {
+    pub files: Vec<OutFile>,
+    /// The path each file was read from
+    pub paths: Vec<String>,
+    pub config: LinkerConfig,
+    pub exports: HashMap<String, Export>,
+    /// The segments in the config that any file has, in the order they are placed
+    pub segments: Vec<PlacedSegment>,
+    /// The bytes used in each memory area, including the gaps between segments
+    pub used: Vec<u32>,
+    /// The address of each segment in each file, keyed by the index of the file and segment
+    addresses: HashMap<(usize, usize), u32>,
+}
+
+impl Linker {
+    /// Matches the symbols each file imports with the file that exports them. Every symbol that is
+    /// exported by more than one file or imported but never exported is reported, sorted by name
+    pub fn new(files: Vec<(String, OutFile)>, config: LinkerConfig) -> Result<Self, Vec<ForgeError>> {
+        let (paths, files): (Vec<String>, Vec<OutFile>) = files.into_iter().unzip();
+        let mut exporters: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (index, file) in files.iter().enumerate() {
+            for symbol in file.contents.symbols.iter().filter(|symbol| symbol.visibility.is_exported()) {
+                exporters.entry(&symbol.name).or_default().push(index);
+            }
+        }
+
+        let mut importers: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (index, file) in files.iter().enumerate() {
+            for import in &file.contents.imports {
+                importers.entry(&import.name).or_default().push(index);
+            }
+        }
+
+        let mut errors = Vec::new();
+
+        for (name, indexes) in exporters.iter().filter(|(_, indexes)| indexes.len() > 1) {
+            errors.push(ForgeError::DuplicateSymbol { name: name.to_string(), files: file_names(&files, indexes) });
+        }
+
+        for (name, indexes) in importers.iter().filter(|(name, _)| !exporters.contains_key(*name)) {
+            errors.push(ForgeError::UndefinedSymbol { name: name.to_string(), files: file_names(&files, indexes) });
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut exports = HashMap::new();
+        for (file, contents) in files.iter().map(|file| &file.contents).enumerate() {
+            for symbol in contents.symbols.iter().filter(|symbol| symbol.visibility.is_exported()) {
+                exports.insert(symbol.name.clone(), Export { file, value: symbol.value });
+            }
+        }
+
+        Ok(Self { files, paths, config, exports, segments: Vec::new(), used: Vec::new(), addresses: HashMap::new() })
+    }
+
+    /// Places the segments of every file into memory, going through the segments in config order
+    /// and the files in the order they were given. Each file's part of a segment follows the part
+    /// before it. Segments missing from the config and memory areas that are too small are reported
+    pub fn place(&mut self) -> Result<(), Vec<ForgeError>> {
+        let mut errors = Vec::new();
+
+        // Empty segments are left out, as every file has a code segment even if nothing is in it
+        let mut unknown: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (index, file) in self.files.iter().enumerate() {
+            for segment in file.contents.segments.iter().filter(|segment| !segment.bytes.is_empty()) {
+                if self.config.segment(&segment.name).is_none() {
+                    unknown.entry(&segment.name).or_default().push(index);
+                }
+            }
+        }
+
+        for (segment, indexes) in unknown {
+            errors.push(ForgeError::SegmentNotInConfig { segment: segment.to_string(), files: file_names(&self.files, &indexes) });
+        }
+
+        let mut next: Vec<u32> = self.config.memory.iter().map(|memory| memory.start).collect();
+        let mut usage: Vec<Vec<(String, u32)>> = vec![Vec::new(); self.config.memory.len()];
+
+        for (index, segment_config) in self.config.segments.iter().enumerate() {
+            let pieces: Vec<(usize, usize, u32)> = self
+                .files
+                .iter()
+                .enumerate()
+                .flat_map(|(file, out_file)| {
+                    out_file.contents.segments.iter().enumerate().filter(|(_, segment)| segment.name == segment_config.name).map(
+                        move |(segment, contents)| (file, segment, contents.bytes.len() as u32),
+                    )
+                })
+                .collect();
+
+            if pieces.is_empty() {
+                continue;
+            }
+
+            let size = pieces.iter().map(|(_, _, size)| size).sum();
+            let load = self.memory_index(&segment_config.load);
+            let run = segment_config.run.as_ref().map_or(load, |run| self.memory_index(run));
+
+            // Labels can still be in an empty segment, so it is given the next free address without
+            // taking up any space
+            if size == 0 {
+                let parts = pieces
+                    .into_iter()
+                    .map(|(file, segment, _)| Part { file, segment, address: next[run], load_address: next[load] })
+                    .collect();
+                self.segments.push(PlacedSegment { config: index, address: next[run], load_address: next[load], size, parts });
+                continue;
+            }
+
+            let load_address = match run == load {
+                true => self.segment_start(segment_config, load, next[load], &mut errors),
+                false => next[load],
+            };
+            let address = match run == load {
+                true => load_address,
+                false => self.segment_start(segment_config, run, next[run], &mut errors),
+            };
+
+            next[load] = load_address + size;
+            next[run] = address + size;
+            usage[load].push((segment_config.name.clone(), size));
+            if run != load {
+                usage[run].push((segment_config.name.clone(), size));
+            }
+
+            let mut parts = Vec::new();
+            let mut offset = 0;
+            for (file, segment, part_size) in pieces {
+                parts.push(Part { file, segment, address: address + offset, load_address: load_address + offset });
+                offset += part_size;
+            }
+
+            self.segments.push(PlacedSegment { config: index, address, load_address, size, parts });
+        }
+
+        self.addresses = self
+            .segments
+            .iter()
+            .flat_map(|segment| &segment.parts)
+            .map(|part| ((part.file, part.segment), part.address))
+            .collect();
+
+        self.used = self.config.memory.iter().zip(&next).map(|(memory, next)| next - memory.start).collect();
+
+        for ((memory, next), segments) in self.config.memory.iter().zip(next).zip(usage) {
+            if next > memory.start + memory.size {
+                errors.push(ForgeError::MemoryAreaOverflow {
+                    memory: memory.name.clone(),
+                    size: memory.size,
+                    used: next - memory.start,
+                    segments,
+                });
+            }
+        }
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+
+    /// The segments in the config that are in none of the files and are not marked optional
+    pub fn missing_segments(&self) -> impl Iterator<Item = &SegmentConfig> {
+        self.config
+            .segments
+            .iter()
+            .enumerate()
+            .filter(|(index, segment)| !segment.optional && !self.segments.iter().any(|placed| placed.config == *index))
+            .map(|(_, segment)| segment)
+    }
+
+    /// Builds the output from the memory areas in config order, with every relocation patched.
+    /// Areas are only written if they have code or data in them or are filled. Gaps between
+    /// segments are filled, and areas with `fill = yes` are padded out to their full size
+    pub fn link(&self) -> Result<Vec<u8>, Vec<ForgeError>> {
+        let mut output = Vec::new();
+        let mut errors = Vec::new();
+
+        for (index, memory) in self.config.memory.iter().enumerate() {
+            let mut image = Vec::new();
+
+            for segment in self.segments.iter().filter(|segment| self.memory_index(&self.config.segments[segment.config].load) == index) {
+                if !self.is_written(segment) {
+                    continue;
+                }
+
+                let segment_config = &self.config.segments[segment.config];
+
+                let fill_value = segment_config.fill_value.unwrap_or(memory.fill_value);
+                image.resize((segment.load_address - memory.start) as usize, fill_value);
+                for part in &segment.parts {
+                    image.extend(self.patch(part, &mut errors));
+                }
+            }
+
+            if memory.fill {
+                image.resize(memory.size as usize, memory.fill_value);
+            }
+
+            output.extend(image);
+        }
+
+        match errors.is_empty() {
+            true => Ok(output),
+            false => Err(errors),
+        }
+    }
+
+    /// How many bytes of each memory area are written to the output
+    pub fn output_sizes(&self) -> Vec<u32> {
+        self.config
+            .memory
+            .iter()
+            .enumerate()
+            .map(|(index, memory)| {
+                let end = self
+                    .segments
+                    .iter()
+                    .filter(|segment| self.is_written(segment) && self.memory_index(&self.config.segments[segment.config].load) == index)
+                    .map(|segment| segment.load_address + segment.size - memory.start)
+                    .max();
+
+                match memory.fill {
+                    true => memory.size,
+                    false => end.unwrap_or(0),
+                }
+            })
+            .collect()
+    }
+
+    /// Whether a segment's bytes go in the output. Uninitialised segments only take up space
+    pub fn is_written(&self, segment: &PlacedSegment) -> bool {
+        segment.size > 0 && !matches!(self.config.segments[segment.config].kind, SegmentKind::Bss | SegmentKind::ZeroPage)
+    }
+
+    /// Returns the bytes of a file's part of a segment with its relocations filled in
+    fn patch(&self, part: &Part, errors: &mut Vec<ForgeError>) -> Vec<u8> {
+        let contents = &self.files[part.file].contents;
+        let segment = &contents.segments[part.segment];
+        let mut bytes = segment.bytes.clone();
+
+        let segment_start = |segment: usize| self.segment_address(part.file, segment) as i32;
+        let symbol = |name: &str| self.exports.get(name).map(|export| self.symbol_value(export.file, export.value) as i32);
+
+        for relocation in &segment.relocations {
+            let address = part.address as i32 + relocation.offset as i32;
+            let encoded = match relocation.expression.evaluate(&segment_start, &symbol) {
+                Ok(value) => relocation.kind.encode(value, address),
+                Err(name) => Err(ForgeError::LabelOrConstantNotFound { label: name }),
+            };
+
+            match encoded {
+                Ok(encoded) => {
+                    let start = relocation.offset as usize;
+                    bytes[start..start + encoded.len()].copy_from_slice(&encoded);
+                }
+                Err(error) => errors.push(self.relocation_error(part.file, relocation, error)),
+            }
+        }
+
+        bytes
+    }
+
+    /// The address a segment in a file was placed at. Only empty segments can be left out of the
+    /// config, so anything in a segment that was not placed has no real address
+    pub fn segment_address(&self, file: usize, segment: usize) -> u32 {
+        self.addresses.get(&(file, segment)).copied().unwrap_or(0)
+    }
+
+    /// The final value of a symbol defined in a file
+    pub fn symbol_value(&self, file: usize, value: SymbolValue) -> u32 {
+        match value {
+            SymbolValue::Constant(value) => value as u32,
+            SymbolValue::Label { segment, offset } => self.segment_address(file, segment) + offset as u32,
+        }
+    }
+
+    /// Adds where a relocation came from to an error, naming the symbol it used if there is one
+    fn relocation_error(&self, file: usize, relocation: &Relocation, error: ForgeError) -> ForgeError {
+        ForgeError::RelocationFailed {
+            file: self.files[file].header.file_name.clone(),
+            object: self.paths[file].clone(),
+            span: relocation.span,
+            symbol: self.symbol_name(file, &relocation.expression),
+            error: Box::new(error),
+        }
+    }
+
+    /// Finds the first symbol in an expression. Labels in the same file were already turned into
+    /// segment offsets, so they are looked up by their value
+    fn symbol_name(&self, file: usize, expression: &ObjectExpression) -> Option<String> {
+        match expression {
+            ObjectExpression::Number(_) => None,
+            ObjectExpression::Symbol(name) => Some(name.clone()),
+            ObjectExpression::SegmentOffset { segment, offset } => self.files[file]
+                .contents
+                .symbols
+                .iter()
+                .find(|symbol| symbol.value == SymbolValue::Label { segment: *segment, offset: *offset })
+                .map(|symbol| symbol.name.clone()),
+            ObjectExpression::Binary(_, left, right) => {
+                self.symbol_name(file, left).or_else(|| self.symbol_name(file, right))
+            }
+            ObjectExpression::Unary(_, inner) => self.symbol_name(file, inner),
+        }
+    }
+
+    /// Finds where a segment starts in a memory area given the next free address in it
+    fn segment_start(&self, segment: &SegmentConfig, memory: usize, next: u32, errors: &mut Vec<ForgeError>) -> u32 {
+        let fixed = match (segment.offset, segment.start) {
+            (Some(offset), _) => Some(self.config.memory[memory].start + offset),
+            (None, start) => start,
+        };
+
+        let start = match fixed {
+            Some(start) if start < next => {
+                errors.push(ForgeError::SegmentStartTooLow { segment: segment.name.clone(), start, next });
+                next
+            }
+            Some(start) => start,
+            None => next,
+        };
+
+        match segment.align {
+            Some(align) => start.next_multiple_of(align),
+            None => start,
+        }
+    }
+
+    /// The config is checked when it is parsed, so segments only name memory areas that exist
+    pub fn memory_index(&self, name: &str) -> usize {
+        self.config.memory.iter().position(|memory| memory.name == name).expect("Segments use memory areas in the config")
+    }
+}
+
+fn file_names(files: &[OutFile], indexes: &[usize]) -> Vec<String> {
+    indexes.iter().map(|index| files[*index].header.file_name.clone()).collect()
+}
+
+#[cfg(test)]
+pub(crate) mod linker_tests {
+    use std::collections::BTreeMap;
+
+    use forge_lib::{
+        error::ForgeError,
+        expression::BinaryOp,
+        linker::LinkerConfig,
+        object::{Contents, Header, ObjectExpression, OutFile, Relocation, RelocationKind, Segment, Symbol, SymbolValue, Visibility},
+        span::Span,
+    };
+
+    use super::Linker;
+
+    /// An object file with the given segments and nothing else in it
+    pub(crate) fn object(name: &str, segments: &[(&str, &[u8])]) -> (String, OutFile) {
+        let segments = segments
+            .iter()
+            .map(|(segment, bytes)| Segment { bytes: bytes.to_vec(), ..Segment::new(segment) })
+            .collect();
+        let contents = Contents { segments, symbols: Vec::new(), imports: Vec::new(), comments: BTreeMap::new() };
+
+        (format!("{}.out", name), OutFile { header: Header::new(&format!("{}.asm", name)), contents })
+    }
+
+    pub(crate) fn place(config: &str, files: Vec<(String, OutFile)>) -> Result<Linker, Vec<ForgeError>> {
+        let mut linker = Linker::new(files, LinkerConfig::parse(config).unwrap())?;
+        linker.place()?;
+        Ok(linker)
+    }
+
+    /// The address each file's part of each segment was placed at, in placement order
+    fn addresses(linker: &Linker) -> Vec<(&str, usize, u32)> {
+        linker
+            .segments
+            .iter()
+            .flat_map(|segment| segment.parts.iter().map(move |part| (segment, part)))
+            .map(|(segment, part)| (linker.config.segments[segment.config].name.as_str(), part.file, part.address))
+            .collect()
+    }
+
+    const CONFIG: &str = "MEMORY { ROM: start = $8000, size = $20; }
+        SEGMENTS { CODE: load = ROM; RODATA: load = ROM; }";
+
+    #[test]
+    fn test_place_in_config_order() {
+        let linker = place(CONFIG, vec![
+            object("a", &[("RODATA", &[1]), ("CODE", &[0xEA, 0xEA])]),
+            object("b", &[("CODE", &[0x60])]),
+        ])
+        .unwrap();
+
+        assert_eq!(addresses(&linker), vec![("CODE", 0, 0x8000), ("CODE", 1, 0x8002), ("RODATA", 0, 0x8003)]);
+        assert_eq!(linker.used, vec![4]);
+        assert_eq!(linker.link().unwrap(), vec![0xEA, 0xEA, 0x60, 1]);
+    }
+
+    #[test]
+    fn test_place_start_offset_and_align() {
+        let config = "MEMORY { ROM: start = $8000, size = $40; }
+            SEGMENTS { CODE: load = ROM; RODATA: load = ROM, align = 8; DATA: load = ROM, start = $8010; VECTOR: load = ROM, offset = $3A; }";
+        let linker = place(config, vec![object("a", &[("CODE", &[1, 2, 3]), ("RODATA", &[4]), ("DATA", &[5]), ("VECTOR", &[6; 6])])]).unwrap();
+
+        assert_eq!(addresses(&linker), vec![("CODE", 0, 0x8000), ("RODATA", 0, 0x8008), ("DATA", 0, 0x8010), ("VECTOR", 0, 0x803A)]);
+        assert_eq!(linker.used, vec![0x40]);
+    }
+
+    #[test]
+    fn test_place_start_too_low() {
+        let config = "MEMORY { ROM: start = $8000, size = $20; } SEGMENTS { CODE: load = ROM; DATA: load = ROM, start = $8001; }";
+        let errors = place(config, vec![object("a", &[("CODE", &[1, 2, 3]), ("DATA", &[4])])]).unwrap_err();
+
+        assert_eq!(errors, vec![ForgeError::SegmentStartTooLow { segment: String::from("DATA"), start: 0x8001, next: 0x8003 }]);
+    }
+
+    #[test]
+    fn test_place_overflow() {
+        let errors = place(CONFIG, vec![object("a", &[("CODE", &[0; 0x1E])]), object("b", &[("RODATA", &[0; 3])])]).unwrap_err();
+
+        assert_eq!(errors, vec![ForgeError::MemoryAreaOverflow {
+            memory: String::from("ROM"),
+            size: 0x20,
+            used: 0x21,
+            segments: vec![(String::from("CODE"), 0x1E), (String::from("RODATA"), 3)],
+        }]);
+        assert_eq!(
+            errors[0].to_string(),
+            "Memory area ROM holds $20 bytes but $21 are needed for CODE ($1E bytes), RODATA ($3 bytes)"
+        );
+    }
+
+    #[test]
+    fn test_place_segment_not_in_config() {
+        let errors = place(CONFIG, vec![object("a", &[("CODE", &[1]), ("EXTRA", &[]), ("CHARS", &[2])])]).unwrap_err();
+
+        assert_eq!(errors, vec![ForgeError::SegmentNotInConfig { segment: String::from("CHARS"), files: vec![String::from("a.asm")] }]);
+    }
+
+    #[test]
+    fn test_link_fill() {
+        let config = "MEMORY { HEADER: start = 0, size = 4, fill = yes, fillval = $EE; ROM: start = $8000, size = 8, fill = yes, fillval = $FF; }
+            SEGMENTS { INES: load = HEADER; CODE: load = ROM; RODATA: load = ROM, align = 4, fillval = $AA; }";
+        let linker = place(config, vec![object("a", &[("INES", &[1, 2]), ("CODE", &[0xEA]), ("RODATA", &[0x11])])]).unwrap();
+
+        assert_eq!(linker.link().unwrap(), vec![1, 2, 0xEE, 0xEE, 0xEA, 0xAA, 0xAA, 0xAA, 0x11, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(linker.output_sizes(), vec![4, 8]);
+    }
+
+    #[test]
+    fn test_link_leaves_out_uninitialised_segments() {
+        let config = "MEMORY { ZP: start = 0, size = $100; RAM: start = $200, size = $100; ROM: start = $8000, size = $10; }
+            SEGMENTS { ZEROPAGE: load = ZP, type = zp; BSS: load = RAM, type = bss; CODE: load = ROM; }";
+        let linker = place(config, vec![object("a", &[("ZEROPAGE", &[0, 0]), ("BSS", &[0]), ("CODE", &[0x60])])]).unwrap();
+
+        assert_eq!(addresses(&linker), vec![("ZEROPAGE", 0, 0), ("BSS", 0, 0x200), ("CODE", 0, 0x8000)]);
+        assert_eq!(linker.link().unwrap(), vec![0x60]);
+        assert_eq!(linker.output_sizes(), vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn test_place_run_differs_from_load() {
+        let config = "MEMORY { ROM: start = $8000, size = $10, fill = yes; RAM: start = $300, size = $10; }
+            SEGMENTS { CODE: load = ROM; DATA: load = ROM, run = RAM, align = 4; }";
+        let linker = place(config, vec![object("a", &[("CODE", &[0xEA]), ("DATA", &[1, 2])])]).unwrap();
+
+        let data = &linker.segments[1];
+        assert_eq!((data.address, data.load_address), (0x300, 0x8001));
+        assert_eq!(linker.used, vec![3, 2]);
+        assert_eq!(&linker.link().unwrap()[..4], &[0xEA, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_missing_segments() {
+        let config = "MEMORY { ROM: start = $8000, size = $10; }
+            SEGMENTS { CODE: load = ROM; VECTOR: load = ROM; EXTRA: load = ROM, optional = yes; }";
+        let linker = place(config, vec![object("a", &[("CODE", &[1])])]).unwrap();
+
+        let missing: Vec<&str> = linker.missing_segments().map(|segment| segment.name.as_str()).collect();
+        assert_eq!(missing, vec!["VECTOR"]);
+    }
+
+    /// A file exporting `zp` at the start of ZEROPAGE and one using it through the given relocation
+    fn zero_page_files(kind: RelocationKind, expression: ObjectExpression) -> Vec<(String, OutFile)> {
+        let (name, mut exporter) = object("zp", &[("ZEROPAGE", &[0])]);
+        exporter.contents.symbols.push(Symbol {
+            name: String::from("zp"),
+            value: SymbolValue::Label { segment: 0, offset: 0 },
+            visibility: Visibility::ExportZeroPage,
+        });
+
+        let (user_name, mut user) = object("main", &[("CODE", &[0xA5, 0])]);
+        user.contents.segments[0].relocations.push(Relocation { offset: 1, kind, expression, span: Span::new(0, 3, 7, 4) });
+
+        vec![(name, exporter), (user_name, user)]
+    }
+
+    const ZERO_PAGE_CONFIG: &str = "MEMORY { ZP: start = 0, size = $100; ROM: start = $8000, size = $10; }
+        SEGMENTS { ZEROPAGE: load = ZP, type = zp; CODE: load = ROM; }";
+
+    #[test]
+    fn test_link_patches_relocations() {
+        let expression = ObjectExpression::Binary(
+            BinaryOp::Add,
+            Box::new(ObjectExpression::Symbol(String::from("zp"))),
+            Box::new(ObjectExpression::Number(1)),
+        );
+        let linker = place(ZERO_PAGE_CONFIG, zero_page_files(RelocationKind::ZeroPage8, expression)).unwrap();
+
+        assert_eq!(linker.link().unwrap(), vec![0xA5, 0x01]);
+    }
+
+    #[test]
+    fn test_link_zero_page_out_of_range() {
+        let expression = ObjectExpression::Binary(
+            BinaryOp::Subtract,
+            Box::new(ObjectExpression::Symbol(String::from("zp"))),
+            Box::new(ObjectExpression::Number(1)),
+        );
+        let linker = place(ZERO_PAGE_CONFIG, zero_page_files(RelocationKind::ZeroPage8, expression)).unwrap();
+        let errors = linker.link().unwrap_err();
+
+        assert_eq!(errors, vec![ForgeError::RelocationFailed {
+            file: String::from("main.asm"),
+            object: String::from("main.out"),
+            span: Span::new(0, 3, 7, 4),
+            symbol: Some(String::from("zp")),
+            error: Box::new(ForgeError::ValueOutOfRange { value: -1, kind: RelocationKind::ZeroPage8 }),
+        }]);
+        assert_eq!(
+            errors[0].to_string(),
+            "main.asm:3:7 in main.out: -$1 is not a zero page address. It has to be between $00 and $FF (symbol zp)"
+        );
+    }
+}
@@ -1,11 +1,15 @@
-use std::path::PathBuf;
+use std::{fs, path::PathBuf};
 
 use clap::{ValueEnum, Parser, CommandFactory, error::ErrorKind};
-use forge_lib::get_file_contents;
-use tracing::{metadata::LevelFilter, info, debug, error};
+use forge_lib::{error::{ForgeError, IoError}, get_file_contents, linker::LinkerConfig};
+use tracing::{metadata::LevelFilter, info, debug, error, warn};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
 mod linker;
+mod map;
+mod symbols;
+
+use linker::Linker;
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum VerboseLevels {
@@ -31,6 +35,26 @@ struct Cli {
     #[arg(short, long)]
     linker: PathBuf,
 
+    /// The binary to write. Defaults to the first input file with a .bin extension
+    #[arg(short, long, value_name = "OUTPUT")]
+    output: Option<PathBuf>,
+
+    /// Writes a map of where the segments and exported symbols were placed to this file
+    #[arg(long, value_name = "MAP")]
+    map: Option<PathBuf>,
+
+    /// Writes FCEUX .nl label files next to the output, one per PRG bank and one for RAM
+    #[arg(long)]
+    fceux: bool,
+
+    /// Writes a Mesen .mlb label file next to the output
+    #[arg(long)]
+    mesen: bool,
+
+    /// Writes a VICE monitor label file next to the output, with a .lbl extension
+    #[arg(long)]
+    vice: bool,
+
     /// The list of out files generated by the assembler
     input: Vec<PathBuf>,
 }
@@ -52,13 +76,25 @@ fn main() {
     let subscriber = FmtSubscriber::builder().with_env_filter(filter).finish();
     tracing::subscriber::set_global_default(subscriber).unwrap();
 
-    if cli.input.len() == 0 {
+    if cli.input.is_empty() {
         let mut cmd = Cli::command();
         cmd.error(ErrorKind::MissingRequiredArgument, "Missing input files. Please provide at least 1").exit();
     }
 
     info!("Starting linker");
 
+    let config = match LinkerConfig::load(&cli.linker) {
+        Ok(config) => config,
+        Err(errors) => exit_with_errors(errors),
+    };
+    debug!("{:?}", config);
+
+    let output_file = match cli.output {
+        Some(output) => output,
+        None => cli.input[0].with_extension("bin"),
+    };
+
+    let mut files = Vec::new();
     for file in cli.input {
         let file_contents = match get_file_contents(&file) {
             Ok(file) => file,
@@ -69,5 +105,65 @@ fn main() {
             }
         };
         debug!("{:?}", file_contents);
+        files.push((file.to_string_lossy().into_owned(), file_contents));
+    }
+
+    let mut linker = match Linker::new(files, config) {
+        Ok(linker) => linker,
+        Err(errors) => exit_with_errors(errors),
+    };
+    info!("Resolved {} exported symbols across {} files", linker.exports.len(), linker.files.len());
+
+    // The map is written even if the segments did not fit, as that is when it is needed most
+    let placed = linker.place();
+    if let Some(map_file) = &cli.map {
+        if let Err(error) = fs::write(map_file, linker.map()) {
+            exit_with_errors(vec![ForgeError::WriteFailed { file: map_file.to_string_lossy().into_owned(), error: IoError(error) }]);
+        }
+    }
+
+    if let Err(errors) = placed {
+        exit_with_errors(errors);
+    }
+
+    for segment in linker.missing_segments() {
+        warn!("Segment {} is in the linker config but not in any input file", segment.name);
+        eprintln!("Warning: Segment {} is in the linker config but not in any input file", segment.name);
+    }
+
+    let output = match linker.link() {
+        Ok(output) => output,
+        Err(errors) => exit_with_errors(errors),
+    };
+
+    let mut files_to_write = vec![(output_file.clone(), output.clone())];
+    if cli.fceux {
+        for (suffix, labels) in linker.fceux_labels() {
+            let mut path = output_file.clone().into_os_string();
+            path.push(format!(".{}", suffix));
+            files_to_write.push((PathBuf::from(path), labels.into_bytes()));
+        }
+    }
+    if cli.mesen {
+        files_to_write.push((output_file.with_extension("mlb"), linker.mesen_labels().into_bytes()));
     }
+    if cli.vice {
+        files_to_write.push((output_file.with_extension("lbl"), linker.vice_labels().into_bytes()));
+    }
+
+    for (path, contents) in files_to_write {
+        if let Err(error) = fs::write(&path, contents) {
+            exit_with_errors(vec![ForgeError::WriteFailed { file: path.to_string_lossy().into_owned(), error: IoError(error) }]);
+        }
+    }
+}
+
+/// Reports every error and stops
+fn exit_with_errors(errors: Vec<ForgeError>) -> ! {
+    for e in errors {
+        error!("{}", e);
+        eprintln!("Error: {}", e);
+    }
+
+    std::process::exit(1);
 }
@@ -0,0 +1,144 @@
+use std::fmt::Write;
+
+use forge_lib::object::SymbolValue;
+
+use crate::linker::Linker;
+
+/// Writes the rows as a table with a header, padding each column to its widest entry
+fn table(out: &mut String, title: &str, header: &[&str], rows: Vec<Vec<String>>) {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| rows.iter().map(|row| row[column].len()).chain([header[column].len()]).max().unwrap_or(0))
+        .collect();
+
+    let _ = writeln!(out, "{}", title);
+    for row in [header.iter().map(|name| name.to_string()).collect()].iter().chain(&rows) {
+        let line: Vec<String> = row.iter().zip(&widths).map(|(cell, width)| format!("{:<width$}", cell, width = width)).collect();
+        let _ = writeln!(out, "{}", line.join("  ").trim_end());
+    }
+    let _ = writeln!(out);
+}
+
+fn hex(value: u32) -> String {
+    format!("${:04X}", value)
+}
+
+impl Linker {
+    /// Lists the memory areas with how much of them is used, where each file's part of each segment
+    /// was placed and the value of every exported symbol. Meant to be called once segments are
+    /// placed, even if they did not fit, so the layout can be checked
+    pub fn map(&self) -> String {
+        let mut out = String::new();
+
+        let memory = self
+            .config
+            .memory
+            .iter()
+            .zip(&self.used)
+            .map(|(memory, used)| {
+                let free = match memory.size.checked_sub(*used) {
+                    Some(free) => hex(free),
+                    None => format!("-{}", hex(used - memory.size)),
+                };
+                vec![memory.name.clone(), hex(memory.start), hex((memory.start + memory.size).saturating_sub(1)), hex(memory.size), hex(*used), free]
+            })
+            .collect();
+        table(&mut out, "Memory areas", &["Name", "Start", "End", "Size", "Used", "Free"], memory);
+
+        let mut segments = Vec::new();
+        for segment in &self.segments {
+            let segment_config = &self.config.segments[segment.config];
+            for part in &segment.parts {
+                let size = self.files[part.file].contents.segments[part.segment].bytes.len() as u32;
+                if size == 0 {
+                    continue;
+                }
+
+                let load = match part.load_address == part.address {
+                    true => String::new(),
+                    false => format!("{} in {}", hex(part.load_address), segment_config.load),
+                };
+                segments.push(vec![
+                    segment_config.name.clone(),
+                    self.paths[part.file].clone(),
+                    hex(part.address),
+                    hex(part.address + size - 1),
+                    hex(size),
+                    load,
+                ]);
+            }
+        }
+        table(&mut out, "Segments", &["Name", "File", "Start", "End", "Size", "Loaded at"], segments);
+
+        let mut exports: Vec<_> = self.exports.iter().collect();
+        exports.sort_by(|a, b| a.0.cmp(b.0));
+        let exports = exports
+            .into_iter()
+            .map(|(name, export)| {
+                let kind = match export.value {
+                    SymbolValue::Constant(_) => "constant",
+                    SymbolValue::Label { .. } => "label",
+                };
+                vec![
+                    name.clone(),
+                    hex(self.symbol_value(export.file, export.value)),
+                    kind.to_string(),
+                    self.paths[export.file].clone(),
+                ]
+            })
+            .collect();
+        table(&mut out, "Exports", &["Name", "Value", "Kind", "File"], exports);
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod map_tests {
+    use forge_lib::{
+        linker::LinkerConfig,
+        object::{Symbol, SymbolValue, Visibility},
+    };
+
+    use crate::linker::{linker_tests::object, Linker};
+
+    fn export(name: &str, value: SymbolValue) -> Symbol {
+        Symbol { name: name.to_string(), value, visibility: Visibility::Export }
+    }
+
+    #[test]
+    fn test_map() {
+        let config = "MEMORY { ROM: start = $8000, size = 4; RAM: start = $200, size = $100; }
+            SEGMENTS { CODE: load = ROM; DATA: load = ROM, run = RAM; }";
+        let (main, mut main_file) = object("main", &[("CODE", &[0xEA, 0xEA]), ("DATA", &[1, 2])]);
+        main_file.contents.symbols.push(export("zeta", SymbolValue::Constant(0x10)));
+        main_file.contents.symbols.push(export("start", SymbolValue::Label { segment: 0, offset: 1 }));
+        let (util, mut util_file) = object("util", &[("CODE", &[0x60])]);
+        util_file.contents.symbols.push(export("alpha", SymbolValue::Label { segment: 0, offset: 0 }));
+
+        let mut linker = Linker::new(vec![(main, main_file), (util, util_file)], LinkerConfig::parse(config).unwrap()).unwrap();
+        // The map is still written when the segments do not fit
+        assert!(linker.place().is_err());
+
+        assert_eq!(
+            linker.map(),
+            "Memory areas
+Name  Start  End    Size   Used   Free
+ROM   $8000  $8003  $0004  $0005  -$0001
+RAM   $0200  $02FF  $0100  $0002  $00FE
+
+Segments
+Name  File      Start  End    Size   Loaded at
+CODE  main.out  $8000  $8001  $0002
+CODE  util.out  $8002  $8002  $0001
+DATA  main.out  $0200  $0201  $0002  $8003 in ROM
+
+Exports
+Name   Value  Kind      File
+alpha  $8002  label     util.out
+start  $8001  label     main.out
+zeta   $0010  constant  main.out
+
+"
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use std::{collections::HashSet, fmt::Write};
+
+use forge_lib::object::SymbolValue;
+
+use crate::linker::Linker;
+
+/// FCEUX numbers PRG ROM in 16K banks whatever the mapper switches
+const PRG_BANK_SIZE: u32 = 0x4000;
+/// Where the CPU sees PRG ROM. Memory areas written to the output from here up are taken to be PRG
+/// ROM, and anything else written, such as the iNES header or CHR ROM, is not
+const PRG_START: u32 = 0x8000;
+
+/// A label with its final address, for the symbol files debuggers read
+#[derive(Debug)]
+struct DebugLabel<'a> {
+    name: &'a str,
+    address: u32,
+    /// Whether the label is in a segment that is written to the output
+    written: bool,
+    /// Where the label is in PRG ROM, if it is in a memory area that is part of it
+    prg_offset: Option<u32>,
+    comment: Option<&'a str>,
+}
+
+impl Linker {
+    /// Where each memory area starts in PRG ROM, for the ones that are part of it, along with the
+    /// size of PRG ROM. The memory areas are in PRG ROM in the order they are written to the output
+    fn prg_rom(&self) -> (Vec<Option<u32>>, u32) {
+        let mut size = 0;
+        let starts = self
+            .config
+            .memory
+            .iter()
+            .zip(self.output_sizes())
+            .map(|(memory, written)| {
+                let start = (written > 0 && memory.start >= PRG_START).then_some(size);
+                size += start.map_or(0, |_| written);
+                start
+            })
+            .collect();
+
+        (starts, size)
+    }
+
+    /// Every label in every file, sorted by address. Anonymous labels have no name to show and are
+    /// left out, as are labels in segments that were not placed. When files use the same name for
+    /// different labels only the first is kept, since debuggers expect names to be unique
+    fn debug_labels(&self) -> Vec<DebugLabel<'_>> {
+        let (prg_starts, _) = self.prg_rom();
+        let mut labels: Vec<DebugLabel> = Vec::new();
+        let mut names = HashSet::new();
+
+        for (file, out_file) in self.files.iter().enumerate() {
+            for symbol in &out_file.contents.symbols {
+                let SymbolValue::Label { segment, offset } = symbol.value else { continue };
+                if symbol.name.starts_with(':') || names.contains(symbol.name.as_str()) {
+                    continue;
+                }
+
+                let Some((placed, part)) = self.segments.iter().find_map(|placed| {
+                    placed.parts.iter().find(|part| part.file == file && part.segment == segment).map(|part| (placed, part))
+                }) else {
+                    continue;
+                };
+
+                let load = self.memory_index(&self.config.segments[placed.config].load);
+                let written = self.is_written(placed);
+                let prg_offset = match (written, prg_starts[load]) {
+                    (true, Some(start)) => Some(start + part.load_address + offset as u32 - self.config.memory[load].start),
+                    _ => None,
+                };
+
+                names.insert(symbol.name.as_str());
+                labels.push(DebugLabel {
+                    name: &symbol.name,
+                    address: part.address + offset as u32,
+                    written,
+                    prg_offset,
+                    comment: out_file.contents.comments.get(&symbol.name).map(String::as_str),
+                });
+            }
+        }
+
+        labels.sort_by(|a, b| a.address.cmp(&b.address).then(a.name.cmp(b.name)));
+        labels
+    }
+
+    /// The FCEUX .nl files, as the suffix to add to the ROM's file name with the contents. Labels
+    /// in PRG ROM go in a file for their 16K bank (`.0.nl`, `.1.nl`, ...) and labels outside the ROM
+    /// below $8000 go in `.ram.nl`
+    pub fn fceux_labels(&self) -> Vec<(String, String)> {
+        let (_, prg_size) = self.prg_rom();
+        let mut ram = String::new();
+        let mut banks: Vec<String> = vec![String::new(); prg_size.div_ceil(PRG_BANK_SIZE) as usize];
+
+        for label in self.debug_labels() {
+            let file = match label.prg_offset {
+                Some(offset) => &mut banks[(offset / PRG_BANK_SIZE) as usize],
+                None if !label.written && label.address < PRG_START => &mut ram,
+                None => continue,
+            };
+
+            let _ = writeln!(file, "${:04X}#{}#{}", label.address, label.name, label.comment.unwrap_or_default());
+        }
+
+        let banks = banks.into_iter().enumerate().filter(|(_, labels)| !labels.is_empty());
+        let mut files: Vec<(String, String)> = banks.map(|(bank, labels)| (format!("{:X}.nl", bank), labels)).collect();
+        if !ram.is_empty() {
+            files.push((String::from("ram.nl"), ram));
+        }
+
+        files
+    }
+
+    /// A Mesen .mlb file. Labels in PRG ROM are given by their offset in it, labels in the 2K of
+    /// internal RAM (and its mirrors) by their address in it and labels at $6000-$7FFF outside the
+    /// ROM by their offset in save RAM. Anything else, such as hardware registers, is left out
+    pub fn mesen_labels(&self) -> String {
+        let mut out = String::new();
+
+        for label in self.debug_labels() {
+            let (kind, address) = match label.prg_offset {
+                Some(offset) => ('P', offset),
+                None if label.written => continue,
+                None if label.address < 0x2000 => ('R', label.address & 0x7FF),
+                None if (0x6000..PRG_START).contains(&label.address) => ('S', label.address - 0x6000),
+                None => continue,
+            };
+
+            let _ = write!(out, "{}:{:04X}:{}", kind, address, label.name);
+            if let Some(comment) = label.comment {
+                let _ = write!(out, ":{}", comment);
+            }
+            let _ = writeln!(out);
+        }
+
+        out
+    }
+
+    /// A VICE monitor label file, loaded with `-moncommands`. VICE names can only have letters,
+    /// digits and underscores, so the `@` in local label names becomes an underscore
+    pub fn vice_labels(&self) -> String {
+        let mut out = String::new();
+
+        for label in self.debug_labels() {
+            let _ = writeln!(out, "al C:{:04X} .{}", label.address, label.name.replace('@', "_"));
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod symbols_tests {
+    use forge_lib::object::{Symbol, SymbolValue, Visibility};
+
+    use crate::linker::{linker_tests::{object, place}, Linker};
+
+    const CONFIG: &str = "MEMORY { HEADER: start = 0, size = $10, fill = yes; PRG: start = $8000, size = $8000, fill = yes; RAM: start = $300, size = $100; }
+        SEGMENTS { INES: load = HEADER; CODE: load = PRG; VECTORS: load = PRG, start = $FFFA; BSS: load = RAM, type = bss; }";
+
+    fn label(name: &str, segment: usize, offset: u16) -> Symbol {
+        Symbol { name: name.to_string(), value: SymbolValue::Label { segment, offset }, visibility: Visibility::Local }
+    }
+
+    /// A 32K NES ROM with labels in both PRG banks and in RAM
+    fn nes_rom() -> Linker {
+        let header = [b'N', b'E', b'S', 0x1A, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let (name, mut file) = object("main", &[("INES", &header), ("CODE", &[0xEA, 0xEA, 0x60]), ("VECTORS", &[0; 6]), ("BSS", &[0; 2])]);
+        file.contents.symbols = vec![
+            label("reset", 1, 0),
+            label("reset@loop", 1, 1),
+            label("vectors", 2, 0),
+            label("counter", 3, 1),
+            label(":1", 1, 2),
+        ];
+        file.contents.comments.insert(String::from("reset"), String::from("Entry point"));
+
+        place(CONFIG, vec![(name, file)]).unwrap()
+    }
+
+    #[test]
+    fn test_fceux_labels() {
+        let linker = nes_rom();
+
+        assert_eq!(linker.fceux_labels(), vec![
+            (String::from("0.nl"), String::from("$8000#reset#Entry point\n$8001#reset@loop#\n")),
+            (String::from("1.nl"), String::from("$FFFA#vectors#\n")),
+            (String::from("ram.nl"), String::from("$0301#counter#\n")),
+        ]);
+    }
+
+    #[test]
+    fn test_mesen_labels() {
+        let linker = nes_rom();
+
+        // PRG ROM offsets leave out the iNES header
+        assert_eq!(
+            linker.mesen_labels(),
+            "R:0301:counter\nP:0000:reset:Entry point\nP:0001:reset@loop\nP:7FFA:vectors\n"
+        );
+    }
+
+    #[test]
+    fn test_labels_after_filled_header() {
+        // The header memory area is filled but holds no iNES header, so only the memory areas say
+        // where PRG ROM starts
+        let (name, mut file) = object("main", &[("INES", &[0xFF; 4]), ("CODE", &[0xEA; 6]), ("VECTORS", &[0; 6])]);
+        file.contents.symbols = vec![label("header", 0, 0), label("init", 1, 6)];
+        let linker = place(CONFIG, vec![(name, file)]).unwrap();
+
+        assert_eq!(linker.mesen_labels(), "P:0006:init\n");
+        assert_eq!(linker.fceux_labels(), vec![(String::from("0.nl"), String::from("$8006#init#\n"))]);
+    }
+
+    #[test]
+    fn test_vice_labels() {
+        let linker = nes_rom();
+
+        assert_eq!(linker.vice_labels(), "al C:0301 .counter\nal C:8000 .reset\nal C:8001 .reset_loop\nal C:FFFA .vectors\n");
+    }
+}
@@ -1,8 +1,8 @@
-use std::{fmt, collections::HashMap};
+use std::fmt;
 
 use serde_derive::{Deserialize, Serialize};
 
-use crate::{scoped_ref_to_string, label::LabelMetaData, error::ForgeError};
+use crate::{scoped_ref_to_string, error::ForgeError, expression::ExpressionNode};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AddressMode {
@@ -15,6 +15,7 @@ pub enum AddressMode {
     Immediate(u8),
     ImmediateIdent(String),
     ImmediateScopedRef(Vec<String>),
+    ImmediateExpression(ExpressionNode),
     ZeroPage(u8),
     ZeroPageX(u8),
     ZeroPageY(u8),
@@ -28,9 +29,24 @@ pub enum AddressMode {
     IndirectIndexYIdent(String),
     IndirectIndexYScopedRef(Vec<String>),
     Accumulator,
+    ZeroPageIndirect(u8),
+    Indirect(u16),
+    IndirectIdent(String),
+    IndirectScopedRef(Vec<String>),
+    AbsoluteIndexedIndirect(u16),
+    ZeroPageRelative(ExpressionNode, ExpressionNode),
+    ImmediateWord(u16),
+    AbsoluteLong(u32),
+    AbsoluteLongX(u32),
+    ZeroPageIndirectLong(u8),
+    ZeroPageIndirectLongY(u8),
+    AbsoluteIndirectLong(u16),
+    StackRelative(u8),
+    StackRelativeIndirectY(u8),
+    BlockMove(u8, u8),
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum AddressModeGeneric {
     Immediate,
     ZeroPage,
@@ -44,7 +60,152 @@ pub enum AddressModeGeneric {
     Implied,
     Accumulator,
     Relative,
-    Indirect
+    Indirect,
+    ZeroPageIndirect,
+    AbsoluteIndexedIndirect,
+    ZeroPageRelative,
+    AbsoluteLong,
+    AbsoluteLongX,
+    ZeroPageIndirectLong,
+    ZeroPageIndirectLongY,
+    AbsoluteIndirectLong,
+    StackRelative,
+    StackRelativeIndirectY,
+    BlockMove,
+    RelativeLong,
+}
+
+/// An address size given explicitly with an operand prefix (`a:`, `z:` or `f:`) or a mnemonic suffix
+/// (`LDA.a`), overriding the size picked from the value of the operand
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum AddressSize {
+    ZeroPage,
+    Absolute,
+    Far,
+}
+
+impl fmt::Display for AddressSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AddressSize::ZeroPage => "zero page",
+            AddressSize::Absolute => "absolute",
+            AddressSize::Far => "far",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl AddressSize {
+    /// Returns the size for a prefix or suffix letter, ignoring case
+    pub fn from_letter(letter: &str) -> Option<Self> {
+        match letter {
+            "z" | "Z" => Some(AddressSize::ZeroPage),
+            "a" | "A" => Some(AddressSize::Absolute),
+            "f" | "F" => Some(AddressSize::Far),
+            _ => None,
+        }
+    }
+
+    /// The number of bytes an address of this size takes
+    pub fn bytes(&self) -> u8 {
+        match self {
+            AddressSize::ZeroPage => 1,
+            AddressSize::Absolute => 2,
+            AddressSize::Far => 3,
+        }
+    }
+
+    /// Returns the address mode of the given kind with this size. None if the address mode has no
+    /// form of this size, such as immediate addressing or zero page Y with a far address
+    pub fn apply(&self, address_mode: AddressModeGeneric) -> Option<AddressModeGeneric> {
+        use AddressModeGeneric::*;
+
+        let sized = match (self, address_mode) {
+            (AddressSize::ZeroPage, ZeroPage | Absolute | AbsoluteLong) => ZeroPage,
+            (AddressSize::ZeroPage, ZeroPageX | AbsoluteX | AbsoluteLongX) => ZeroPageX,
+            (AddressSize::ZeroPage, ZeroPageY | AbsoluteY) => ZeroPageY,
+            (AddressSize::ZeroPage, ZeroPageIndirect | Indirect) => ZeroPageIndirect,
+            (AddressSize::ZeroPage, IndexedIndirectX | IndirectIndexY | ZeroPageIndirectLong | ZeroPageIndirectLongY) => {
+                address_mode
+            }
+            (AddressSize::Absolute, ZeroPage | Absolute | AbsoluteLong) => Absolute,
+            (AddressSize::Absolute, ZeroPageX | AbsoluteX | AbsoluteLongX) => AbsoluteX,
+            (AddressSize::Absolute, ZeroPageY | AbsoluteY) => AbsoluteY,
+            (AddressSize::Absolute, ZeroPageIndirect | Indirect) => Indirect,
+            (AddressSize::Far, ZeroPage | Absolute | AbsoluteLong) => AbsoluteLong,
+            (AddressSize::Far, ZeroPageX | AbsoluteX | AbsoluteLongX) => AbsoluteLongX,
+            _ => return None,
+        };
+
+        Some(sized)
+    }
+}
+
+impl AddressModeGeneric {
+    /// The number of bytes after the opcode. Immediates are given as one byte, since on the 65816
+    /// that depends on the register widths
+    pub fn operand_size(&self) -> u8 {
+        match self {
+            AddressModeGeneric::Implied | AddressModeGeneric::Accumulator => 0,
+            AddressModeGeneric::Immediate
+            | AddressModeGeneric::ZeroPage
+            | AddressModeGeneric::ZeroPageX
+            | AddressModeGeneric::ZeroPageY
+            | AddressModeGeneric::IndexedIndirectX
+            | AddressModeGeneric::IndirectIndexY
+            | AddressModeGeneric::Relative
+            | AddressModeGeneric::ZeroPageIndirect
+            | AddressModeGeneric::ZeroPageIndirectLong
+            | AddressModeGeneric::ZeroPageIndirectLongY
+            | AddressModeGeneric::StackRelative
+            | AddressModeGeneric::StackRelativeIndirectY => 1,
+            AddressModeGeneric::Absolute
+            | AddressModeGeneric::AbsoluteX
+            | AddressModeGeneric::AbsoluteY
+            | AddressModeGeneric::Indirect
+            | AddressModeGeneric::AbsoluteIndexedIndirect
+            | AddressModeGeneric::ZeroPageRelative
+            | AddressModeGeneric::AbsoluteIndirectLong
+            | AddressModeGeneric::BlockMove
+            | AddressModeGeneric::RelativeLong => 2,
+            AddressModeGeneric::AbsoluteLong | AddressModeGeneric::AbsoluteLongX => 3,
+        }
+    }
+}
+
+impl fmt::Display for AddressModeGeneric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AddressModeGeneric::Immediate => "immediate",
+            AddressModeGeneric::ZeroPage => "zero page",
+            AddressModeGeneric::ZeroPageX => "zero page X",
+            AddressModeGeneric::ZeroPageY => "zero page Y",
+            AddressModeGeneric::Absolute => "absolute",
+            AddressModeGeneric::AbsoluteX => "absolute X",
+            AddressModeGeneric::AbsoluteY => "absolute Y",
+            AddressModeGeneric::IndexedIndirectX => "indexed indirect X",
+            AddressModeGeneric::IndirectIndexY => "indirect index Y",
+            AddressModeGeneric::Implied => "implied",
+            AddressModeGeneric::Accumulator => "accumulator",
+            AddressModeGeneric::Relative => "relative",
+            AddressModeGeneric::Indirect => "indirect",
+            AddressModeGeneric::ZeroPageIndirect => "zero page indirect",
+            AddressModeGeneric::AbsoluteIndexedIndirect => "absolute indexed indirect",
+            AddressModeGeneric::ZeroPageRelative => "zero page relative",
+            AddressModeGeneric::AbsoluteLong => "absolute long",
+            AddressModeGeneric::AbsoluteLongX => "absolute long X",
+            AddressModeGeneric::ZeroPageIndirectLong => "zero page indirect long",
+            AddressModeGeneric::ZeroPageIndirectLongY => "zero page indirect long Y",
+            AddressModeGeneric::AbsoluteIndirectLong => "absolute indirect long",
+            AddressModeGeneric::StackRelative => "stack relative",
+            AddressModeGeneric::StackRelativeIndirectY => "stack relative indirect Y",
+            AddressModeGeneric::BlockMove => "block move",
+            AddressModeGeneric::RelativeLong => "relative long",
+        };
+
+        write!(f, "{}", name)
+    }
 }
 
 impl fmt::Display for AddressMode {
@@ -89,6 +250,9 @@ impl fmt::Display for AddressMode {
             AddressMode::ImmediateScopedRef(val) => {
                 write!(f, "Immediate Address Mode: #{}", scoped_ref_to_string(val))
             }
+            AddressMode::ImmediateExpression(val) => {
+                write!(f, "Immediate Address Mode: #{:?}", val)
+            }
             AddressMode::ZeroPage(val) => {
                 write!(f, "Zero Page Address Mode: ${:02X}", val)
             }
@@ -136,82 +300,126 @@ impl fmt::Display for AddressMode {
             AddressMode::Accumulator => {
                 write!(f, "Accumulator Address Mode: A")
             }
+            AddressMode::ZeroPageIndirect(val) => {
+                write!(f, "Zero Page Indirect Address Mode: (${:02X})", val)
+            }
+            AddressMode::Indirect(val) => {
+                write!(f, "Indirect Address Mode: (${:04X})", val)
+            }
+            AddressMode::IndirectIdent(val) => {
+                write!(f, "Indirect Address Mode: ({})", val)
+            }
+            AddressMode::IndirectScopedRef(val) => {
+                write!(f, "Indirect Address Mode: ({})", scoped_ref_to_string(val))
+            }
+            AddressMode::AbsoluteIndexedIndirect(val) => {
+                write!(f, "Absolute Indexed Indirect Address Mode: (${:04X},X)", val)
+            }
+            AddressMode::ZeroPageRelative(zero_page, target) => {
+                write!(f, "Zero Page Relative Address Mode: {:?},{:?}", zero_page, target)
+            }
+            AddressMode::ImmediateWord(val) => {
+                write!(f, "Immediate Address Mode: #${:04X}", val)
+            }
+            AddressMode::AbsoluteLong(val) => {
+                write!(f, "Absolute Long Address Mode: ${:06X}", val)
+            }
+            AddressMode::AbsoluteLongX(val) => {
+                write!(f, "Absolute Long X Address Mode: ${:06X},X", val)
+            }
+            AddressMode::ZeroPageIndirectLong(val) => {
+                write!(f, "Zero Page Indirect Long Address Mode: [${:02X}]", val)
+            }
+            AddressMode::ZeroPageIndirectLongY(val) => {
+                write!(f, "Zero Page Indirect Long Y Address Mode: [${:02X}],Y", val)
+            }
+            AddressMode::AbsoluteIndirectLong(val) => {
+                write!(f, "Absolute Indirect Long Address Mode: [${:04X}]", val)
+            }
+            AddressMode::StackRelative(val) => {
+                write!(f, "Stack Relative Address Mode: ${:02X},S", val)
+            }
+            AddressMode::StackRelativeIndirectY(val) => {
+                write!(f, "Stack Relative Indirect Y Address Mode: (${:02X},S),Y", val)
+            }
+            AddressMode::BlockMove(source, destination) => {
+                write!(f, "Block Move Address Mode: ${:02X},${:02X}", source, destination)
+            }
         }
     }
 }
 
 impl AddressMode {
-    pub fn to_generic(&self, label_map: &HashMap<String, LabelMetaData>, constant_map: &HashMap<String, u16>) -> Result<AddressModeGeneric, ForgeError> {
-        let value = match self {
-            AddressMode::Immediate(_) => AddressModeGeneric::Immediate,
-            AddressMode::Accumulator => AddressModeGeneric::Accumulator,
+    /// Returns the kind of addressing from the form of the address mode alone. A name is taken to be
+    /// a 16-bit address, since its value may not be known yet
+    pub fn generic(&self) -> AddressModeGeneric {
+        match self {
+            AddressMode::ZeroPageOrAbsoluteIdent(_) | AddressMode::ZeroPageOrAbsoluteScopedRef(_) => AddressModeGeneric::Absolute,
+            AddressMode::ZeroPageOrAbsoluteXIdent(_) | AddressMode::ZeroPageOrAbsoluteXScopedRef(_) => AddressModeGeneric::AbsoluteX,
+            AddressMode::ZeroPageOrAbsoluteYIdent(_) | AddressMode::ZeroPageOrAbsoluteYScopedRef(_) => AddressModeGeneric::AbsoluteY,
+            AddressMode::Immediate(_)
+            | AddressMode::ImmediateIdent(_)
+            | AddressMode::ImmediateScopedRef(_)
+            | AddressMode::ImmediateExpression(_)
+            | AddressMode::ImmediateWord(_) => AddressModeGeneric::Immediate,
             AddressMode::ZeroPage(_) => AddressModeGeneric::ZeroPage,
             AddressMode::ZeroPageX(_) => AddressModeGeneric::ZeroPageX,
             AddressMode::ZeroPageY(_) => AddressModeGeneric::ZeroPageY,
             AddressMode::Absolute(_) => AddressModeGeneric::Absolute,
             AddressMode::AbsoluteX(_) => AddressModeGeneric::AbsoluteX,
             AddressMode::AbsoluteY(_) => AddressModeGeneric::AbsoluteY,
-            AddressMode::IndexedIndirectX(_) => AddressModeGeneric::IndexedIndirectX,
-            AddressMode::IndirectIndexY(_) => AddressModeGeneric::IndirectIndexY,
-            AddressMode::ImmediateIdent(_) => AddressModeGeneric::Immediate,
-            AddressMode::ImmediateScopedRef(_) => AddressModeGeneric::Immediate,
-            AddressMode::IndexedIndirectXIdent(_) => AddressModeGeneric::IndexedIndirectX,
-            AddressMode::IndexedIndirectXScopedRef(_) => AddressModeGeneric::IndexedIndirectX,
-            AddressMode::IndirectIndexYIdent(_) => AddressModeGeneric::IndirectIndexY,
-            AddressMode::IndirectIndexYScopedRef(_) => AddressModeGeneric::IndirectIndexY,
-            AddressMode::ZeroPageOrAbsoluteIdent(ident) => {
-                if label_map.contains_key(ident) {
-                    return Ok(AddressModeGeneric::Absolute)
-                }
-
-                if constant_map.contains_key(ident) {
-                    let value = constant_map.get(ident).unwrap();
-                    if value <= &0xFF {
-                        return Ok(AddressModeGeneric::ZeroPage)
-                    } else {
-                        return Ok(AddressModeGeneric::Absolute)
-                    }
-                }
-
-                return Err(ForgeError::LabelOrConstantNotFound{ label: ident.clone() })
-            },
-            AddressMode::ZeroPageOrAbsoluteScopedRef(_) => AddressModeGeneric::Absolute,
-            AddressMode::ZeroPageOrAbsoluteXIdent(ident) => {
-                if label_map.contains_key(ident) {
-                    return Ok(AddressModeGeneric::AbsoluteX)
-                }
-
-                if constant_map.contains_key(ident) {
-                    let value = constant_map.get(ident).unwrap();
-                    if value <= &0xFF {
-                        return Ok(AddressModeGeneric::ZeroPageX)
-                    } else {
-                        return Ok(AddressModeGeneric::AbsoluteX)
-                    }
-                }
-
-                return Err(ForgeError::LabelOrConstantNotFound{ label: ident.clone() })
-            }
-            AddressMode::ZeroPageOrAbsoluteXScopedRef(_) => AddressModeGeneric::AbsoluteX,
-            AddressMode::ZeroPageOrAbsoluteYIdent(ident) => {
-                if label_map.contains_key(ident) {
-                    return Ok(AddressModeGeneric::AbsoluteY)
-                }
-
-                if constant_map.contains_key(ident) {
-                    let value = constant_map.get(ident).unwrap();
-                    if value <= &0xFF {
-                        return Ok(AddressModeGeneric::ZeroPageY)
-                    } else {
-                        return Ok(AddressModeGeneric::AbsoluteY)
-                    }
-                }
-
-                return Err(ForgeError::LabelOrConstantNotFound { label: ident.clone() })
-            }
-            AddressMode::ZeroPageOrAbsoluteYScopedRef(_) => AddressModeGeneric::AbsoluteY,
+            AddressMode::IndexedIndirectX(_)
+            | AddressMode::IndexedIndirectXIdent(_)
+            | AddressMode::IndexedIndirectXScopedRef(_) => AddressModeGeneric::IndexedIndirectX,
+            AddressMode::IndirectIndexY(_)
+            | AddressMode::IndirectIndexYIdent(_)
+            | AddressMode::IndirectIndexYScopedRef(_) => AddressModeGeneric::IndirectIndexY,
+            AddressMode::Accumulator => AddressModeGeneric::Accumulator,
+            AddressMode::ZeroPageIndirect(_) => AddressModeGeneric::ZeroPageIndirect,
+            AddressMode::Indirect(_) | AddressMode::IndirectIdent(_) | AddressMode::IndirectScopedRef(_) => {
+                AddressModeGeneric::Indirect
+            }
+            AddressMode::AbsoluteIndexedIndirect(_) => AddressModeGeneric::AbsoluteIndexedIndirect,
+            AddressMode::ZeroPageRelative(_, _) => AddressModeGeneric::ZeroPageRelative,
+            AddressMode::AbsoluteLong(_) => AddressModeGeneric::AbsoluteLong,
+            AddressMode::AbsoluteLongX(_) => AddressModeGeneric::AbsoluteLongX,
+            AddressMode::ZeroPageIndirectLong(_) => AddressModeGeneric::ZeroPageIndirectLong,
+            AddressMode::ZeroPageIndirectLongY(_) => AddressModeGeneric::ZeroPageIndirectLongY,
+            AddressMode::AbsoluteIndirectLong(_) => AddressModeGeneric::AbsoluteIndirectLong,
+            AddressMode::StackRelative(_) => AddressModeGeneric::StackRelative,
+            AddressMode::StackRelativeIndirectY(_) => AddressModeGeneric::StackRelativeIndirectY,
+            AddressMode::BlockMove(_, _) => AddressModeGeneric::BlockMove,
+        }
+    }
+
+    /// Returns the address mode with a numeric address given the forced size. Address modes using a
+    /// symbol are left alone, since the value is not known yet
+    pub fn with_size(self, size: AddressSize) -> Result<AddressMode, ForgeError> {
+        let address_mode = match (size, self) {
+            (
+                AddressSize::ZeroPage,
+                AddressMode::Absolute(value) | AddressMode::AbsoluteX(value) | AddressMode::AbsoluteY(value),
+            ) if value > 0xFF => {
+                return Err(ForgeError::ZeroPageOutOfRange { value: value as u32 });
+            }
+            (AddressSize::ZeroPage, AddressMode::AbsoluteLong(value) | AddressMode::AbsoluteLongX(value)) if value > 0xFF => {
+                return Err(ForgeError::ZeroPageOutOfRange { value });
+            }
+            (AddressSize::ZeroPage, AddressMode::Absolute(value)) => AddressMode::ZeroPage(value as u8),
+            (AddressSize::ZeroPage, AddressMode::AbsoluteX(value)) => AddressMode::ZeroPageX(value as u8),
+            (AddressSize::ZeroPage, AddressMode::AbsoluteY(value)) => AddressMode::ZeroPageY(value as u8),
+            (AddressSize::ZeroPage, AddressMode::AbsoluteLong(value)) => AddressMode::ZeroPage(value as u8),
+            (AddressSize::ZeroPage, AddressMode::AbsoluteLongX(value)) => AddressMode::ZeroPageX(value as u8),
+            (AddressSize::Absolute, AddressMode::ZeroPage(value)) => AddressMode::Absolute(value as u16),
+            (AddressSize::Absolute, AddressMode::ZeroPageX(value)) => AddressMode::AbsoluteX(value as u16),
+            (AddressSize::Absolute, AddressMode::ZeroPageY(value)) => AddressMode::AbsoluteY(value as u16),
+            (AddressSize::Far, AddressMode::ZeroPage(value)) => AddressMode::AbsoluteLong(value as u32),
+            (AddressSize::Far, AddressMode::ZeroPageX(value)) => AddressMode::AbsoluteLongX(value as u32),
+            (AddressSize::Far, AddressMode::Absolute(value)) => AddressMode::AbsoluteLong(value as u32),
+            (AddressSize::Far, AddressMode::AbsoluteX(value)) => AddressMode::AbsoluteLongX(value as u32),
+            (_, address_mode) => address_mode,
         };
 
-        Ok(value)
+        Ok(address_mode)
     }
 }
@@ -0,0 +1,194 @@
+use serde_derive::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+use crate::{
+    address::AddressMode,
+    directive::{Directive, Feature},
+    line::MainComponent,
+    mnemonic::Mnemonic,
+    operand::Operand,
+};
+
+/// The processor being assembled for. Selected with `--cpu` on the command line or
+/// with the `.cpu` directive in the source
+#[derive(
+    Debug, Default, PartialEq, Eq, Clone, Copy, EnumString, Display, Serialize, Deserialize, Hash,
+)]
+#[strum(ascii_case_insensitive)]
+pub enum Cpu {
+    /// The original NMOS 6502
+    #[default]
+    #[strum(to_string = "6502")]
+    Mos6502,
+    /// The NMOS 6502 with its undocumented ("illegal") opcodes enabled
+    #[strum(to_string = "6502X")]
+    Mos6502X,
+    /// The WDC 65C02, including the Rockwell bit instructions
+    #[strum(to_string = "65C02")]
+    Wdc65C02,
+    /// The WDC 65816 used in the SNES, with 16-bit registers and 24-bit addressing
+    #[strum(to_string = "65816")]
+    Wdc65816,
+    /// The Ricoh 2A03 used in the NES. An NMOS 6502 without decimal mode
+    #[strum(to_string = "2A03")]
+    Ricoh2A03,
+    /// The Ricoh 2A03 with its undocumented opcodes enabled
+    #[strum(to_string = "2A03X")]
+    Ricoh2A03X,
+}
+
+/// The family of processors an opcode belongs to
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum InstructionSet {
+    /// Documented NMOS 6502 instructions, available on every CPU
+    Nmos,
+    /// Instructions and address modes added by the CMOS 65C02
+    Cmos,
+    /// The Rockwell bit manipulation instructions (RMB, SMB, BBR and BBS)
+    Rockwell,
+    /// The undocumented NMOS opcodes such as LAX and DCP. These do not exist on CMOS parts
+    Undocumented,
+    /// Instructions and address modes added by the 65816
+    W65816,
+}
+
+impl Cpu {
+    /// Returns true if the CPU is able to execute instructions from the given set
+    pub fn supports(&self, instruction_set: InstructionSet) -> bool {
+        match self {
+            Cpu::Mos6502 | Cpu::Ricoh2A03 => instruction_set == InstructionSet::Nmos,
+            Cpu::Mos6502X | Cpu::Ricoh2A03X => {
+                matches!(instruction_set, InstructionSet::Nmos | InstructionSet::Undocumented)
+            }
+            Cpu::Wdc65C02 => matches!(
+                instruction_set,
+                InstructionSet::Nmos | InstructionSet::Cmos | InstructionSet::Rockwell
+            ),
+            Cpu::Wdc65816 => matches!(
+                instruction_set,
+                InstructionSet::Nmos | InstructionSet::Cmos | InstructionSet::W65816
+            ),
+        }
+    }
+
+    /// Returns the CPU with the undocumented opcodes enabled, which is only possible on NMOS parts
+    pub fn with_illegal_opcodes(&self) -> Option<Cpu> {
+        match self {
+            Cpu::Mos6502 | Cpu::Mos6502X => Some(Cpu::Mos6502X),
+            Cpu::Ricoh2A03 | Cpu::Ricoh2A03X => Some(Cpu::Ricoh2A03X),
+            Cpu::Wdc65C02 | Cpu::Wdc65816 => None,
+        }
+    }
+
+    /// Returns false for CPUs where SED is accepted but ADC and SBC always do binary arithmetic
+    pub fn has_decimal_mode(&self) -> bool {
+        !matches!(self, Cpu::Ricoh2A03 | Cpu::Ricoh2A03X)
+    }
+}
+
+/// The width of the 65816 accumulator or index registers
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum RegisterWidth {
+    #[default]
+    Bits8,
+    Bits16,
+}
+
+/// The register widths the assembler assumes when sizing immediate operands. They are changed
+/// with `.a8`, `.a16`, `.i8` and `.i16` or by a `REP`/`SEP` with a constant operand
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct RegisterWidths {
+    pub accumulator: RegisterWidth,
+    pub index: RegisterWidth,
+}
+
+impl RegisterWidths {
+    /// The bit in the status register selecting an 8-bit accumulator
+    const M_FLAG: u8 = 0x20;
+    /// The bit in the status register selecting 8-bit index registers
+    const X_FLAG: u8 = 0x10;
+
+    /// Sets the widths for the status register bits being changed by a `REP` or `SEP`
+    fn set_flags(&mut self, flags: u8, width: RegisterWidth) {
+        if flags & Self::M_FLAG != 0 {
+            self.accumulator = width;
+        }
+
+        if flags & Self::X_FLAG != 0 {
+            self.index = width;
+        }
+    }
+}
+
+/// Everything the assembler needs to know about the processor at a point in the source
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct CpuState {
+    pub cpu: Cpu,
+    pub widths: RegisterWidths,
+}
+
+impl CpuState {
+    pub fn new(cpu: Cpu) -> Self {
+        Self {
+            cpu,
+            widths: RegisterWidths::default(),
+        }
+    }
+
+    /// Updates the state after a line has been assembled. This follows `.cpu`, `.feature`, the
+    /// register width directives and `REP`/`SEP`
+    pub fn update(&mut self, main_component: &MainComponent) {
+        match main_component {
+            MainComponent::Directive(directive, _) => match directive {
+                Directive::CPU(cpu) => *self = CpuState::new(*cpu),
+                Directive::FEATURE(Feature::Illegal) => {
+                    self.cpu = self.cpu.with_illegal_opcodes().unwrap_or(self.cpu);
+                }
+                Directive::A8 => self.widths.accumulator = RegisterWidth::Bits8,
+                Directive::A16 => self.widths.accumulator = RegisterWidth::Bits16,
+                Directive::I8 => self.widths.index = RegisterWidth::Bits8,
+                Directive::I16 => self.widths.index = RegisterWidth::Bits16,
+                _ => {}
+            },
+            MainComponent::Instruction(instruction) => {
+                if self.cpu != Cpu::Wdc65816 {
+                    return;
+                }
+
+                if let Some(Operand::AddressMode(AddressMode::Immediate(flags))) = &instruction.operand {
+                    match instruction.mnemonic {
+                        Mnemonic::REP => self.widths.set_flags(*flags, RegisterWidth::Bits16),
+                        Mnemonic::SEP => self.widths.set_flags(*flags, RegisterWidth::Bits8),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the number of bytes an immediate operand takes for the given mnemonic. Only the
+    /// 65816 has 16-bit immediates, and only for instructions using the accumulator or index registers
+    pub fn immediate_size(&self, mnemonic: Mnemonic) -> u8 {
+        if self.cpu != Cpu::Wdc65816 {
+            return 1;
+        }
+
+        let width = match mnemonic {
+            Mnemonic::ADC
+            | Mnemonic::AND
+            | Mnemonic::BIT
+            | Mnemonic::CMP
+            | Mnemonic::EOR
+            | Mnemonic::LDA
+            | Mnemonic::ORA
+            | Mnemonic::SBC => self.widths.accumulator,
+            Mnemonic::CPX | Mnemonic::CPY | Mnemonic::LDX | Mnemonic::LDY => self.widths.index,
+            _ => RegisterWidth::Bits8,
+        };
+
+        match width {
+            RegisterWidth::Bits8 => 1,
+            RegisterWidth::Bits16 => 2,
+        }
+    }
+}
@@ -2,9 +2,9 @@ use std::collections::HashMap;
 
 use lazy_static::lazy_static;
 use serde_derive::{Serialize, Deserialize};
-use strum_macros::EnumString;
+use strum_macros::{Display, EnumString};
 
-use crate::expression::ExpressionNode;
+use crate::{cpu::Cpu, expression::ExpressionNode};
 
 #[derive(Debug, PartialEq, Clone, Copy, EnumString)]
 pub enum DirectiveName {
@@ -22,7 +22,24 @@ pub enum DirectiveName {
     SCOPE,
     ENDSCOPE,
     CODE,
+    RODATA,
+    DATA,
+    BSS,
+    ZEROPAGE,
     ADDR,
+    FARADDR,
+    EXPORT,
+    EXPORTZP,
+    IMPORT,
+    IMPORTZP,
+    GLOBAL,
+    CPU,
+    FEATURE,
+    A8,
+    A16,
+    I8,
+    I16,
+    WARNING,
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -31,6 +48,16 @@ pub enum Directive {
     BYTE(Vec<ByteArgs>),
     WORD(Vec<WordArgs>),
     SEGMENT(String),
+    /// 16-bit addresses, the same as `.word` but meant for address tables and vectors
+    ADDR(Vec<WordArgs>),
+    /// 24-bit addresses, for targets in another bank on the 65816
+    FARADDR(Vec<FarAddrArgs>),
+    /// Makes symbols defined in this file available to other files. Zero page symbols are 1-byte addresses
+    EXPORT { names: Vec<String>, zero_page: bool },
+    /// Declares symbols defined in another file. Zero page symbols are assembled as 1-byte addresses
+    IMPORT { names: Vec<String>, zero_page: bool },
+    /// Exports the symbols that are defined in this file and imports the rest
+    GLOBAL(Vec<String>),
     PROC(String),
     INCLUDE(String),
     ENDPROC,
@@ -39,7 +66,24 @@ pub enum Directive {
     MACRO(String),
     ENDMACRO,
     SCOPE(String),
-    ENDSCOPE
+    ENDSCOPE,
+    CPU(Cpu),
+    FEATURE(Feature),
+    A8,
+    A16,
+    I8,
+    I16,
+    /// Turns a category of assembler warnings on or off from this point on
+    WARNING { enabled: bool, name: String },
+}
+
+/// Optional assembler features that can be turned on with the `.feature` directive
+#[derive(Debug, PartialEq, Eq, Clone, Copy, EnumString, Display, Serialize, Deserialize)]
+#[strum(ascii_case_insensitive)]
+pub enum Feature {
+    /// Allows the undocumented NMOS opcodes, the same as selecting the 6502X
+    #[strum(to_string = "illegal")]
+    Illegal,
 }
 
 
@@ -69,6 +113,23 @@ impl Default for WordArgs {
     }
 }
 
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum FarAddrArgs {
+    Value(u32),
+    Identifier(String),
+    Expression(ExpressionNode),
+}
+
+impl From<WordArgs> for FarAddrArgs {
+    fn from(value: WordArgs) -> Self {
+        match value {
+            WordArgs::Value(value) => FarAddrArgs::Value(value as u32),
+            WordArgs::Identifier(name) => FarAddrArgs::Identifier(name),
+            WordArgs::Expression(expression) => FarAddrArgs::Expression(expression),
+        }
+    }
+}
+
 lazy_static! {
     static ref DIRECTIVE_MAP: HashMap<&'static str, DirectiveName> = {
         let mut m = HashMap::new();
@@ -86,11 +147,35 @@ lazy_static! {
         m.insert("MACRO", DirectiveName::MACRO);
         m.insert("ENDMACRO", DirectiveName::ENDMACRO);
         m.insert("CODE", DirectiveName::CODE);
+        m.insert("RODATA", DirectiveName::RODATA);
+        m.insert("DATA", DirectiveName::DATA);
+        m.insert("BSS", DirectiveName::BSS);
+        m.insert("ZEROPAGE", DirectiveName::ZEROPAGE);
         m.insert("ADDR", DirectiveName::ADDR);
+        m.insert("FARADDR", DirectiveName::FARADDR);
+        m.insert("EXPORT", DirectiveName::EXPORT);
+        m.insert("EXPORTZP", DirectiveName::EXPORTZP);
+        m.insert("IMPORT", DirectiveName::IMPORT);
+        m.insert("IMPORTZP", DirectiveName::IMPORTZP);
+        m.insert("GLOBAL", DirectiveName::GLOBAL);
+        m.insert("CPU", DirectiveName::CPU);
+        m.insert("FEATURE", DirectiveName::FEATURE);
+        m.insert("A8", DirectiveName::A8);
+        m.insert("A16", DirectiveName::A16);
+        m.insert("I8", DirectiveName::I8);
+        m.insert("I16", DirectiveName::I16);
+        m.insert("WARNING", DirectiveName::WARNING);
         m
     };
 }
 
+impl DirectiveName {
+    /// Looks up a directive by its upper case name, without the leading dot
+    pub fn from_name(name: &str) -> Option<DirectiveName> {
+        DIRECTIVE_MAP.get(name).copied()
+    }
+}
+
 impl From<String> for DirectiveName {
     fn from(value: String) -> Self {
         if let Some(&directive) = DIRECTIVE_MAP.get(value.as_str()) {
@@ -107,9 +192,12 @@ impl Directive {
             Directive::BYTE(args_list) => {
                 args_list.len() as u8
             }
-            Directive::WORD(args_list) => {
+            Directive::WORD(args_list) | Directive::ADDR(args_list) => {
                 (args_list.len() * 2) as u8
             }
+            Directive::FARADDR(args_list) => {
+                (args_list.len() * 3) as u8
+            }
             _ => {
                 0
             }
@@ -1,9 +1,54 @@
-use std::fmt::Display;
+use std::{fmt::Display, io};
 
-#[derive(Debug)]
+use semver::Version;
+
+use crate::{address::{AddressModeGeneric, AddressSize}, cpu::Cpu, linker::ConfigError, mnemonic::Mnemonic, object::{RelocationKind, LEGACY_OBJECT_VERSION, OBJECT_VERSION}, span::Span};
+
+#[derive(Debug, PartialEq)]
 pub enum ForgeError {
     NoSuchFileOrDir { file: String },
+    ReadFailed { file: String, error: IoError },
+    WriteFailed { file: String, error: IoError },
     LabelOrConstantNotFound { label: String },
+    InvalidAddressMode { mnemonic: Mnemonic, address_mode: AddressModeGeneric },
+    UnsupportedInstruction { mnemonic: Mnemonic, address_mode: AddressModeGeneric, cpu: Cpu },
+    ImmediateTooLarge { mnemonic: Mnemonic, cpu: Cpu },
+    InvalidAddressSize { mnemonic: Mnemonic, address_mode: AddressModeGeneric, size: AddressSize },
+    ZeroPageOutOfRange { value: u32 },
+    ValueOutOfRange { value: i32, kind: RelocationKind },
+    BadMagic { file: String },
+    UnsupportedObjectVersion { file: String, version: Version },
+    TruncatedObjectFile { file: String },
+    CorruptObjectFile { file: String },
+    UndefinedSymbol { name: String, files: Vec<String> },
+    DuplicateSymbol { name: String, files: Vec<String> },
+    InvalidLinkerConfig { file: String, span: Span, error: ConfigError },
+    SegmentNotInConfig { segment: String, files: Vec<String> },
+    SegmentStartTooLow { segment: String, start: u32, next: u32 },
+    MemoryAreaOverflow { memory: String, size: u32, used: u32, segments: Vec<(String, u32)> },
+    RelocationFailed { file: String, object: String, span: Span, symbol: Option<String>, error: Box<ForgeError> },
+}
+
+/// An I/O error kept for reporting. `io::Error` cannot be compared, so errors of the same kind are
+/// taken to be equal
+#[derive(Debug)]
+pub struct IoError(pub io::Error);
+
+impl PartialEq for IoError {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.kind() == other.0.kind()
+    }
+}
+
+impl ForgeError {
+    /// The error for a file that could not be read. A missing file is reported as such and
+    /// anything else keeps the I/O error
+    pub fn read_failed(file: String, error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => Self::NoSuchFileOrDir { file },
+            _ => Self::ReadFailed { file, error: IoError(error) },
+        }
+    }
 }
 
 impl Display for ForgeError {
@@ -12,9 +57,99 @@ impl Display for ForgeError {
             Self::NoSuchFileOrDir { file } => {
                 write!(f, "No such file or directory: {}", file)
             }
+            Self::ReadFailed { file, error } => {
+                write!(f, "Could not read {}: {}", file, error.0)
+            }
+            Self::WriteFailed { file, error } => {
+                write!(f, "Could not write {}: {}", file, error.0)
+            }
             Self::LabelOrConstantNotFound { label } => {
                 write!(f, "Label or constant not found: {}", label)
             }
+            Self::InvalidAddressMode { mnemonic, address_mode } => {
+                write!(f, "{} does not support {} addressing", mnemonic, address_mode)
+            }
+            Self::UnsupportedInstruction { mnemonic, address_mode, cpu } => {
+                write!(f, "{} with {} addressing is not available on the {}", mnemonic, address_mode, cpu)
+            }
+            Self::ImmediateTooLarge { mnemonic, cpu: Cpu::Wdc65816 } => {
+                write!(f, "{} only takes an 8-bit immediate here. Use .a16/.i16 or REP to widen the register", mnemonic)
+            }
+            Self::ImmediateTooLarge { mnemonic, cpu } => {
+                write!(f, "{} only takes an 8-bit immediate on the {}", mnemonic, cpu)
+            }
+            Self::InvalidAddressSize { mnemonic, address_mode, size } => {
+                write!(f, "{} with {} addressing cannot be given a {} address", mnemonic, address_mode, size)
+            }
+            Self::ValueOutOfRange { value, kind: kind @ (RelocationKind::Relative8 | RelocationKind::Relative16) } => {
+                let (min, max) = match kind {
+                    RelocationKind::Relative8 => (i8::MIN as i32, i8::MAX as i32),
+                    _ => (i16::MIN as i32, i16::MAX as i32),
+                };
+                write!(f, "Branch target is {} bytes away, but it has to be between {} and {}", value, min, max)
+            }
+            Self::ValueOutOfRange { value, kind: RelocationKind::ZeroPage8 } => {
+                write!(f, "{} is not a zero page address. It has to be between $00 and $FF", hex(*value))
+            }
+            Self::ValueOutOfRange { value, kind } => {
+                write!(f, "{} does not fit in {} bits", hex(*value), kind.size() * 8)
+            }
+            Self::ZeroPageOutOfRange { value } => {
+                write!(f, "${:04X} is forced to zero page but does not fit in a byte", value)
+            }
+            Self::BadMagic { file } => {
+                write!(f, "{} is not an object file", file)
+            }
+            Self::UnsupportedObjectVersion { file, version } => {
+                write!(
+                    f,
+                    "{} uses object format {}, but only {}.{} to {}.{} can be read. Assemble it again",
+                    file, version, LEGACY_OBJECT_VERSION.major, LEGACY_OBJECT_VERSION.minor, OBJECT_VERSION.major, OBJECT_VERSION.minor
+                )
+            }
+            Self::TruncatedObjectFile { file } => {
+                write!(f, "{} ends before the end of the object file. It may not have been written fully", file)
+            }
+            Self::CorruptObjectFile { file } => {
+                write!(f, "{} is corrupt and cannot be read as an object file", file)
+            }
+            Self::UndefinedSymbol { name, files } => {
+                write!(f, "Symbol {} is imported by {} but no file exports it", name, files.join(", "))
+            }
+            Self::DuplicateSymbol { name, files } => {
+                write!(f, "Symbol {} is exported by more than one file: {}", name, files.join(", "))
+            }
+            Self::InvalidLinkerConfig { file, span, error } => {
+                write!(f, "{}:{}: {}", file, span, error)
+            }
+            Self::SegmentNotInConfig { segment, files } => {
+                write!(f, "Segment {} from {} is not in the linker config", segment, files.join(", "))
+            }
+            Self::SegmentStartTooLow { segment, start, next } => {
+                write!(f, "Segment {} has to start at ${:04X}, but its memory area is already used up to ${:04X}", segment, start, next)
+            }
+            Self::MemoryAreaOverflow { memory, size, used, segments } => {
+                let segments: Vec<String> = segments.iter().map(|(name, size)| format!("{} (${:X} bytes)", name, size)).collect();
+                write!(
+                    f,
+                    "Memory area {} holds ${:X} bytes but ${:X} are needed for {}",
+                    memory, size, used, segments.join(", ")
+                )
+            }
+            Self::RelocationFailed { file, object, span, symbol: Some(symbol), error } => {
+                write!(f, "{}:{} in {}: {} (symbol {})", file, span, object, error, symbol)
+            }
+            Self::RelocationFailed { file, object, span, symbol: None, error } => {
+                write!(f, "{}:{} in {}: {}", file, span, object, error)
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Writes a value in hex with the sign in front, rather than as its two's complement
+fn hex(value: i32) -> String {
+    match value < 0 {
+        true => format!("-${:X}", value.unsigned_abs()),
+        false => format!("${:X}", value),
+    }
+}
@@ -1,7 +1,7 @@
-use std::collections::HashMap;
-
 use serde_derive::{Serialize, Deserialize};
 
+use crate::span::Span;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum HighPrecedenceOp {
     Mul,
@@ -18,13 +18,44 @@ pub enum LowPrecedenceOp {
     And,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// A node of an expression tree. The last field of every node is where it was found in the source
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ExpressionNode {
-    BinOp(BinaryOp, Box<ExpressionNode>, Box<ExpressionNode>),
-    Number(u16),
-    Identifier(String),
-    Parenthesized(Box<ExpressionNode>),
-    ScopedReference(Vec<String>),
+    BinOp(BinaryOp, Box<ExpressionNode>, Box<ExpressionNode>, Span),
+    Number(u16, Span),
+    Identifier(String, Span),
+    Parenthesized(Box<ExpressionNode>, Span),
+    ScopedReference(Vec<String>, Span),
+    Unary(UnaryOp, Box<ExpressionNode>, Span),
+}
+
+impl PartialEq for ExpressionNode {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::BinOp(op, left, right, _), Self::BinOp(other_op, other_left, other_right, _)) => {
+                op == other_op && left == other_left && right == other_right
+            }
+            (Self::Number(n, _), Self::Number(other_n, _)) => n == other_n,
+            (Self::Identifier(ident, _), Self::Identifier(other_ident, _)) => ident == other_ident,
+            (Self::Parenthesized(expr, _), Self::Parenthesized(other_expr, _)) => expr == other_expr,
+            (Self::ScopedReference(refs, _), Self::ScopedReference(other_refs, _)) => refs == other_refs,
+            (Self::Unary(op, expr, _), Self::Unary(other_op, other_expr, _)) => op == other_op && expr == other_expr,
+            _ => false,
+        }
+    }
+}
+
+impl ExpressionNode {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::BinOp(_, _, _, span)
+            | Self::Number(_, span)
+            | Self::Identifier(_, span)
+            | Self::Parenthesized(_, span)
+            | Self::ScopedReference(_, span)
+            | Self::Unary(_, _, span) => *span,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -39,32 +70,20 @@ pub enum BinaryOp {
     ShiftRight,
 }
 
-pub fn evaluate_expression(node: &ExpressionNode, constant_map: &HashMap<String, u16>) -> u16 {
-    match node {
-        ExpressionNode::BinOp(op, left, right) => {
-            let l_val = evaluate_expression(left, constant_map);
-            let r_val = evaluate_expression(right, constant_map);
+/// An operator taking one byte of a 16-bit value, written before the expression it applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnaryOp {
+    /// `<`, the low byte
+    LowByte,
+    /// `>`, the high byte
+    HighByte,
+}
 
-            match op {
-                BinaryOp::Add => l_val + r_val,
-                BinaryOp::Subtract => l_val - r_val,
-                BinaryOp::Multiply => l_val * r_val,
-                BinaryOp::Divide => l_val / r_val,
-                BinaryOp::And => l_val & r_val,
-                BinaryOp::Or => l_val | r_val,
-                BinaryOp::ShiftLeft => l_val << r_val,
-                BinaryOp::ShiftRight => l_val >> r_val
-            }
-        },
-        ExpressionNode::Number(n) => *n,
-        ExpressionNode::Identifier(ident) => {
-            constant_map.get(ident).cloned().unwrap()
-        },
-        ExpressionNode::Parenthesized(expr) => {
-            evaluate_expression(&expr, constant_map)
-        }
-        ExpressionNode::ScopedReference(_scoped_ref) => {
-            0
+impl UnaryOp {
+    pub fn apply(&self, value: u16) -> u16 {
+        match self {
+            UnaryOp::LowByte => value & 0xFF,
+            UnaryOp::HighByte => value >> 8,
         }
     }
-}
\ No newline at end of file
+}
@@ -1,9 +1,18 @@
 use serde_derive::{Serialize, Deserialize};
 
-use crate::{operand::Operand, mnemonic::Mnemonic};
+use crate::{operand::Operand, mnemonic::Mnemonic, address::AddressSize, span::Span};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Instruction {
     pub mnemonic: Mnemonic,
     pub operand: Option<Operand>,
-}
\ No newline at end of file
+    /// The address size forced with an operand prefix or mnemonic suffix, if any
+    pub address_size: Option<AddressSize>,
+    pub span: Span,
+}
+
+impl PartialEq for Instruction {
+    fn eq(&self, other: &Self) -> bool {
+        self.mnemonic == other.mnemonic && self.operand == other.operand && self.address_size == other.address_size
+    }
+}
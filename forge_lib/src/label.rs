@@ -1,7 +1,46 @@
 use serde_derive::{Serialize, Deserialize};
 
+use crate::span::Span;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LabelMetaData {
+    /// The index of the segment the label is in, in the order the segments are first used
+    pub segment: usize,
+    /// The offset from the start of the segment
     pub offset: u16,
-    pub is_local: bool
-}
\ No newline at end of file
+    pub is_local: bool,
+    /// Where the label was defined
+    pub span: Span,
+}
+
+/// Returns the name a local label is stored under. The same local name can be reused under each
+/// global label, so it is stored along with the global label it follows
+pub fn local_label_name(scope: &str, name: &str) -> String {
+    format!("{}@{}", scope, name)
+}
+
+/// Returns the name the anonymous label with the given index (counting from 0 in the order they are
+/// defined) is stored under in the label map. Names in the source cannot contain a colon, so it
+/// never clashes with them
+pub fn anonymous_label_name(index: u16) -> String {
+    format!(":{}", index)
+}
+
+/// Returns the index of the anonymous label a reference points to. `defined` is the number of
+/// anonymous labels up to and including the line with the reference, and `total` is the number in
+/// the whole file. Returns None if there is no such label
+pub fn anonymous_label_index(defined: u16, total: u16, offset: i8) -> Option<u16> {
+    let index = match offset {
+        0 => return None,
+        offset if offset < 0 => defined.checked_sub(offset.unsigned_abs() as u16)?,
+        offset => defined + offset as u16 - 1,
+    };
+
+    (index < total).then_some(index)
+}
+
+/// Returns how a reference to an anonymous label is written in the source (e.g. `:--` for -2)
+pub fn anonymous_reference_name(offset: i8) -> String {
+    let sign = if offset < 0 { "-" } else { "+" };
+    format!(":{}", sign.repeat(offset.unsigned_abs() as usize))
+}
@@ -1,13 +1,14 @@
 use std::{
     fs::File,
     io::{Read, Write},
-    path::PathBuf,
+    path::Path,
 };
 
 use error::ForgeError;
 use object::OutFile;
 
 pub mod address;
+pub mod cpu;
 pub mod directive;
 pub mod error;
 pub mod expression;
@@ -17,26 +18,25 @@ pub mod line;
 pub mod mnemonic;
 pub mod object;
 pub mod operand;
+pub mod span;
 pub mod linker;
 
-pub fn write_object_file_to_contents(data: OutFile, output_file: &PathBuf) {
-    let encoded: Vec<u8> = bincode::serialize(&data).unwrap();
+pub fn write_object_file_to_contents(data: OutFile, output_file: &Path) {
+    let encoded: Vec<u8> = data.to_bytes();
 
     let mut file = File::create(output_file).unwrap();
     file.write_all(&encoded).unwrap();
 }
 
-pub fn get_file_contents(input_file: &PathBuf) -> Result<OutFile, ForgeError> {
-    let mut file = File::open(input_file).map_err(|_| ForgeError::NoSuchFileOrDir {
-        file: input_file.to_string_lossy().into_owned(),
-    })?;
+pub fn get_file_contents(input_file: &Path) -> Result<OutFile, ForgeError> {
+    let file_name = input_file.to_string_lossy().into_owned();
+    let mut file = File::open(input_file).map_err(|error| ForgeError::read_failed(file_name.clone(), error))?;
     let mut encoded = Vec::new();
-    file.read_to_end(&mut encoded).unwrap();
+    file.read_to_end(&mut encoded).map_err(|error| ForgeError::read_failed(file_name.clone(), error))?;
 
-    let data: OutFile = bincode::deserialize(&encoded).unwrap();
-    Ok(data)
+    OutFile::from_bytes(&encoded, &file_name)
 }
 
-pub fn scoped_ref_to_string(val: &Vec<String>) -> String {
+pub fn scoped_ref_to_string(val: &[String]) -> String {
     val.join("::")
 }
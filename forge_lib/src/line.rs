@@ -1,53 +1,107 @@
 use serde_derive::{Serialize, Deserialize};
 
-use crate::{instruction::Instruction, directive::Directive, operand::Operand, address::AddressMode};
+use crate::{instruction::Instruction, directive::Directive, operand::Operand, address::AddressModeGeneric, cpu::CpuState, mnemonic::find_opcode, span::Span};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Line {
     pub comment: Option<String>,
     pub constant: Option<(String, u16)>,
+    /// The label at the start of the line. A label on a line with no statements refers to the
+    /// address of the next statement in the file
     pub label: Option<Labels>,
-    pub main_component: Option<MainComponent>,
+    /// The instructions and directives on the line in order. There can be several separated by
+    /// colons (e.g. `CLC : ADC #$01`)
+    pub main_components: Vec<MainComponent>,
     pub newlines: u32,
+    /// The span of the line, not including the newlines after it
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+impl PartialEq for Line {
+    fn eq(&self, other: &Self) -> bool {
+        self.comment == other.comment
+            && self.constant == other.constant
+            && self.label == other.label
+            && self.main_components == other.main_components
+            && self.newlines == other.newlines
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MainComponent {
     Instruction(Instruction),
-    Directive(Directive)
+    Directive(Directive, Span)
+}
+
+impl PartialEq for MainComponent {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Instruction(left), Self::Instruction(right)) => left == right,
+            (Self::Directive(left, _), Self::Directive(right, _)) => left == right,
+            _ => false,
+        }
+    }
+}
+
+impl Line {
+    /// Returns the span of the label at the start of the line, including the colon
+    pub fn label_span(&self) -> Option<Span> {
+        let length = match self.label.as_ref()? {
+            Labels::Label(label) => label.chars().count() + 1,
+            Labels::LocalLabel(label) => label.chars().count() + 2,
+            Labels::Anonymous => 1,
+        };
+
+        Some(Span { length: length as u32, ..self.span })
+    }
+}
+
+impl MainComponent {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Instruction(instruction) => instruction.span,
+            Self::Directive(_, span) => *span,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Labels {
     Label(String),
     LocalLabel(String),
+    /// A bare `:`, which is referred to by how many anonymous labels away it is (e.g. `:-` or `:++`)
+    Anonymous,
 }
 
 impl Instruction {
-    pub fn size(&self) -> u8 {
-        // The Mnemonic will always take 1 byte
-        let mut size = 1;
-
-        // Now it is time to get what kind of addressing we have
-        match &self.operand {
-            Some(operand) => match operand {
-                Operand::AddressMode(address_mode) => match address_mode {
-                    AddressMode::ZeroPage(_)
-                    | AddressMode::ZeroPageX(_)
-                    | AddressMode::ZeroPageY(_)
-                    | AddressMode::Immediate(_)
-                    | AddressMode::IndexedIndirectX(_)
-                    | AddressMode::IndirectIndexY(_) => {
-                        size += 1;
-                    }
-                    // For now, the value of an expression or constant will always be assume to be in absolute addressing mode
-                    _ => size += 2,
-                },
-                _ => size += 2,
-            },
-            None => {}
+    /// Returns the number of bytes the instruction assembles to. The CPU state is needed because
+    /// the size of an immediate operand depends on the register widths on the 65816
+    pub fn size(&self, state: &CpuState) -> u8 {
+        // An instruction that cannot be assembled is reported later, so its operand is sized as written
+        let address_mode = self.address_mode();
+        let address_mode = find_opcode(self.mnemonic, address_mode, state.cpu)
+            .map(|opcode| opcode.address_mode)
+            .unwrap_or(address_mode);
+
+        match address_mode {
+            AddressModeGeneric::Immediate => 1 + state.immediate_size(self.mnemonic),
+            address_mode => 1 + address_mode.operand_size(),
         }
+    }
 
-        size
+    /// Returns the kind of addressing the operand is written with. A name or expression is taken to be
+    /// a 16-bit address, since only constants defined before the instruction have been replaced by
+    /// their values. The opcode used may have a different address mode if this one does not exist
+    /// for the mnemonic, such as relative addressing for a branch
+    pub fn address_mode(&self) -> AddressModeGeneric {
+        let address_mode = match &self.operand {
+            Some(Operand::AddressMode(address_mode)) => address_mode.generic(),
+            Some(_) => AddressModeGeneric::Absolute,
+            None => AddressModeGeneric::Implied,
+        };
+
+        self.address_size
+            .and_then(|size| size.apply(address_mode))
+            .unwrap_or(address_mode)
     }
-}
\ No newline at end of file
+}
@@ -1,15 +1,689 @@
-use nom::{bytes::complete::take_while1, combinator::map_res, IResult};
+use std::{collections::HashMap, fmt, fs, path::Path};
 
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_till, take_while1},
+    character::complete::{char, multispace1},
+    combinator::{cut, eof, opt, recognize, value},
+    error::{context, ContextError, ErrorKind, ParseError},
+    multi::many0,
+    sequence::{pair, preceded, terminated},
+    IResult, Offset,
+};
+
+use crate::{error::ForgeError, span::Span};
+
+/// The highest address a memory area can reach
+const ADDRESS_LIMIT: u32 = 0x100_0000;
+
+/// Whether a memory area can be written to at run time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryKind {
+    ReadOnly,
+    ReadWrite,
+}
+
+/// What a segment holds, which decides whether its bytes are written to the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentKind {
+    ReadOnly,
+    ReadWrite,
+    /// Uninitialised memory. Only its size is used
+    Bss,
+    /// Uninitialised memory in the zero page
+    ZeroPage,
+}
+
+/// A block of memory in the `MEMORY` section that segments are placed into
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryArea {
+    pub name: String,
+    pub start: u32,
+    pub size: u32,
+    pub kind: MemoryKind,
+    /// Whether the unused space at the end of the area is written to the output
+    pub fill: bool,
+    /// The byte unused space is filled with
+    pub fill_value: u8,
+    /// Whether symbols for the start and size of the area are defined
+    pub define: bool,
+    pub span: Span,
+}
+
+/// Where a segment from the `SEGMENTS` section is placed
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentConfig {
+    pub name: String,
+    /// The memory area the segment is stored in
+    pub load: String,
+    /// The memory area the segment is copied to before it runs, if it is not the load area
+    pub run: Option<String>,
+    pub kind: SegmentKind,
+    pub align: Option<u32>,
+    /// A fixed address for the segment in its memory area
+    pub start: Option<u32>,
+    /// A fixed address for the segment, given from the start of its memory area. Used over `start`
+    pub offset: Option<u32>,
+    /// The byte gaps before the segment are filled with. The memory area's value is used if not set
+    pub fill_value: Option<u8>,
+    /// Whether symbols for the start and size of the segment are defined
+    pub define: bool,
+    /// Whether the segment can be missing from every object file without a warning
+    pub optional: bool,
+    pub span: Span,
+}
+
+/// A linker config in the style of ld65. Memory areas and segments are kept in the order they are
+/// written, which is the order segments are placed in
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkerConfig {
+    pub memory: Vec<MemoryArea>,
+    pub segments: Vec<SegmentConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    Expected { expected: &'static str, found: String },
+    UnknownSection { name: String },
+    UnknownAttribute { attribute: String, section: &'static str },
+    DuplicateAttribute { attribute: String },
+    MissingAttribute { attribute: &'static str, name: String },
+    InvalidValue { attribute: &'static str, value: String, expected: &'static str },
+    DuplicateName { name: String },
+    UndefinedMemoryArea { segment: String, memory: String },
+    MemoryAreaOutOfRange { name: String, end: u32 },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Expected { expected, found } => {
+                write!(f, "Expected {} but found {}", expected, found)
+            }
+            Self::UnknownSection { name } => {
+                write!(f, "Unknown section {}. Only MEMORY and SEGMENTS are supported", name)
+            }
+            Self::UnknownAttribute { attribute, section } => {
+                write!(f, "{} is not an attribute of {}", attribute, section)
+            }
+            Self::DuplicateAttribute { attribute } => {
+                write!(f, "{} is given more than once", attribute)
+            }
+            Self::MissingAttribute { attribute, name } => {
+                write!(f, "{} is missing {}", name, attribute)
+            }
+            Self::InvalidValue { attribute, value, expected } => {
+                write!(f, "{} is not a valid {}. Expected {}", value, attribute, expected)
+            }
+            Self::DuplicateName { name } => {
+                write!(f, "{} is defined more than once", name)
+            }
+            Self::UndefinedMemoryArea { segment, memory } => {
+                write!(f, "Segment {} uses memory area {}, which is not in MEMORY", segment, memory)
+            }
+            Self::MemoryAreaOutOfRange { name, end } => {
+                write!(f, "Memory area {} ends at ${:X}, past the end of memory at ${:X}", name, end, ADDRESS_LIMIT - 1)
+            }
+        }
+    }
+}
+
+impl LinkerConfig {
+    /// Reads and parses a linker config, reporting every problem found in it
+    pub fn load(path: &Path) -> Result<Self, Vec<ForgeError>> {
+        let file = path.to_string_lossy().into_owned();
+        let source = fs::read_to_string(path).map_err(|error| vec![ForgeError::read_failed(file.clone(), error)])?;
+
+        Self::parse(&source).map_err(|errors| {
+            errors
+                .into_iter()
+                .map(|(error, span)| ForgeError::InvalidLinkerConfig { file: file.clone(), span, error })
+                .collect()
+        })
+    }
+
+    /// Parses a linker config. A syntax error stops parsing, but every problem with the memory
+    /// areas and segments is reported
+    pub fn parse(source: &str) -> Result<Self, Vec<(ConfigError, Span)>> {
+        let sections = match sections(source) {
+            Ok((_, sections)) => sections,
+            Err(nom::Err::Error(error) | nom::Err::Failure(error)) => {
+                let found = match error.input.chars().next() {
+                    None => String::from("the end of the file"),
+                    Some(_) => format!("'{}'", next_token(error.input)),
+                };
+                let error_span = span(source, next_token(error.input));
+                return Err(vec![(ConfigError::Expected { expected: error.expected.unwrap_or("a section"), found }, error_span)]);
+            }
+            Err(nom::Err::Incomplete(_)) => unreachable!("Only complete parsers are used"),
+        };
+
+        let mut errors = Vec::new();
+        let mut memory_items = Vec::new();
+        let mut segment_items = Vec::new();
+        for section in sections {
+            match section.name {
+                "MEMORY" => memory_items.extend(section.items),
+                "SEGMENTS" => segment_items.extend(section.items),
+                name => errors.push((ConfigError::UnknownSection { name: name.to_string() }, span(source, name))),
+            }
+        }
+
+        let mut config = Self::default();
+        for item in &memory_items {
+            let mut attributes = Attributes::new(source, item, "MEMORY", MEMORY_ATTRIBUTES, &mut errors);
+            let start = attributes.number("start", ADDRESS_LIMIT - 1);
+            let size = attributes.number("size", ADDRESS_LIMIT);
+            let (start, size) = (attributes.required("start", start), attributes.required("size", size));
+            let kind = attributes.choice("type", "ro or rw", &[("ro", MemoryKind::ReadOnly), ("rw", MemoryKind::ReadWrite)]);
+            let fill = attributes.flag("fill");
+            let fill_value = attributes.number("fillval", u8::MAX as u32);
+            let define = attributes.flag("define");
+
+            let (Some(start), Some(size)) = (start, size) else { continue };
+            if start + size > ADDRESS_LIMIT {
+                let error = ConfigError::MemoryAreaOutOfRange { name: item.name.to_string(), end: start + size - 1 };
+                errors.push((error, span(source, item.name)));
+            }
+
+            config.memory.push(MemoryArea {
+                name: item.name.to_string(),
+                start,
+                size,
+                kind: kind.unwrap_or(MemoryKind::ReadWrite),
+                fill: fill.unwrap_or(false),
+                fill_value: fill_value.unwrap_or(0) as u8,
+                define: define.unwrap_or(false),
+                span: span(source, item.name),
+            });
+        }
+
+        for item in &segment_items {
+            let mut attributes = Attributes::new(source, item, "SEGMENTS", SEGMENT_ATTRIBUTES, &mut errors);
+            let load = attributes.memory_area("load", &memory_items);
+            let load = attributes.required("load", load);
+            let run = attributes.memory_area("run", &memory_items);
+            let kind = attributes.choice("type", "ro, rw, bss or zp", &[
+                ("ro", SegmentKind::ReadOnly),
+                ("rw", SegmentKind::ReadWrite),
+                ("bss", SegmentKind::Bss),
+                ("zp", SegmentKind::ZeroPage),
+            ]);
+            let align = attributes.number("align", ADDRESS_LIMIT);
+            let start = attributes.number("start", ADDRESS_LIMIT - 1);
+            let offset = attributes.number("offset", ADDRESS_LIMIT - 1);
+            let fill_value = attributes.number("fillval", u8::MAX as u32);
+            let define = attributes.flag("define");
+            let optional = attributes.flag("optional");
+
+            if align == Some(0) {
+                attributes.invalid("align", "a number from 1 to $1000000");
+            }
+
+            let Some(load) = load else { continue };
+            config.segments.push(SegmentConfig {
+                name: item.name.to_string(),
+                load,
+                run,
+                kind: kind.unwrap_or(SegmentKind::ReadOnly),
+                align,
+                start,
+                offset,
+                fill_value: fill_value.map(|value| value as u8),
+                define: define.unwrap_or(false),
+                optional: optional.unwrap_or(false),
+                span: span(source, item.name),
+            });
+        }
+
+        for names in [&memory_items, &segment_items] {
+            let mut seen = HashMap::new();
+            for item in names.iter() {
+                if seen.insert(item.name, ()).is_some() {
+                    errors.push((ConfigError::DuplicateName { name: item.name.to_string() }, span(source, item.name)));
+                }
+            }
+        }
+
+        match errors.is_empty() {
+            true => Ok(config),
+            false => {
+                errors.sort_by_key(|(_, span)| (span.line, span.column));
+                Err(errors)
+            }
+        }
+    }
+
+    pub fn memory_area(&self, name: &str) -> Option<&MemoryArea> {
+        self.memory.iter().find(|memory| memory.name == name)
+    }
+
+    pub fn segment(&self, name: &str) -> Option<&SegmentConfig> {
+        self.segments.iter().find(|segment| segment.name == name)
+    }
+}
+
+const MEMORY_ATTRIBUTES: &[&str] = &["start", "size", "type", "fill", "fillval", "define"];
+const SEGMENT_ATTRIBUTES: &[&str] = &["load", "run", "type", "align", "start", "offset", "fillval", "define", "optional"];
+
+/// A `key = value` pair. The value is kept as written and checked once the key is known
 #[derive(Debug, PartialEq)]
-pub struct Property {
-    key: String,
-    value: String,
+struct Property<'a> {
+    key: &'a str,
+    value: &'a str,
 }
 
+/// A named memory area or segment with its attributes
 #[derive(Debug, PartialEq)]
-pub struct SectionItem {
-    name: String,
-    properties: Vec<Property>,
+struct SectionItem<'a> {
+    name: &'a str,
+    properties: Vec<Property<'a>>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Section<'a> {
+    name: &'a str,
+    items: Vec<SectionItem<'a>>,
+}
+
+/// The attributes of one memory area or segment. Each is looked up by the value it should hold,
+/// and any that cannot be read are reported
+struct Attributes<'a, 's> {
+    source: &'s str,
+    item: &'a SectionItem<'s>,
+    properties: HashMap<&'s str, &'a Property<'s>>,
+    errors: &'a mut Vec<(ConfigError, Span)>,
+}
+
+impl<'a, 's> Attributes<'a, 's> {
+    /// Reports attributes the section does not allow and attributes given more than once
+    fn new(
+        source: &'s str,
+        item: &'a SectionItem<'s>,
+        section: &'static str,
+        allowed: &[&str],
+        errors: &'a mut Vec<(ConfigError, Span)>,
+    ) -> Self {
+        let mut properties = HashMap::new();
+        for property in &item.properties {
+            if !allowed.contains(&property.key) {
+                let error = ConfigError::UnknownAttribute { attribute: property.key.to_string(), section };
+                errors.push((error, span(source, property.key)));
+            } else if properties.insert(property.key, property).is_some() {
+                errors.push((ConfigError::DuplicateAttribute { attribute: property.key.to_string() }, span(source, property.key)));
+            }
+        }
+
+        Self { source, item, properties, errors }
+    }
+
+    /// Reports the attribute's value as invalid
+    fn invalid(&mut self, key: &'static str, expected: &'static str) {
+        if let Some(property) = self.properties.get(key) {
+            let error = ConfigError::InvalidValue { attribute: key, value: property.value.to_string(), expected };
+            self.errors.push((error, span(self.source, property.value)));
+        }
+    }
+
+    /// Reports the attribute as missing if it has no value
+    fn required<T>(&mut self, key: &'static str, value: Option<T>) -> Option<T> {
+        if value.is_none() && !self.properties.contains_key(key) {
+            let error = ConfigError::MissingAttribute { attribute: key, name: self.item.name.to_string() };
+            self.errors.push((error, span(self.source, self.item.name)));
+        }
+
+        value
+    }
+
+    fn number(&mut self, key: &'static str, max: u32) -> Option<u32> {
+        let property = self.properties.get(key)?;
+        match parse_number(property.value).filter(|number| *number <= max) {
+            Some(number) => Some(number),
+            None => {
+                let expected = match max {
+                    0xFF => "a number from 0 to $FF",
+                    0xFF_FFFF => "a number from 0 to $FFFFFF",
+                    _ => "a number from 0 to $1000000",
+                };
+                self.invalid(key, expected);
+                None
+            }
+        }
+    }
+
+    fn flag(&mut self, key: &'static str) -> Option<bool> {
+        self.choice(key, "yes or no", &[("yes", true), ("no", false)])
+    }
+
+    fn choice<T: Copy>(&mut self, key: &'static str, expected: &'static str, options: &[(&str, T)]) -> Option<T> {
+        let property = self.properties.get(key)?;
+        match options.iter().find(|(name, _)| *name == property.value) {
+            Some((_, value)) => Some(*value),
+            None => {
+                self.invalid(key, expected);
+                None
+            }
+        }
+    }
+
+    /// Reads the name of a memory area. Areas with errors of their own still count as defined
+    fn memory_area(&mut self, key: &'static str, memory: &[SectionItem]) -> Option<String> {
+        let property = self.properties.get(key)?;
+        match memory.iter().any(|memory| memory.name == property.value) {
+            true => Some(property.value.to_string()),
+            false => {
+                let error = ConfigError::UndefinedMemoryArea { segment: self.item.name.to_string(), memory: property.value.to_string() };
+                self.errors.push((error, span(self.source, property.value)));
+                None
+            }
+        }
+    }
+}
+
+/// Reads `$` hex, `%` binary and decimal numbers
+fn parse_number(text: &str) -> Option<u32> {
+    match text.as_bytes().first()? {
+        b'$' => u32::from_str_radix(&text[1..], 16).ok(),
+        b'%' => u32::from_str_radix(&text[1..], 2).ok(),
+        _ => text.parse().ok(),
+    }
+}
+
+/// Where a slice of the source is. The slice has to come from the source
+fn span(source: &str, text: &str) -> Span {
+    let before = &source[..source.offset(text)];
+    let line = before.matches('\n').count() + 1;
+    let column = before[before.rfind('\n').map_or(0, |newline| newline + 1)..].chars().count() + 1;
+
+    Span::new(0, line as u32, column as u32, text.chars().count() as u32)
 }
 
-type Section = Vec<SectionItem>;
+/// The name or single character at the start of the input, used to show what was found instead
+/// of what was expected
+fn next_token(input: &str) -> &str {
+    match word(input) {
+        Ok((_, token)) => token,
+        Err(_) => &input[..input.chars().next().map_or(0, char::len_utf8)],
+    }
+}
+
+/// The error of the config parsers, which keeps the outermost thing that was expected
+#[derive(Debug)]
+struct SyntaxError<'a> {
+    input: &'a str,
+    expected: Option<&'static str>,
+}
+
+impl<'a> ParseError<&'a str> for SyntaxError<'a> {
+    fn from_error_kind(input: &'a str, _kind: ErrorKind) -> Self {
+        Self { input, expected: None }
+    }
+
+    fn append(_input: &'a str, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<'a> ContextError<&'a str> for SyntaxError<'a> {
+    fn add_context(_input: &'a str, context: &'static str, mut other: Self) -> Self {
+        other.expected.get_or_insert(context);
+        other
+    }
+}
+
+type ConfigResult<'a, T> = IResult<&'a str, T, SyntaxError<'a>>;
+
+/// Whitespace along with `//` and `#` comments
+fn ignored<'a>(input: &'a str) -> ConfigResult<'a, ()> {
+    let comment = recognize(pair(alt((tag("//"), tag("#"))), take_till(|c| c == '\n')));
+    value((), many0(alt((multispace1, comment))))(input)
+}
+
+/// A name or a value. Numbers are read with their `$` or `%` prefix so they can be checked later
+fn word<'a>(input: &'a str) -> ConfigResult<'a, &'a str> {
+    take_while1(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '%')(input)
+}
+
+/// Runs the parser after any whitespace and comments
+fn token<'a, O>(parser: impl FnMut(&'a str) -> ConfigResult<'a, O>) -> impl FnMut(&'a str) -> ConfigResult<'a, O> {
+    preceded(ignored, parser)
+}
+
+fn property<'a>(input: &'a str) -> ConfigResult<'a, Property<'a>> {
+    let (input, key) = token(word)(input)?;
+    let (input, _) = cut(token(context("'='", char('='))))(input)?;
+    let (input, value) = cut(token(context("a value", word)))(input)?;
+
+    Ok((input, Property { key, value }))
+}
+
+/// `NAME: key = value, key = value;`. The commas are optional
+fn section_item<'a>(input: &'a str) -> ConfigResult<'a, SectionItem<'a>> {
+    let (input, name) = token(word)(input)?;
+    let (input, _) = cut(token(context("':'", char(':'))))(input)?;
+    let (input, properties) = many0(terminated(property, opt(token(char(',')))))(input)?;
+    let (input, _) = cut(token(context("an attribute or ';'", char(';'))))(input)?;
+
+    Ok((input, SectionItem { name, properties }))
+}
+
+fn section<'a>(input: &'a str) -> ConfigResult<'a, Section<'a>> {
+    let (input, name) = token(word)(input)?;
+    let (input, _) = cut(token(context("'{'", char('{'))))(input)?;
+    let (input, items) = many0(section_item)(input)?;
+    let (input, _) = cut(token(context("a name or '}'", char('}'))))(input)?;
+
+    Ok((input, Section { name, items }))
+}
+
+fn sections<'a>(input: &'a str) -> ConfigResult<'a, Vec<Section<'a>>> {
+    terminated(many0(section), token(context("a section", eof)))(input)
+}
+
+#[cfg(test)]
+mod linker_tests {
+    use super::{ConfigError, LinkerConfig, MemoryArea, MemoryKind, SegmentConfig, SegmentKind};
+
+    /// Parses a config that is expected to have errors, giving each with its line and column
+    fn errors(source: &str) -> Vec<(ConfigError, u32, u32)> {
+        LinkerConfig::parse(source).unwrap_err().into_iter().map(|(error, span)| (error, span.line, span.column)).collect()
+    }
+
+    fn memory(source: &str) -> MemoryArea {
+        LinkerConfig::parse(source).unwrap().memory.remove(0)
+    }
+
+    fn segment(source: &str) -> SegmentConfig {
+        LinkerConfig::parse(source).unwrap().segments.remove(0)
+    }
+
+    #[test]
+    fn test_parse_numbers() {
+        let area = memory("MEMORY { M: start = $8000, size = %10000; }");
+        assert_eq!((area.start, area.size), (0x8000, 16));
+
+        let area = memory("MEMORY { M: start = 512, size = 1024; }");
+        assert_eq!((area.start, area.size), (512, 1024));
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let config = LinkerConfig::parse(
+            "// Line comment\n# Hash comment\nMEMORY { // after a brace\n  M: start = 0, # between attributes\n  size = 1; }\n// at the end",
+        )
+        .unwrap();
+
+        assert_eq!(config.memory.len(), 1);
+        assert_eq!(config.memory[0].size, 1);
+    }
+
+    #[test]
+    fn test_parse_memory_attributes() {
+        let area = memory("MEMORY {\n  PRG: start = $C000, size = $4000, type = ro, fill = yes, fillval = $FF, define = yes;\n}");
+
+        assert_eq!(area.name, "PRG");
+        assert_eq!(area.start, 0xC000);
+        assert_eq!(area.size, 0x4000);
+        assert_eq!(area.kind, MemoryKind::ReadOnly);
+        assert!(area.fill);
+        assert_eq!(area.fill_value, 0xFF);
+        assert!(area.define);
+        assert_eq!((area.span.line, area.span.column, area.span.length), (2, 3, 3));
+    }
+
+    #[test]
+    fn test_parse_memory_defaults() {
+        let area = memory("MEMORY { RAM: start = $0300, size = $0400; }");
+
+        assert_eq!(area.kind, MemoryKind::ReadWrite);
+        assert!(!area.fill);
+        assert_eq!(area.fill_value, 0);
+        assert!(!area.define);
+    }
+
+    #[test]
+    fn test_parse_segment_attributes() {
+        let segment = segment(
+            "MEMORY { ROM: start = $8000, size = $100; RAM: start = $200, size = $100; }\nSEGMENTS {\n  DATA: load = ROM, run = RAM, type = rw, align = $10, start = $8010, offset = $20, fillval = $EA, define = yes, optional = yes;\n}",
+        );
+
+        assert_eq!(segment.name, "DATA");
+        assert_eq!(segment.load, "ROM");
+        assert_eq!(segment.run.as_deref(), Some("RAM"));
+        assert_eq!(segment.kind, SegmentKind::ReadWrite);
+        assert_eq!(segment.align, Some(0x10));
+        assert_eq!(segment.start, Some(0x8010));
+        assert_eq!(segment.offset, Some(0x20));
+        assert_eq!(segment.fill_value, Some(0xEA));
+        assert!(segment.define);
+        assert!(segment.optional);
+    }
+
+    #[test]
+    fn test_parse_segment_defaults() {
+        let segment = segment("SEGMENTS { CODE: load = ROM; }\nMEMORY { ROM: start = $8000, size = $100; }");
+
+        assert_eq!(segment.run, None);
+        assert_eq!(segment.kind, SegmentKind::ReadOnly);
+        assert_eq!((segment.align, segment.start, segment.offset, segment.fill_value), (None, None, None, None));
+        assert!(!segment.define);
+        assert!(!segment.optional);
+    }
+
+    #[test]
+    fn test_parse_segment_kinds() {
+        for (name, kind) in [("ro", SegmentKind::ReadOnly), ("rw", SegmentKind::ReadWrite), ("bss", SegmentKind::Bss), ("zp", SegmentKind::ZeroPage)] {
+            let source = format!("MEMORY {{ M: start = 0, size = 1; }} SEGMENTS {{ S: load = M, type = {}; }}", name);
+            assert_eq!(segment(&source).kind, kind);
+        }
+    }
+
+    #[test]
+    fn test_parse_example_config() {
+        let config = LinkerConfig::parse(include_str!("../../resources/test/linker.ld")).unwrap();
+
+        let names: Vec<&str> = config.memory.iter().map(|memory| memory.name.as_str()).collect();
+        assert_eq!(names, ["ZP", "RAM", "HEADER", "PRG0", "CHR0a", "CHR0b"]);
+        let names: Vec<&str> = config.segments.iter().map(|segment| segment.name.as_str()).collect();
+        assert_eq!(names, ["ZEROPAGE", "BSS", "INES", "CODE", "VECTOR", "CHR0a", "CHR0b"]);
+
+        let prg = config.memory_area("PRG0").unwrap();
+        assert_eq!((prg.start, prg.size, prg.kind, prg.fill), (0x8000, 0x4000, MemoryKind::ReadOnly, true));
+        assert_eq!(config.segment("ZEROPAGE").unwrap().kind, SegmentKind::ZeroPage);
+        assert_eq!(config.segment("INES").unwrap().align, Some(0x10));
+        assert_eq!(config.segment("VECTOR").unwrap().start, Some(0xBFFA));
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        assert_eq!(errors("MEMORY { RAM start = 1; }"), vec![(
+            ConfigError::Expected { expected: "':'", found: String::from("'start'") },
+            1,
+            14
+        )]);
+        assert_eq!(errors("MEMORY {\n  RAM: start = ;\n}"), vec![(
+            ConfigError::Expected { expected: "a value", found: String::from("';'") },
+            2,
+            16
+        )]);
+        assert_eq!(errors("MEMORY { RAM: start = 1 size = 2 }"), vec![(
+            ConfigError::Expected { expected: "an attribute or ';'", found: String::from("'}'") },
+            1,
+            34
+        )]);
+        assert_eq!(errors("MEMORY { RAM: start = 1, size = 2; "), vec![(
+            ConfigError::Expected { expected: "a name or '}'", found: String::from("the end of the file") },
+            1,
+            36
+        )]);
+        assert_eq!(errors("MEMORY ["), vec![(ConfigError::Expected { expected: "'{'", found: String::from("'['") }, 1, 8)]);
+        assert_eq!(errors("MEMORY { }\n@"), vec![(ConfigError::Expected { expected: "a section", found: String::from("'@'") }, 2, 1)]);
+    }
+
+    #[test]
+    fn test_attribute_errors() {
+        assert_eq!(errors("FILES { }"), vec![(ConfigError::UnknownSection { name: String::from("FILES") }, 1, 1)]);
+        assert_eq!(errors("MEMORY { M: start = 0, size = 1, load = M; }"), vec![(
+            ConfigError::UnknownAttribute { attribute: String::from("load"), section: "MEMORY" },
+            1,
+            34
+        )]);
+        assert_eq!(errors("MEMORY { M: start = 0, size = 1, start = 2; }"), vec![(
+            ConfigError::DuplicateAttribute { attribute: String::from("start") },
+            1,
+            34
+        )]);
+        assert_eq!(errors("MEMORY {\n  M: size = 1;\n}"), vec![(
+            ConfigError::MissingAttribute { attribute: "start", name: String::from("M") },
+            2,
+            3
+        )]);
+        assert_eq!(errors("MEMORY { M: start = 0, size = 1; }\nSEGMENTS { S: type = ro; }"), vec![(
+            ConfigError::MissingAttribute { attribute: "load", name: String::from("S") },
+            2,
+            12
+        )]);
+        assert_eq!(errors("MEMORY { M: start = 0, size = 1; M: start = 1, size = 1; }"), vec![(
+            ConfigError::DuplicateName { name: String::from("M") },
+            1,
+            34
+        )]);
+        assert_eq!(errors("SEGMENTS { CODE: load = PRG; }"), vec![(
+            ConfigError::UndefinedMemoryArea { segment: String::from("CODE"), memory: String::from("PRG") },
+            1,
+            25
+        )]);
+        assert_eq!(errors("MEMORY { M: start = $FFFFFF, size = 2; }"), vec![(
+            ConfigError::MemoryAreaOutOfRange { name: String::from("M"), end: 0x1000000 },
+            1,
+            10
+        )]);
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let invalid = |attribute, value: &str, expected| ConfigError::InvalidValue { attribute, value: value.to_string(), expected };
+
+        assert_eq!(errors("MEMORY { M: start = RAM, size = $1000001, type = rx, fill = maybe, fillval = 256; }"), vec![
+            (invalid("start", "RAM", "a number from 0 to $FFFFFF"), 1, 21),
+            (invalid("size", "$1000001", "a number from 0 to $1000000"), 1, 33),
+            (invalid("type", "rx", "ro or rw"), 1, 50),
+            (invalid("fill", "maybe", "yes or no"), 1, 61),
+            (invalid("fillval", "256", "a number from 0 to $FF"), 1, 78),
+        ]);
+        assert_eq!(errors("MEMORY { M: start = 0, size = 1; }\nSEGMENTS { S: load = M, type = code, align = 0, optional = 1; }"), vec![
+            (invalid("type", "code", "ro, rw, bss or zp"), 2, 32),
+            (invalid("align", "0", "a number from 1 to $1000000"), 2, 46),
+            (invalid("optional", "1", "yes or no"), 2, 60),
+        ]);
+    }
+
+    #[test]
+    fn test_error_messages() {
+        let error = ConfigError::MemoryAreaOutOfRange { name: String::from("M"), end: 0x1000000 };
+        assert_eq!(error.to_string(), "Memory area M ends at $1000000, past the end of memory at $FFFFFF");
+
+        let error = ConfigError::InvalidValue { attribute: "type", value: String::from("rx"), expected: "ro or rw" };
+        assert_eq!(error.to_string(), "rx is not a valid type. Expected ro or rw");
+    }
+}
@@ -4,14 +4,38 @@ use lazy_static::lazy_static;
 use serde_derive::{Serialize, Deserialize};
 use strum_macros::{EnumString, Display};
 
-use crate::address::AddressModeGeneric;
+use crate::{
+    address::AddressModeGeneric,
+    cpu::{Cpu, InstructionSet},
+    error::ForgeError,
+};
 
 lazy_static! {
     static ref MNEMONIC_MAP: HashMap<&'static str, Mnemonic> = {
         let mut m = HashMap::new();
         m.insert("ADC", Mnemonic::ADC);
+        m.insert("ALR", Mnemonic::ALR);
+        m.insert("ANC", Mnemonic::ANC);
         m.insert("AND", Mnemonic::AND);
+        m.insert("ANE", Mnemonic::ANE);
+        m.insert("ARR", Mnemonic::ARR);
         m.insert("ASL", Mnemonic::ASL);
+        m.insert("BBR0", Mnemonic::BBR0);
+        m.insert("BBR1", Mnemonic::BBR1);
+        m.insert("BBR2", Mnemonic::BBR2);
+        m.insert("BBR3", Mnemonic::BBR3);
+        m.insert("BBR4", Mnemonic::BBR4);
+        m.insert("BBR5", Mnemonic::BBR5);
+        m.insert("BBR6", Mnemonic::BBR6);
+        m.insert("BBR7", Mnemonic::BBR7);
+        m.insert("BBS0", Mnemonic::BBS0);
+        m.insert("BBS1", Mnemonic::BBS1);
+        m.insert("BBS2", Mnemonic::BBS2);
+        m.insert("BBS3", Mnemonic::BBS3);
+        m.insert("BBS4", Mnemonic::BBS4);
+        m.insert("BBS5", Mnemonic::BBS5);
+        m.insert("BBS6", Mnemonic::BBS6);
+        m.insert("BBS7", Mnemonic::BBS7);
         m.insert("BCC", Mnemonic::BCC);
         m.insert("BCS", Mnemonic::BCS);
         m.insert("BEQ", Mnemonic::BEQ);
@@ -19,7 +43,9 @@ lazy_static! {
         m.insert("BMI", Mnemonic::BMI);
         m.insert("BNE", Mnemonic::BNE);
         m.insert("BPL", Mnemonic::BPL);
+        m.insert("BRA", Mnemonic::BRA);
         m.insert("BRK", Mnemonic::BRK);
+        m.insert("BRL", Mnemonic::BRL);
         m.insert("BVC", Mnemonic::BVC);
         m.insert("BVS", Mnemonic::BVS);
         m.insert("CLC", Mnemonic::CLC);
@@ -27,44 +53,118 @@ lazy_static! {
         m.insert("CLI", Mnemonic::CLI);
         m.insert("CLV", Mnemonic::CLV);
         m.insert("CMP", Mnemonic::CMP);
+        m.insert("COP", Mnemonic::COP);
         m.insert("CPX", Mnemonic::CPX);
         m.insert("CPY", Mnemonic::CPY);
+        m.insert("DCP", Mnemonic::DCP);
         m.insert("DEC", Mnemonic::DEC);
         m.insert("DEX", Mnemonic::DEX);
         m.insert("DEY", Mnemonic::DEY);
-        m.insert("EQR", Mnemonic::EQR);
+        m.insert("EOR", Mnemonic::EOR);
         m.insert("INC", Mnemonic::INC);
         m.insert("INX", Mnemonic::INX);
         m.insert("INY", Mnemonic::INY);
+        m.insert("ISC", Mnemonic::ISC);
+        m.insert("JAM", Mnemonic::JAM);
+        m.insert("JML", Mnemonic::JML);
         m.insert("JMP", Mnemonic::JMP);
+        m.insert("JSL", Mnemonic::JSL);
         m.insert("JSR", Mnemonic::JSR);
+        m.insert("LAS", Mnemonic::LAS);
+        m.insert("LAX", Mnemonic::LAX);
         m.insert("LDA", Mnemonic::LDA);
         m.insert("LDX", Mnemonic::LDX);
         m.insert("LDY", Mnemonic::LDY);
         m.insert("LSR", Mnemonic::LSR);
+        m.insert("MVN", Mnemonic::MVN);
+        m.insert("MVP", Mnemonic::MVP);
         m.insert("NOP", Mnemonic::NOP);
         m.insert("ORA", Mnemonic::ORA);
+        m.insert("PEA", Mnemonic::PEA);
+        m.insert("PEI", Mnemonic::PEI);
+        m.insert("PER", Mnemonic::PER);
         m.insert("PHA", Mnemonic::PHA);
+        m.insert("PHB", Mnemonic::PHB);
+        m.insert("PHD", Mnemonic::PHD);
+        m.insert("PHK", Mnemonic::PHK);
         m.insert("PHP", Mnemonic::PHP);
+        m.insert("PHX", Mnemonic::PHX);
+        m.insert("PHY", Mnemonic::PHY);
         m.insert("PLA", Mnemonic::PLA);
+        m.insert("PLB", Mnemonic::PLB);
+        m.insert("PLD", Mnemonic::PLD);
         m.insert("PLP", Mnemonic::PLP);
+        m.insert("PLX", Mnemonic::PLX);
+        m.insert("PLY", Mnemonic::PLY);
+        m.insert("REP", Mnemonic::REP);
+        m.insert("RLA", Mnemonic::RLA);
+        m.insert("RMB0", Mnemonic::RMB0);
+        m.insert("RMB1", Mnemonic::RMB1);
+        m.insert("RMB2", Mnemonic::RMB2);
+        m.insert("RMB3", Mnemonic::RMB3);
+        m.insert("RMB4", Mnemonic::RMB4);
+        m.insert("RMB5", Mnemonic::RMB5);
+        m.insert("RMB6", Mnemonic::RMB6);
+        m.insert("RMB7", Mnemonic::RMB7);
         m.insert("ROL", Mnemonic::ROL);
         m.insert("ROR", Mnemonic::ROR);
+        m.insert("RRA", Mnemonic::RRA);
         m.insert("RTI", Mnemonic::RTI);
+        m.insert("RTL", Mnemonic::RTL);
         m.insert("RTS", Mnemonic::RTS);
+        m.insert("SAX", Mnemonic::SAX);
         m.insert("SBC", Mnemonic::SBC);
+        m.insert("SBX", Mnemonic::SBX);
         m.insert("SEC", Mnemonic::SEC);
         m.insert("SED", Mnemonic::SED);
         m.insert("SEI", Mnemonic::SEI);
+        m.insert("SEP", Mnemonic::SEP);
+        m.insert("SHA", Mnemonic::SHA);
+        m.insert("SHX", Mnemonic::SHX);
+        m.insert("SHY", Mnemonic::SHY);
+        m.insert("SLO", Mnemonic::SLO);
+        m.insert("SMB0", Mnemonic::SMB0);
+        m.insert("SMB1", Mnemonic::SMB1);
+        m.insert("SMB2", Mnemonic::SMB2);
+        m.insert("SMB3", Mnemonic::SMB3);
+        m.insert("SMB4", Mnemonic::SMB4);
+        m.insert("SMB5", Mnemonic::SMB5);
+        m.insert("SMB6", Mnemonic::SMB6);
+        m.insert("SMB7", Mnemonic::SMB7);
+        m.insert("SRE", Mnemonic::SRE);
         m.insert("STA", Mnemonic::STA);
+        m.insert("STP", Mnemonic::STP);
         m.insert("STX", Mnemonic::STX);
         m.insert("STY", Mnemonic::STY);
+        m.insert("STZ", Mnemonic::STZ);
+        m.insert("TAS", Mnemonic::TAS);
         m.insert("TAX", Mnemonic::TAX);
         m.insert("TAY", Mnemonic::TAY);
+        m.insert("TCD", Mnemonic::TCD);
+        m.insert("TCS", Mnemonic::TCS);
+        m.insert("TDC", Mnemonic::TDC);
+        m.insert("TRB", Mnemonic::TRB);
+        m.insert("TSB", Mnemonic::TSB);
+        m.insert("TSC", Mnemonic::TSC);
         m.insert("TSX", Mnemonic::TSX);
         m.insert("TXA", Mnemonic::TXA);
         m.insert("TXS", Mnemonic::TXS);
+        m.insert("TXY", Mnemonic::TXY);
         m.insert("TYA", Mnemonic::TYA);
+        m.insert("TYX", Mnemonic::TYX);
+        m.insert("WAI", Mnemonic::WAI);
+        m.insert("WDM", Mnemonic::WDM);
+        m.insert("XBA", Mnemonic::XBA);
+        m.insert("XCE", Mnemonic::XCE);
+        // Other names the undocumented instructions are commonly known by
+        m.insert("AHX", Mnemonic::SHA);
+        m.insert("ASR", Mnemonic::ALR);
+        m.insert("AXS", Mnemonic::SBX);
+        m.insert("DCM", Mnemonic::DCP);
+        m.insert("ISB", Mnemonic::ISC);
+        m.insert("KIL", Mnemonic::JAM);
+        m.insert("XAA", Mnemonic::ANE);
+
         m
     };
 }
@@ -72,8 +172,28 @@ lazy_static! {
 #[derive(Debug, PartialEq, Eq, Clone, Copy, EnumString, Display, Serialize, Deserialize, Hash)]
 pub enum Mnemonic {
     ADC,
+    ALR,
+    ANC,
     AND,
+    ANE,
+    ARR,
     ASL,
+    BBR0,
+    BBR1,
+    BBR2,
+    BBR3,
+    BBR4,
+    BBR5,
+    BBR6,
+    BBR7,
+    BBS0,
+    BBS1,
+    BBS2,
+    BBS3,
+    BBS4,
+    BBS5,
+    BBS6,
+    BBS7,
     BCC,
     BCS,
     BEQ,
@@ -81,7 +201,9 @@ pub enum Mnemonic {
     BMI,
     BNE,
     BPL,
+    BRA,
     BRK,
+    BRL,
     BVC,
     BVS,
     CLC,
@@ -89,44 +211,196 @@ pub enum Mnemonic {
     CLI,
     CLV,
     CMP,
+    COP,
     CPX,
     CPY,
+    DCP,
     DEC,
     DEX,
     DEY,
-    EQR,
+    EOR,
     INC,
     INX,
     INY,
+    ISC,
+    JAM,
+    JML,
     JMP,
+    JSL,
     JSR,
+    LAS,
+    LAX,
     LDA,
     LDX,
     LDY,
     LSR,
+    MVN,
+    MVP,
     NOP,
     ORA,
+    PEA,
+    PEI,
+    PER,
     PHA,
+    PHB,
+    PHD,
+    PHK,
     PHP,
+    PHX,
+    PHY,
     PLA,
+    PLB,
+    PLD,
     PLP,
+    PLX,
+    PLY,
+    REP,
+    RLA,
+    RMB0,
+    RMB1,
+    RMB2,
+    RMB3,
+    RMB4,
+    RMB5,
+    RMB6,
+    RMB7,
     ROL,
     ROR,
+    RRA,
     RTI,
+    RTL,
     RTS,
+    SAX,
     SBC,
+    SBX,
     SEC,
     SED,
     SEI,
+    SEP,
+    SHA,
+    SHX,
+    SHY,
+    SLO,
+    SMB0,
+    SMB1,
+    SMB2,
+    SMB3,
+    SMB4,
+    SMB5,
+    SMB6,
+    SMB7,
+    SRE,
     STA,
+    STP,
     STX,
     STY,
+    STZ,
+    TAS,
     TAX,
     TAY,
+    TCD,
+    TCS,
+    TDC,
+    TRB,
+    TSB,
+    TSC,
     TSX,
     TXA,
     TXS,
-    TYA
+    TXY,
+    TYA,
+    TYX,
+    WAI,
+    WDM,
+    XBA,
+    XCE,
+}
+
+impl Mnemonic {
+    /// Looks up a mnemonic by its upper case name
+    pub fn from_name(name: &str) -> Option<Mnemonic> {
+        MNEMONIC_MAP.get(name).copied()
+    }
+
+    /// Returns the mnemonic the given name was most likely a misspelling of. Only mnemonics the
+    /// CPU supports and that are a single edit away are suggested
+    pub fn suggest(name: &str, cpu: Cpu) -> Option<Mnemonic> {
+        let name = name.to_ascii_uppercase();
+
+        let mut candidates: Vec<(&str, Mnemonic)> = MNEMONIC_MAP
+            .iter()
+            .filter(|(candidate, mnemonic)| edit_distance(&name, candidate) == 1 && mnemonic.is_supported_by(cpu))
+            .map(|(candidate, mnemonic)| (*candidate, *mnemonic))
+            .collect();
+
+        // The map is unordered, so sort to always suggest the same mnemonic
+        candidates.sort_by_key(|(candidate, _)| *candidate);
+        candidates.first().map(|(_, mnemonic)| *mnemonic)
+    }
+
+    /// Returns true if the given CPU has at least one opcode for this mnemonic
+    pub fn is_supported_by(&self, cpu: Cpu) -> bool {
+        OPCODES_TO_BYTES
+            .values()
+            .any(|opcode| opcode.mnemonic == *self && cpu.supports(opcode.instruction_set))
+    }
+
+    /// Returns true if every opcode for this mnemonic is an undocumented NMOS instruction
+    pub fn is_undocumented(&self) -> bool {
+        OPCODES_TO_BYTES
+            .values()
+            .filter(|opcode| opcode.mnemonic == *self)
+            .all(|opcode| opcode.instruction_set == InstructionSet::Undocumented)
+    }
+
+    /// Returns true if this is one of the 65816 block move instructions, which take a source
+    /// and destination bank
+    pub fn is_block_move(&self) -> bool {
+        matches!(self, Mnemonic::MVN | Mnemonic::MVP)
+    }
+
+    /// Returns true for the branches taking an 8-bit offset from the next instruction
+    pub fn is_branch(&self) -> bool {
+        matches!(
+            self,
+            Mnemonic::BCC | Mnemonic::BCS | Mnemonic::BEQ | Mnemonic::BMI |
+            Mnemonic::BNE | Mnemonic::BPL | Mnemonic::BVC | Mnemonic::BVS | Mnemonic::BRA
+        )
+    }
+
+    /// Returns true for the 65816 instructions taking a 16-bit offset from the next instruction
+    pub fn is_long_branch(&self) -> bool {
+        matches!(self, Mnemonic::BRL | Mnemonic::PER)
+    }
+
+    /// Returns true if this is one of the Rockwell branch on bit instructions, which take
+    /// both a zero page address and a branch target
+    pub fn is_bit_branch(&self) -> bool {
+        matches!(
+            self,
+            Mnemonic::BBR0 | Mnemonic::BBR1 | Mnemonic::BBR2 | Mnemonic::BBR3 |
+            Mnemonic::BBR4 | Mnemonic::BBR5 | Mnemonic::BBR6 | Mnemonic::BBR7 |
+            Mnemonic::BBS0 | Mnemonic::BBS1 | Mnemonic::BBS2 | Mnemonic::BBS3 |
+            Mnemonic::BBS4 | Mnemonic::BBS5 | Mnemonic::BBS6 | Mnemonic::BBS7
+        )
+    }
+}
+
+/// The number of single character insertions, deletions or substitutions to turn one string into another
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
 }
 
 impl From<String> for Mnemonic {
@@ -144,20 +418,71 @@ pub struct OpCode {
     pub opcode: u8,
     pub mnemonic: Mnemonic,
     pub len: u8,
-    pub address_mode: AddressModeGeneric
+    pub address_mode: AddressModeGeneric,
+    pub instruction_set: InstructionSet,
 }
 
 impl OpCode {
+    /// Creates an opcode that is part of the documented NMOS 6502 instruction set
     pub fn new(opcode: u8, mnemonic: Mnemonic, len: u8, address_mode: AddressModeGeneric) -> Self {
+        Self::with_set(opcode, mnemonic, len, address_mode, InstructionSet::Nmos)
+    }
+
+    /// Creates an opcode that is only available on CPUs supporting the given instruction set
+    pub fn with_set(opcode: u8, mnemonic: Mnemonic, len: u8, address_mode: AddressModeGeneric, instruction_set: InstructionSet) -> Self {
         Self {
             opcode,
             mnemonic,
             len,
-            address_mode
+            address_mode,
+            instruction_set
         }
     }
 }
 
+/// Finds the opcode to use for the mnemonic and address mode on the given CPU.
+///
+/// The parser cannot always tell which address mode was meant (a label used as a branch target
+/// looks like an absolute address), so if the exact address mode does not exist for the mnemonic
+/// the closest alternative is tried before giving up
+pub fn find_opcode(mnemonic: Mnemonic, address_mode: AddressModeGeneric, cpu: Cpu) -> Result<&'static OpCode, ForgeError> {
+    let alternatives: &[AddressModeGeneric] = match address_mode {
+        AddressModeGeneric::ZeroPage => &[
+            AddressModeGeneric::Absolute,
+            AddressModeGeneric::Relative,
+            AddressModeGeneric::RelativeLong,
+            AddressModeGeneric::AbsoluteLong,
+        ],
+        AddressModeGeneric::ZeroPageX => &[AddressModeGeneric::AbsoluteX],
+        AddressModeGeneric::ZeroPageY => &[AddressModeGeneric::AbsoluteY],
+        AddressModeGeneric::Absolute => &[
+            AddressModeGeneric::Relative,
+            AddressModeGeneric::RelativeLong,
+            AddressModeGeneric::AbsoluteLong,
+        ],
+        AddressModeGeneric::Implied => &[AddressModeGeneric::Accumulator],
+        AddressModeGeneric::IndexedIndirectX => &[AddressModeGeneric::AbsoluteIndexedIndirect],
+        AddressModeGeneric::ZeroPageIndirect => &[AddressModeGeneric::Indirect],
+        _ => &[],
+    };
+
+    let mut unsupported = None;
+    for mode in std::iter::once(&address_mode).chain(alternatives.iter()) {
+        if let Some(opcode) = OPCODES_TO_BYTES.get(&(mnemonic, *mode)) {
+            if cpu.supports(opcode.instruction_set) {
+                return Ok(opcode);
+            }
+
+            unsupported.get_or_insert(*mode);
+        }
+    }
+
+    match unsupported {
+        Some(address_mode) => Err(ForgeError::UnsupportedInstruction { mnemonic, address_mode, cpu }),
+        None => Err(ForgeError::InvalidAddressMode { mnemonic, address_mode }),
+    }
+}
+
 lazy_static! {
     pub static ref OPCODES_TO_BYTES: HashMap<(Mnemonic, AddressModeGeneric), OpCode> = {
         let mut m = HashMap::new();
@@ -171,6 +496,7 @@ lazy_static! {
         m.insert((Mnemonic::ADC, AddressModeGeneric::AbsoluteY), OpCode::new(0x79, Mnemonic::ADC, 3, AddressModeGeneric::AbsoluteY));
         m.insert((Mnemonic::ADC, AddressModeGeneric::IndexedIndirectX), OpCode::new(0x61, Mnemonic::ADC, 2, AddressModeGeneric::IndexedIndirectX));
         m.insert((Mnemonic::ADC, AddressModeGeneric::IndirectIndexY), OpCode::new(0x71, Mnemonic::ADC, 2, AddressModeGeneric::IndirectIndexY));
+        m.insert((Mnemonic::ADC, AddressModeGeneric::ZeroPageIndirect), OpCode::with_set(0x72, Mnemonic::ADC, 2, AddressModeGeneric::ZeroPageIndirect, InstructionSet::Cmos));
 
         // AND
         m.insert((Mnemonic::AND, AddressModeGeneric::Immediate), OpCode::new(0x29, Mnemonic::AND, 2, AddressModeGeneric::Immediate));
@@ -181,6 +507,7 @@ lazy_static! {
         m.insert((Mnemonic::AND, AddressModeGeneric::AbsoluteY), OpCode::new(0x39, Mnemonic::AND, 3, AddressModeGeneric::AbsoluteY));
         m.insert((Mnemonic::AND, AddressModeGeneric::IndexedIndirectX), OpCode::new(0x21, Mnemonic::AND, 2, AddressModeGeneric::IndexedIndirectX));
         m.insert((Mnemonic::AND, AddressModeGeneric::IndirectIndexY), OpCode::new(0x31, Mnemonic::AND, 2, AddressModeGeneric::IndirectIndexY));
+        m.insert((Mnemonic::AND, AddressModeGeneric::ZeroPageIndirect), OpCode::with_set(0x32, Mnemonic::AND, 2, AddressModeGeneric::ZeroPageIndirect, InstructionSet::Cmos));
 
         // ASL
         m.insert((Mnemonic::ASL, AddressModeGeneric::Accumulator), OpCode::new(0x0A, Mnemonic::ASL, 1, AddressModeGeneric::Accumulator));
@@ -189,6 +516,26 @@ lazy_static! {
         m.insert((Mnemonic::ASL, AddressModeGeneric::Absolute), OpCode::new(0x0E, Mnemonic::ASL, 3, AddressModeGeneric::Absolute));
         m.insert((Mnemonic::ASL, AddressModeGeneric::AbsoluteX), OpCode::new(0x1E, Mnemonic::ASL, 3, AddressModeGeneric::AbsoluteX));
 
+        // BBR
+        m.insert((Mnemonic::BBR0, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0x0F, Mnemonic::BBR0, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+        m.insert((Mnemonic::BBR1, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0x1F, Mnemonic::BBR1, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+        m.insert((Mnemonic::BBR2, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0x2F, Mnemonic::BBR2, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+        m.insert((Mnemonic::BBR3, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0x3F, Mnemonic::BBR3, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+        m.insert((Mnemonic::BBR4, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0x4F, Mnemonic::BBR4, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+        m.insert((Mnemonic::BBR5, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0x5F, Mnemonic::BBR5, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+        m.insert((Mnemonic::BBR6, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0x6F, Mnemonic::BBR6, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+        m.insert((Mnemonic::BBR7, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0x7F, Mnemonic::BBR7, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+
+        // BBS
+        m.insert((Mnemonic::BBS0, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0x8F, Mnemonic::BBS0, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+        m.insert((Mnemonic::BBS1, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0x9F, Mnemonic::BBS1, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+        m.insert((Mnemonic::BBS2, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0xAF, Mnemonic::BBS2, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+        m.insert((Mnemonic::BBS3, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0xBF, Mnemonic::BBS3, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+        m.insert((Mnemonic::BBS4, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0xCF, Mnemonic::BBS4, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+        m.insert((Mnemonic::BBS5, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0xDF, Mnemonic::BBS5, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+        m.insert((Mnemonic::BBS6, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0xEF, Mnemonic::BBS6, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+        m.insert((Mnemonic::BBS7, AddressModeGeneric::ZeroPageRelative), OpCode::with_set(0xFF, Mnemonic::BBS7, 3, AddressModeGeneric::ZeroPageRelative, InstructionSet::Rockwell));
+
         // BCC
         m.insert((Mnemonic::BCC, AddressModeGeneric::Relative), OpCode::new(0x90, Mnemonic::BCC, 2, AddressModeGeneric::Relative));
 
@@ -201,6 +548,9 @@ lazy_static! {
         // BIT
         m.insert((Mnemonic::BIT, AddressModeGeneric::ZeroPage), OpCode::new(0x24, Mnemonic::BIT, 2, AddressModeGeneric::ZeroPage));
         m.insert((Mnemonic::BIT, AddressModeGeneric::Absolute), OpCode::new(0x2C, Mnemonic::BIT, 3, AddressModeGeneric::Absolute));
+        m.insert((Mnemonic::BIT, AddressModeGeneric::Immediate), OpCode::with_set(0x89, Mnemonic::BIT, 2, AddressModeGeneric::Immediate, InstructionSet::Cmos));
+        m.insert((Mnemonic::BIT, AddressModeGeneric::ZeroPageX), OpCode::with_set(0x34, Mnemonic::BIT, 2, AddressModeGeneric::ZeroPageX, InstructionSet::Cmos));
+        m.insert((Mnemonic::BIT, AddressModeGeneric::AbsoluteX), OpCode::with_set(0x3C, Mnemonic::BIT, 3, AddressModeGeneric::AbsoluteX, InstructionSet::Cmos));
 
         // BMI
         m.insert((Mnemonic::BMI, AddressModeGeneric::Relative), OpCode::new(0x30, Mnemonic::BMI, 2, AddressModeGeneric::Relative));
@@ -211,6 +561,9 @@ lazy_static! {
         // BPL
         m.insert((Mnemonic::BPL, AddressModeGeneric::Relative), OpCode::new(0x10, Mnemonic::BPL, 2, AddressModeGeneric::Relative));
 
+        // BRA
+        m.insert((Mnemonic::BRA, AddressModeGeneric::Relative), OpCode::with_set(0x80, Mnemonic::BRA, 2, AddressModeGeneric::Relative, InstructionSet::Cmos));
+
         // BRK
         m.insert((Mnemonic::BRK, AddressModeGeneric::Implied), OpCode::new(0x00, Mnemonic::BRK, 1, AddressModeGeneric::Implied));
 
@@ -241,6 +594,7 @@ lazy_static! {
         m.insert((Mnemonic::CMP, AddressModeGeneric::AbsoluteY), OpCode::new(0xD9, Mnemonic::CMP, 3, AddressModeGeneric::AbsoluteY));
         m.insert((Mnemonic::CMP, AddressModeGeneric::IndexedIndirectX), OpCode::new(0xC1, Mnemonic::CMP, 2, AddressModeGeneric::IndexedIndirectX));
         m.insert((Mnemonic::CMP, AddressModeGeneric::IndirectIndexY), OpCode::new(0xD1, Mnemonic::CMP, 2, AddressModeGeneric::IndirectIndexY));
+        m.insert((Mnemonic::CMP, AddressModeGeneric::ZeroPageIndirect), OpCode::with_set(0xD2, Mnemonic::CMP, 2, AddressModeGeneric::ZeroPageIndirect, InstructionSet::Cmos));
 
         // CPX
         m.insert((Mnemonic::CPX, AddressModeGeneric::Immediate), OpCode::new(0xE0, Mnemonic::CPX, 2, AddressModeGeneric::Immediate));
@@ -257,6 +611,7 @@ lazy_static! {
         m.insert((Mnemonic::DEC, AddressModeGeneric::ZeroPageX), OpCode::new(0xD6, Mnemonic::DEC, 2, AddressModeGeneric::ZeroPageX));
         m.insert((Mnemonic::DEC, AddressModeGeneric::Absolute), OpCode::new(0xCE, Mnemonic::DEC, 3, AddressModeGeneric::Absolute));
         m.insert((Mnemonic::DEC, AddressModeGeneric::AbsoluteX), OpCode::new(0xDE, Mnemonic::DEC, 3, AddressModeGeneric::AbsoluteX));
+        m.insert((Mnemonic::DEC, AddressModeGeneric::Accumulator), OpCode::with_set(0x3A, Mnemonic::DEC, 1, AddressModeGeneric::Accumulator, InstructionSet::Cmos));
 
         // DEX
         m.insert((Mnemonic::DEX, AddressModeGeneric::Implied), OpCode::new(0xCA, Mnemonic::DEX, 1, AddressModeGeneric::Implied));
@@ -265,20 +620,22 @@ lazy_static! {
         m.insert((Mnemonic::DEY, AddressModeGeneric::Implied), OpCode::new(0x88, Mnemonic::DEY, 1, AddressModeGeneric::Implied));
 
         // EOR
-        m.insert((Mnemonic::EQR, AddressModeGeneric::Immediate), OpCode::new(0x49, Mnemonic::EQR, 2, AddressModeGeneric::Immediate));
-        m.insert((Mnemonic::EQR, AddressModeGeneric::ZeroPage), OpCode::new(0x45, Mnemonic::EQR, 2, AddressModeGeneric::ZeroPage));
-        m.insert((Mnemonic::EQR, AddressModeGeneric::ZeroPageX), OpCode::new(0x55, Mnemonic::EQR, 2, AddressModeGeneric::ZeroPageX));
-        m.insert((Mnemonic::EQR, AddressModeGeneric::Absolute), OpCode::new(0x4D, Mnemonic::EQR, 3, AddressModeGeneric::Absolute));
-        m.insert((Mnemonic::EQR, AddressModeGeneric::AbsoluteX), OpCode::new(0x5D, Mnemonic::EQR, 3, AddressModeGeneric::AbsoluteX));
-        m.insert((Mnemonic::EQR, AddressModeGeneric::AbsoluteY), OpCode::new(0x59, Mnemonic::EQR, 3, AddressModeGeneric::AbsoluteY));
-        m.insert((Mnemonic::EQR, AddressModeGeneric::IndexedIndirectX), OpCode::new(0x41, Mnemonic::EQR, 2, AddressModeGeneric::IndexedIndirectX));
-        m.insert((Mnemonic::EQR, AddressModeGeneric::IndirectIndexY), OpCode::new(0x51, Mnemonic::EQR, 2, AddressModeGeneric::IndirectIndexY));
+        m.insert((Mnemonic::EOR, AddressModeGeneric::Immediate), OpCode::new(0x49, Mnemonic::EOR, 2, AddressModeGeneric::Immediate));
+        m.insert((Mnemonic::EOR, AddressModeGeneric::ZeroPage), OpCode::new(0x45, Mnemonic::EOR, 2, AddressModeGeneric::ZeroPage));
+        m.insert((Mnemonic::EOR, AddressModeGeneric::ZeroPageX), OpCode::new(0x55, Mnemonic::EOR, 2, AddressModeGeneric::ZeroPageX));
+        m.insert((Mnemonic::EOR, AddressModeGeneric::Absolute), OpCode::new(0x4D, Mnemonic::EOR, 3, AddressModeGeneric::Absolute));
+        m.insert((Mnemonic::EOR, AddressModeGeneric::AbsoluteX), OpCode::new(0x5D, Mnemonic::EOR, 3, AddressModeGeneric::AbsoluteX));
+        m.insert((Mnemonic::EOR, AddressModeGeneric::AbsoluteY), OpCode::new(0x59, Mnemonic::EOR, 3, AddressModeGeneric::AbsoluteY));
+        m.insert((Mnemonic::EOR, AddressModeGeneric::IndexedIndirectX), OpCode::new(0x41, Mnemonic::EOR, 2, AddressModeGeneric::IndexedIndirectX));
+        m.insert((Mnemonic::EOR, AddressModeGeneric::IndirectIndexY), OpCode::new(0x51, Mnemonic::EOR, 2, AddressModeGeneric::IndirectIndexY));
+        m.insert((Mnemonic::EOR, AddressModeGeneric::ZeroPageIndirect), OpCode::with_set(0x52, Mnemonic::EOR, 2, AddressModeGeneric::ZeroPageIndirect, InstructionSet::Cmos));
 
         // INC
         m.insert((Mnemonic::INC, AddressModeGeneric::ZeroPage), OpCode::new(0xE6, Mnemonic::INC, 2, AddressModeGeneric::ZeroPage));
         m.insert((Mnemonic::INC, AddressModeGeneric::ZeroPageX), OpCode::new(0xF6, Mnemonic::INC, 2, AddressModeGeneric::ZeroPageX));
         m.insert((Mnemonic::INC, AddressModeGeneric::Absolute), OpCode::new(0xEE, Mnemonic::INC, 3, AddressModeGeneric::Absolute));
         m.insert((Mnemonic::INC, AddressModeGeneric::AbsoluteX), OpCode::new(0xFE, Mnemonic::INC, 3, AddressModeGeneric::AbsoluteX));
+        m.insert((Mnemonic::INC, AddressModeGeneric::Accumulator), OpCode::with_set(0x1A, Mnemonic::INC, 1, AddressModeGeneric::Accumulator, InstructionSet::Cmos));
 
         // INX
         m.insert((Mnemonic::INX, AddressModeGeneric::Implied), OpCode::new(0xE8, Mnemonic::INX, 1, AddressModeGeneric::Implied));
@@ -289,6 +646,7 @@ lazy_static! {
         // JMP
         m.insert((Mnemonic::JMP, AddressModeGeneric::Absolute), OpCode::new(0x4C, Mnemonic::JMP, 3, AddressModeGeneric::Absolute));
         m.insert((Mnemonic::JMP, AddressModeGeneric::Indirect), OpCode::new(0x6C, Mnemonic::JMP, 3, AddressModeGeneric::Indirect));
+        m.insert((Mnemonic::JMP, AddressModeGeneric::AbsoluteIndexedIndirect), OpCode::with_set(0x7C, Mnemonic::JMP, 3, AddressModeGeneric::AbsoluteIndexedIndirect, InstructionSet::Cmos));
 
         // JSR
         m.insert((Mnemonic::JSR, AddressModeGeneric::Absolute), OpCode::new(0x20, Mnemonic::JSR, 3, AddressModeGeneric::Absolute));
@@ -302,6 +660,7 @@ lazy_static! {
         m.insert((Mnemonic::LDA, AddressModeGeneric::AbsoluteY), OpCode::new(0xB9, Mnemonic::LDA, 3, AddressModeGeneric::AbsoluteY));
         m.insert((Mnemonic::LDA, AddressModeGeneric::IndexedIndirectX), OpCode::new(0xA1, Mnemonic::LDA, 2, AddressModeGeneric::IndexedIndirectX));
         m.insert((Mnemonic::LDA, AddressModeGeneric::IndirectIndexY), OpCode::new(0xB1, Mnemonic::LDA, 2, AddressModeGeneric::IndirectIndexY));
+        m.insert((Mnemonic::LDA, AddressModeGeneric::ZeroPageIndirect), OpCode::with_set(0xB2, Mnemonic::LDA, 2, AddressModeGeneric::ZeroPageIndirect, InstructionSet::Cmos));
 
         // LDX
         m.insert((Mnemonic::LDX, AddressModeGeneric::Immediate), OpCode::new(0xA2, Mnemonic::LDX, 2, AddressModeGeneric::Immediate));
@@ -336,6 +695,7 @@ lazy_static! {
         m.insert((Mnemonic::ORA, AddressModeGeneric::AbsoluteY), OpCode::new(0x19, Mnemonic::ORA, 3, AddressModeGeneric::AbsoluteY));
         m.insert((Mnemonic::ORA, AddressModeGeneric::IndexedIndirectX), OpCode::new(0x01, Mnemonic::ORA, 2, AddressModeGeneric::IndexedIndirectX));
         m.insert((Mnemonic::ORA, AddressModeGeneric::IndirectIndexY), OpCode::new(0x11, Mnemonic::ORA, 2, AddressModeGeneric::IndirectIndexY));
+        m.insert((Mnemonic::ORA, AddressModeGeneric::ZeroPageIndirect), OpCode::with_set(0x12, Mnemonic::ORA, 2, AddressModeGeneric::ZeroPageIndirect, InstructionSet::Cmos));
 
         // PHA
         m.insert((Mnemonic::PHA, AddressModeGeneric::Implied), OpCode::new(0x48, Mnemonic::PHA, 1, AddressModeGeneric::Implied));
@@ -343,12 +703,34 @@ lazy_static! {
         // PHP
         m.insert((Mnemonic::PHP, AddressModeGeneric::Implied), OpCode::new(0x08, Mnemonic::PHP, 1, AddressModeGeneric::Implied));
 
+        // PHX
+        m.insert((Mnemonic::PHX, AddressModeGeneric::Implied), OpCode::with_set(0xDA, Mnemonic::PHX, 1, AddressModeGeneric::Implied, InstructionSet::Cmos));
+
+        // PHY
+        m.insert((Mnemonic::PHY, AddressModeGeneric::Implied), OpCode::with_set(0x5A, Mnemonic::PHY, 1, AddressModeGeneric::Implied, InstructionSet::Cmos));
+
         // PLA
         m.insert((Mnemonic::PLA, AddressModeGeneric::Implied), OpCode::new(0x68, Mnemonic::PLA, 1, AddressModeGeneric::Implied));
 
         // PLP
         m.insert((Mnemonic::PLP, AddressModeGeneric::Implied), OpCode::new(0x28, Mnemonic::PLP, 1, AddressModeGeneric::Implied));
 
+        // PLX
+        m.insert((Mnemonic::PLX, AddressModeGeneric::Implied), OpCode::with_set(0xFA, Mnemonic::PLX, 1, AddressModeGeneric::Implied, InstructionSet::Cmos));
+
+        // PLY
+        m.insert((Mnemonic::PLY, AddressModeGeneric::Implied), OpCode::with_set(0x7A, Mnemonic::PLY, 1, AddressModeGeneric::Implied, InstructionSet::Cmos));
+
+        // RMB
+        m.insert((Mnemonic::RMB0, AddressModeGeneric::ZeroPage), OpCode::with_set(0x07, Mnemonic::RMB0, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+        m.insert((Mnemonic::RMB1, AddressModeGeneric::ZeroPage), OpCode::with_set(0x17, Mnemonic::RMB1, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+        m.insert((Mnemonic::RMB2, AddressModeGeneric::ZeroPage), OpCode::with_set(0x27, Mnemonic::RMB2, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+        m.insert((Mnemonic::RMB3, AddressModeGeneric::ZeroPage), OpCode::with_set(0x37, Mnemonic::RMB3, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+        m.insert((Mnemonic::RMB4, AddressModeGeneric::ZeroPage), OpCode::with_set(0x47, Mnemonic::RMB4, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+        m.insert((Mnemonic::RMB5, AddressModeGeneric::ZeroPage), OpCode::with_set(0x57, Mnemonic::RMB5, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+        m.insert((Mnemonic::RMB6, AddressModeGeneric::ZeroPage), OpCode::with_set(0x67, Mnemonic::RMB6, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+        m.insert((Mnemonic::RMB7, AddressModeGeneric::ZeroPage), OpCode::with_set(0x77, Mnemonic::RMB7, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+
         // ROL
         m.insert((Mnemonic::ROL, AddressModeGeneric::Accumulator), OpCode::new(0x2A, Mnemonic::ROL, 1, AddressModeGeneric::Accumulator));
         m.insert((Mnemonic::ROL, AddressModeGeneric::ZeroPage), OpCode::new(0x26, Mnemonic::ROL, 2, AddressModeGeneric::ZeroPage));
@@ -378,6 +760,7 @@ lazy_static! {
         m.insert((Mnemonic::SBC, AddressModeGeneric::AbsoluteY), OpCode::new(0xF9, Mnemonic::SBC, 3, AddressModeGeneric::AbsoluteY));
         m.insert((Mnemonic::SBC, AddressModeGeneric::IndexedIndirectX), OpCode::new(0xE1, Mnemonic::SBC, 2, AddressModeGeneric::IndexedIndirectX));
         m.insert((Mnemonic::SBC, AddressModeGeneric::IndirectIndexY), OpCode::new(0xF1, Mnemonic::SBC, 2, AddressModeGeneric::IndirectIndexY));
+        m.insert((Mnemonic::SBC, AddressModeGeneric::ZeroPageIndirect), OpCode::with_set(0xF2, Mnemonic::SBC, 2, AddressModeGeneric::ZeroPageIndirect, InstructionSet::Cmos));
 
         // SEC
         m.insert((Mnemonic::SEC, AddressModeGeneric::Implied), OpCode::new(0x38, Mnemonic::SEC, 1, AddressModeGeneric::Implied));
@@ -388,6 +771,16 @@ lazy_static! {
         // SEI
         m.insert((Mnemonic::SEI, AddressModeGeneric::Implied), OpCode::new(0x78, Mnemonic::SEI, 1, AddressModeGeneric::Implied));
 
+        // SMB
+        m.insert((Mnemonic::SMB0, AddressModeGeneric::ZeroPage), OpCode::with_set(0x87, Mnemonic::SMB0, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+        m.insert((Mnemonic::SMB1, AddressModeGeneric::ZeroPage), OpCode::with_set(0x97, Mnemonic::SMB1, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+        m.insert((Mnemonic::SMB2, AddressModeGeneric::ZeroPage), OpCode::with_set(0xA7, Mnemonic::SMB2, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+        m.insert((Mnemonic::SMB3, AddressModeGeneric::ZeroPage), OpCode::with_set(0xB7, Mnemonic::SMB3, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+        m.insert((Mnemonic::SMB4, AddressModeGeneric::ZeroPage), OpCode::with_set(0xC7, Mnemonic::SMB4, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+        m.insert((Mnemonic::SMB5, AddressModeGeneric::ZeroPage), OpCode::with_set(0xD7, Mnemonic::SMB5, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+        m.insert((Mnemonic::SMB6, AddressModeGeneric::ZeroPage), OpCode::with_set(0xE7, Mnemonic::SMB6, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+        m.insert((Mnemonic::SMB7, AddressModeGeneric::ZeroPage), OpCode::with_set(0xF7, Mnemonic::SMB7, 2, AddressModeGeneric::ZeroPage, InstructionSet::Rockwell));
+
         // STA
         m.insert((Mnemonic::STA, AddressModeGeneric::ZeroPage), OpCode::new(0x85, Mnemonic::STA, 2, AddressModeGeneric::ZeroPage));
         m.insert((Mnemonic::STA, AddressModeGeneric::ZeroPageX), OpCode::new(0x95, Mnemonic::STA, 2, AddressModeGeneric::ZeroPageX));
@@ -396,6 +789,10 @@ lazy_static! {
         m.insert((Mnemonic::STA, AddressModeGeneric::AbsoluteY), OpCode::new(0x99, Mnemonic::STA, 3, AddressModeGeneric::AbsoluteY));
         m.insert((Mnemonic::STA, AddressModeGeneric::IndexedIndirectX), OpCode::new(0x81, Mnemonic::STA, 2, AddressModeGeneric::IndexedIndirectX));
         m.insert((Mnemonic::STA, AddressModeGeneric::IndirectIndexY), OpCode::new(0x91, Mnemonic::STA, 2, AddressModeGeneric::IndirectIndexY));
+        m.insert((Mnemonic::STA, AddressModeGeneric::ZeroPageIndirect), OpCode::with_set(0x92, Mnemonic::STA, 2, AddressModeGeneric::ZeroPageIndirect, InstructionSet::Cmos));
+
+        // STP
+        m.insert((Mnemonic::STP, AddressModeGeneric::Implied), OpCode::with_set(0xDB, Mnemonic::STP, 1, AddressModeGeneric::Implied, InstructionSet::Cmos));
 
         // STX
         m.insert((Mnemonic::STX, AddressModeGeneric::ZeroPage), OpCode::new(0x86, Mnemonic::STX, 2, AddressModeGeneric::ZeroPage));
@@ -407,12 +804,26 @@ lazy_static! {
         m.insert((Mnemonic::STY, AddressModeGeneric::ZeroPageX), OpCode::new(0x94, Mnemonic::STY, 2, AddressModeGeneric::ZeroPageX));
         m.insert((Mnemonic::STY, AddressModeGeneric::Absolute), OpCode::new(0x8C, Mnemonic::STY, 3, AddressModeGeneric::Absolute));
 
+        // STZ
+        m.insert((Mnemonic::STZ, AddressModeGeneric::ZeroPage), OpCode::with_set(0x64, Mnemonic::STZ, 2, AddressModeGeneric::ZeroPage, InstructionSet::Cmos));
+        m.insert((Mnemonic::STZ, AddressModeGeneric::ZeroPageX), OpCode::with_set(0x74, Mnemonic::STZ, 2, AddressModeGeneric::ZeroPageX, InstructionSet::Cmos));
+        m.insert((Mnemonic::STZ, AddressModeGeneric::Absolute), OpCode::with_set(0x9C, Mnemonic::STZ, 3, AddressModeGeneric::Absolute, InstructionSet::Cmos));
+        m.insert((Mnemonic::STZ, AddressModeGeneric::AbsoluteX), OpCode::with_set(0x9E, Mnemonic::STZ, 3, AddressModeGeneric::AbsoluteX, InstructionSet::Cmos));
+
         // TAX
         m.insert((Mnemonic::TAX, AddressModeGeneric::Implied), OpCode::new(0xAA, Mnemonic::TAX, 1, AddressModeGeneric::Implied));
 
         // TAY
         m.insert((Mnemonic::TAY, AddressModeGeneric::Implied), OpCode::new(0xA8, Mnemonic::TAY, 1, AddressModeGeneric::Implied));
 
+        // TRB
+        m.insert((Mnemonic::TRB, AddressModeGeneric::ZeroPage), OpCode::with_set(0x14, Mnemonic::TRB, 2, AddressModeGeneric::ZeroPage, InstructionSet::Cmos));
+        m.insert((Mnemonic::TRB, AddressModeGeneric::Absolute), OpCode::with_set(0x1C, Mnemonic::TRB, 3, AddressModeGeneric::Absolute, InstructionSet::Cmos));
+
+        // TSB
+        m.insert((Mnemonic::TSB, AddressModeGeneric::ZeroPage), OpCode::with_set(0x04, Mnemonic::TSB, 2, AddressModeGeneric::ZeroPage, InstructionSet::Cmos));
+        m.insert((Mnemonic::TSB, AddressModeGeneric::Absolute), OpCode::with_set(0x0C, Mnemonic::TSB, 3, AddressModeGeneric::Absolute, InstructionSet::Cmos));
+
         // TSX
         m.insert((Mnemonic::TSX, AddressModeGeneric::Implied), OpCode::new(0xBA, Mnemonic::TSX, 1, AddressModeGeneric::Implied));
 
@@ -425,6 +836,260 @@ lazy_static! {
         // TYA
         m.insert((Mnemonic::TYA, AddressModeGeneric::Implied), OpCode::new(0x98, Mnemonic::TYA, 1, AddressModeGeneric::Implied));
 
+        // WAI
+        m.insert((Mnemonic::WAI, AddressModeGeneric::Implied), OpCode::with_set(0xCB, Mnemonic::WAI, 1, AddressModeGeneric::Implied, InstructionSet::Cmos));
+
+        // Undocumented NMOS instructions. Only available with --cpu 6502x or .feature illegal
+
+        // ALR
+        m.insert((Mnemonic::ALR, AddressModeGeneric::Immediate), OpCode::with_set(0x4B, Mnemonic::ALR, 2, AddressModeGeneric::Immediate, InstructionSet::Undocumented));
+
+        // ANC
+        m.insert((Mnemonic::ANC, AddressModeGeneric::Immediate), OpCode::with_set(0x0B, Mnemonic::ANC, 2, AddressModeGeneric::Immediate, InstructionSet::Undocumented));
+
+        // ANE
+        m.insert((Mnemonic::ANE, AddressModeGeneric::Immediate), OpCode::with_set(0x8B, Mnemonic::ANE, 2, AddressModeGeneric::Immediate, InstructionSet::Undocumented));
+
+        // ARR
+        m.insert((Mnemonic::ARR, AddressModeGeneric::Immediate), OpCode::with_set(0x6B, Mnemonic::ARR, 2, AddressModeGeneric::Immediate, InstructionSet::Undocumented));
+
+        // DCP
+        m.insert((Mnemonic::DCP, AddressModeGeneric::ZeroPage), OpCode::with_set(0xC7, Mnemonic::DCP, 2, AddressModeGeneric::ZeroPage, InstructionSet::Undocumented));
+        m.insert((Mnemonic::DCP, AddressModeGeneric::ZeroPageX), OpCode::with_set(0xD7, Mnemonic::DCP, 2, AddressModeGeneric::ZeroPageX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::DCP, AddressModeGeneric::Absolute), OpCode::with_set(0xCF, Mnemonic::DCP, 3, AddressModeGeneric::Absolute, InstructionSet::Undocumented));
+        m.insert((Mnemonic::DCP, AddressModeGeneric::AbsoluteX), OpCode::with_set(0xDF, Mnemonic::DCP, 3, AddressModeGeneric::AbsoluteX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::DCP, AddressModeGeneric::AbsoluteY), OpCode::with_set(0xDB, Mnemonic::DCP, 3, AddressModeGeneric::AbsoluteY, InstructionSet::Undocumented));
+        m.insert((Mnemonic::DCP, AddressModeGeneric::IndexedIndirectX), OpCode::with_set(0xC3, Mnemonic::DCP, 2, AddressModeGeneric::IndexedIndirectX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::DCP, AddressModeGeneric::IndirectIndexY), OpCode::with_set(0xD3, Mnemonic::DCP, 2, AddressModeGeneric::IndirectIndexY, InstructionSet::Undocumented));
+
+        // ISC
+        m.insert((Mnemonic::ISC, AddressModeGeneric::ZeroPage), OpCode::with_set(0xE7, Mnemonic::ISC, 2, AddressModeGeneric::ZeroPage, InstructionSet::Undocumented));
+        m.insert((Mnemonic::ISC, AddressModeGeneric::ZeroPageX), OpCode::with_set(0xF7, Mnemonic::ISC, 2, AddressModeGeneric::ZeroPageX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::ISC, AddressModeGeneric::Absolute), OpCode::with_set(0xEF, Mnemonic::ISC, 3, AddressModeGeneric::Absolute, InstructionSet::Undocumented));
+        m.insert((Mnemonic::ISC, AddressModeGeneric::AbsoluteX), OpCode::with_set(0xFF, Mnemonic::ISC, 3, AddressModeGeneric::AbsoluteX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::ISC, AddressModeGeneric::AbsoluteY), OpCode::with_set(0xFB, Mnemonic::ISC, 3, AddressModeGeneric::AbsoluteY, InstructionSet::Undocumented));
+        m.insert((Mnemonic::ISC, AddressModeGeneric::IndexedIndirectX), OpCode::with_set(0xE3, Mnemonic::ISC, 2, AddressModeGeneric::IndexedIndirectX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::ISC, AddressModeGeneric::IndirectIndexY), OpCode::with_set(0xF3, Mnemonic::ISC, 2, AddressModeGeneric::IndirectIndexY, InstructionSet::Undocumented));
+
+        // JAM
+        m.insert((Mnemonic::JAM, AddressModeGeneric::Implied), OpCode::with_set(0x02, Mnemonic::JAM, 1, AddressModeGeneric::Implied, InstructionSet::Undocumented));
+
+        // LAS
+        m.insert((Mnemonic::LAS, AddressModeGeneric::AbsoluteY), OpCode::with_set(0xBB, Mnemonic::LAS, 3, AddressModeGeneric::AbsoluteY, InstructionSet::Undocumented));
+
+        // LAX
+        m.insert((Mnemonic::LAX, AddressModeGeneric::Immediate), OpCode::with_set(0xAB, Mnemonic::LAX, 2, AddressModeGeneric::Immediate, InstructionSet::Undocumented));
+        m.insert((Mnemonic::LAX, AddressModeGeneric::ZeroPage), OpCode::with_set(0xA7, Mnemonic::LAX, 2, AddressModeGeneric::ZeroPage, InstructionSet::Undocumented));
+        m.insert((Mnemonic::LAX, AddressModeGeneric::ZeroPageY), OpCode::with_set(0xB7, Mnemonic::LAX, 2, AddressModeGeneric::ZeroPageY, InstructionSet::Undocumented));
+        m.insert((Mnemonic::LAX, AddressModeGeneric::Absolute), OpCode::with_set(0xAF, Mnemonic::LAX, 3, AddressModeGeneric::Absolute, InstructionSet::Undocumented));
+        m.insert((Mnemonic::LAX, AddressModeGeneric::AbsoluteY), OpCode::with_set(0xBF, Mnemonic::LAX, 3, AddressModeGeneric::AbsoluteY, InstructionSet::Undocumented));
+        m.insert((Mnemonic::LAX, AddressModeGeneric::IndexedIndirectX), OpCode::with_set(0xA3, Mnemonic::LAX, 2, AddressModeGeneric::IndexedIndirectX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::LAX, AddressModeGeneric::IndirectIndexY), OpCode::with_set(0xB3, Mnemonic::LAX, 2, AddressModeGeneric::IndirectIndexY, InstructionSet::Undocumented));
+
+        // NOP (the undocumented operand forms)
+        m.insert((Mnemonic::NOP, AddressModeGeneric::Immediate), OpCode::with_set(0x80, Mnemonic::NOP, 2, AddressModeGeneric::Immediate, InstructionSet::Undocumented));
+        m.insert((Mnemonic::NOP, AddressModeGeneric::ZeroPage), OpCode::with_set(0x04, Mnemonic::NOP, 2, AddressModeGeneric::ZeroPage, InstructionSet::Undocumented));
+        m.insert((Mnemonic::NOP, AddressModeGeneric::ZeroPageX), OpCode::with_set(0x14, Mnemonic::NOP, 2, AddressModeGeneric::ZeroPageX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::NOP, AddressModeGeneric::Absolute), OpCode::with_set(0x0C, Mnemonic::NOP, 3, AddressModeGeneric::Absolute, InstructionSet::Undocumented));
+        m.insert((Mnemonic::NOP, AddressModeGeneric::AbsoluteX), OpCode::with_set(0x1C, Mnemonic::NOP, 3, AddressModeGeneric::AbsoluteX, InstructionSet::Undocumented));
+
+        // RLA
+        m.insert((Mnemonic::RLA, AddressModeGeneric::ZeroPage), OpCode::with_set(0x27, Mnemonic::RLA, 2, AddressModeGeneric::ZeroPage, InstructionSet::Undocumented));
+        m.insert((Mnemonic::RLA, AddressModeGeneric::ZeroPageX), OpCode::with_set(0x37, Mnemonic::RLA, 2, AddressModeGeneric::ZeroPageX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::RLA, AddressModeGeneric::Absolute), OpCode::with_set(0x2F, Mnemonic::RLA, 3, AddressModeGeneric::Absolute, InstructionSet::Undocumented));
+        m.insert((Mnemonic::RLA, AddressModeGeneric::AbsoluteX), OpCode::with_set(0x3F, Mnemonic::RLA, 3, AddressModeGeneric::AbsoluteX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::RLA, AddressModeGeneric::AbsoluteY), OpCode::with_set(0x3B, Mnemonic::RLA, 3, AddressModeGeneric::AbsoluteY, InstructionSet::Undocumented));
+        m.insert((Mnemonic::RLA, AddressModeGeneric::IndexedIndirectX), OpCode::with_set(0x23, Mnemonic::RLA, 2, AddressModeGeneric::IndexedIndirectX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::RLA, AddressModeGeneric::IndirectIndexY), OpCode::with_set(0x33, Mnemonic::RLA, 2, AddressModeGeneric::IndirectIndexY, InstructionSet::Undocumented));
+
+        // RRA
+        m.insert((Mnemonic::RRA, AddressModeGeneric::ZeroPage), OpCode::with_set(0x67, Mnemonic::RRA, 2, AddressModeGeneric::ZeroPage, InstructionSet::Undocumented));
+        m.insert((Mnemonic::RRA, AddressModeGeneric::ZeroPageX), OpCode::with_set(0x77, Mnemonic::RRA, 2, AddressModeGeneric::ZeroPageX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::RRA, AddressModeGeneric::Absolute), OpCode::with_set(0x6F, Mnemonic::RRA, 3, AddressModeGeneric::Absolute, InstructionSet::Undocumented));
+        m.insert((Mnemonic::RRA, AddressModeGeneric::AbsoluteX), OpCode::with_set(0x7F, Mnemonic::RRA, 3, AddressModeGeneric::AbsoluteX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::RRA, AddressModeGeneric::AbsoluteY), OpCode::with_set(0x7B, Mnemonic::RRA, 3, AddressModeGeneric::AbsoluteY, InstructionSet::Undocumented));
+        m.insert((Mnemonic::RRA, AddressModeGeneric::IndexedIndirectX), OpCode::with_set(0x63, Mnemonic::RRA, 2, AddressModeGeneric::IndexedIndirectX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::RRA, AddressModeGeneric::IndirectIndexY), OpCode::with_set(0x73, Mnemonic::RRA, 2, AddressModeGeneric::IndirectIndexY, InstructionSet::Undocumented));
+
+        // SAX
+        m.insert((Mnemonic::SAX, AddressModeGeneric::ZeroPage), OpCode::with_set(0x87, Mnemonic::SAX, 2, AddressModeGeneric::ZeroPage, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SAX, AddressModeGeneric::ZeroPageY), OpCode::with_set(0x97, Mnemonic::SAX, 2, AddressModeGeneric::ZeroPageY, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SAX, AddressModeGeneric::Absolute), OpCode::with_set(0x8F, Mnemonic::SAX, 3, AddressModeGeneric::Absolute, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SAX, AddressModeGeneric::IndexedIndirectX), OpCode::with_set(0x83, Mnemonic::SAX, 2, AddressModeGeneric::IndexedIndirectX, InstructionSet::Undocumented));
+
+        // SBX
+        m.insert((Mnemonic::SBX, AddressModeGeneric::Immediate), OpCode::with_set(0xCB, Mnemonic::SBX, 2, AddressModeGeneric::Immediate, InstructionSet::Undocumented));
+
+        // SHA
+        m.insert((Mnemonic::SHA, AddressModeGeneric::AbsoluteY), OpCode::with_set(0x9F, Mnemonic::SHA, 3, AddressModeGeneric::AbsoluteY, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SHA, AddressModeGeneric::IndirectIndexY), OpCode::with_set(0x93, Mnemonic::SHA, 2, AddressModeGeneric::IndirectIndexY, InstructionSet::Undocumented));
+
+        // SHX
+        m.insert((Mnemonic::SHX, AddressModeGeneric::AbsoluteY), OpCode::with_set(0x9E, Mnemonic::SHX, 3, AddressModeGeneric::AbsoluteY, InstructionSet::Undocumented));
+
+        // SHY
+        m.insert((Mnemonic::SHY, AddressModeGeneric::AbsoluteX), OpCode::with_set(0x9C, Mnemonic::SHY, 3, AddressModeGeneric::AbsoluteX, InstructionSet::Undocumented));
+
+        // SLO
+        m.insert((Mnemonic::SLO, AddressModeGeneric::ZeroPage), OpCode::with_set(0x07, Mnemonic::SLO, 2, AddressModeGeneric::ZeroPage, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SLO, AddressModeGeneric::ZeroPageX), OpCode::with_set(0x17, Mnemonic::SLO, 2, AddressModeGeneric::ZeroPageX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SLO, AddressModeGeneric::Absolute), OpCode::with_set(0x0F, Mnemonic::SLO, 3, AddressModeGeneric::Absolute, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SLO, AddressModeGeneric::AbsoluteX), OpCode::with_set(0x1F, Mnemonic::SLO, 3, AddressModeGeneric::AbsoluteX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SLO, AddressModeGeneric::AbsoluteY), OpCode::with_set(0x1B, Mnemonic::SLO, 3, AddressModeGeneric::AbsoluteY, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SLO, AddressModeGeneric::IndexedIndirectX), OpCode::with_set(0x03, Mnemonic::SLO, 2, AddressModeGeneric::IndexedIndirectX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SLO, AddressModeGeneric::IndirectIndexY), OpCode::with_set(0x13, Mnemonic::SLO, 2, AddressModeGeneric::IndirectIndexY, InstructionSet::Undocumented));
+
+        // SRE
+        m.insert((Mnemonic::SRE, AddressModeGeneric::ZeroPage), OpCode::with_set(0x47, Mnemonic::SRE, 2, AddressModeGeneric::ZeroPage, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SRE, AddressModeGeneric::ZeroPageX), OpCode::with_set(0x57, Mnemonic::SRE, 2, AddressModeGeneric::ZeroPageX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SRE, AddressModeGeneric::Absolute), OpCode::with_set(0x4F, Mnemonic::SRE, 3, AddressModeGeneric::Absolute, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SRE, AddressModeGeneric::AbsoluteX), OpCode::with_set(0x5F, Mnemonic::SRE, 3, AddressModeGeneric::AbsoluteX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SRE, AddressModeGeneric::AbsoluteY), OpCode::with_set(0x5B, Mnemonic::SRE, 3, AddressModeGeneric::AbsoluteY, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SRE, AddressModeGeneric::IndexedIndirectX), OpCode::with_set(0x43, Mnemonic::SRE, 2, AddressModeGeneric::IndexedIndirectX, InstructionSet::Undocumented));
+        m.insert((Mnemonic::SRE, AddressModeGeneric::IndirectIndexY), OpCode::with_set(0x53, Mnemonic::SRE, 2, AddressModeGeneric::IndirectIndexY, InstructionSet::Undocumented));
+
+        // TAS
+        m.insert((Mnemonic::TAS, AddressModeGeneric::AbsoluteY), OpCode::with_set(0x9B, Mnemonic::TAS, 3, AddressModeGeneric::AbsoluteY, InstructionSet::Undocumented));
+
+        // 65816 instructions and address modes. Only available with --cpu 65816 or .cpu "65816"
+
+        // The long and stack relative modes of the accumulator instructions
+        m.insert((Mnemonic::ADC, AddressModeGeneric::StackRelative), OpCode::with_set(0x63, Mnemonic::ADC, 2, AddressModeGeneric::StackRelative, InstructionSet::W65816));
+        m.insert((Mnemonic::ADC, AddressModeGeneric::ZeroPageIndirectLong), OpCode::with_set(0x67, Mnemonic::ADC, 2, AddressModeGeneric::ZeroPageIndirectLong, InstructionSet::W65816));
+        m.insert((Mnemonic::ADC, AddressModeGeneric::AbsoluteLong), OpCode::with_set(0x6F, Mnemonic::ADC, 4, AddressModeGeneric::AbsoluteLong, InstructionSet::W65816));
+        m.insert((Mnemonic::ADC, AddressModeGeneric::StackRelativeIndirectY), OpCode::with_set(0x73, Mnemonic::ADC, 2, AddressModeGeneric::StackRelativeIndirectY, InstructionSet::W65816));
+        m.insert((Mnemonic::ADC, AddressModeGeneric::ZeroPageIndirectLongY), OpCode::with_set(0x77, Mnemonic::ADC, 2, AddressModeGeneric::ZeroPageIndirectLongY, InstructionSet::W65816));
+        m.insert((Mnemonic::ADC, AddressModeGeneric::AbsoluteLongX), OpCode::with_set(0x7F, Mnemonic::ADC, 4, AddressModeGeneric::AbsoluteLongX, InstructionSet::W65816));
+        m.insert((Mnemonic::AND, AddressModeGeneric::StackRelative), OpCode::with_set(0x23, Mnemonic::AND, 2, AddressModeGeneric::StackRelative, InstructionSet::W65816));
+        m.insert((Mnemonic::AND, AddressModeGeneric::ZeroPageIndirectLong), OpCode::with_set(0x27, Mnemonic::AND, 2, AddressModeGeneric::ZeroPageIndirectLong, InstructionSet::W65816));
+        m.insert((Mnemonic::AND, AddressModeGeneric::AbsoluteLong), OpCode::with_set(0x2F, Mnemonic::AND, 4, AddressModeGeneric::AbsoluteLong, InstructionSet::W65816));
+        m.insert((Mnemonic::AND, AddressModeGeneric::StackRelativeIndirectY), OpCode::with_set(0x33, Mnemonic::AND, 2, AddressModeGeneric::StackRelativeIndirectY, InstructionSet::W65816));
+        m.insert((Mnemonic::AND, AddressModeGeneric::ZeroPageIndirectLongY), OpCode::with_set(0x37, Mnemonic::AND, 2, AddressModeGeneric::ZeroPageIndirectLongY, InstructionSet::W65816));
+        m.insert((Mnemonic::AND, AddressModeGeneric::AbsoluteLongX), OpCode::with_set(0x3F, Mnemonic::AND, 4, AddressModeGeneric::AbsoluteLongX, InstructionSet::W65816));
+        m.insert((Mnemonic::CMP, AddressModeGeneric::StackRelative), OpCode::with_set(0xC3, Mnemonic::CMP, 2, AddressModeGeneric::StackRelative, InstructionSet::W65816));
+        m.insert((Mnemonic::CMP, AddressModeGeneric::ZeroPageIndirectLong), OpCode::with_set(0xC7, Mnemonic::CMP, 2, AddressModeGeneric::ZeroPageIndirectLong, InstructionSet::W65816));
+        m.insert((Mnemonic::CMP, AddressModeGeneric::AbsoluteLong), OpCode::with_set(0xCF, Mnemonic::CMP, 4, AddressModeGeneric::AbsoluteLong, InstructionSet::W65816));
+        m.insert((Mnemonic::CMP, AddressModeGeneric::StackRelativeIndirectY), OpCode::with_set(0xD3, Mnemonic::CMP, 2, AddressModeGeneric::StackRelativeIndirectY, InstructionSet::W65816));
+        m.insert((Mnemonic::CMP, AddressModeGeneric::ZeroPageIndirectLongY), OpCode::with_set(0xD7, Mnemonic::CMP, 2, AddressModeGeneric::ZeroPageIndirectLongY, InstructionSet::W65816));
+        m.insert((Mnemonic::CMP, AddressModeGeneric::AbsoluteLongX), OpCode::with_set(0xDF, Mnemonic::CMP, 4, AddressModeGeneric::AbsoluteLongX, InstructionSet::W65816));
+        m.insert((Mnemonic::EOR, AddressModeGeneric::StackRelative), OpCode::with_set(0x43, Mnemonic::EOR, 2, AddressModeGeneric::StackRelative, InstructionSet::W65816));
+        m.insert((Mnemonic::EOR, AddressModeGeneric::ZeroPageIndirectLong), OpCode::with_set(0x47, Mnemonic::EOR, 2, AddressModeGeneric::ZeroPageIndirectLong, InstructionSet::W65816));
+        m.insert((Mnemonic::EOR, AddressModeGeneric::AbsoluteLong), OpCode::with_set(0x4F, Mnemonic::EOR, 4, AddressModeGeneric::AbsoluteLong, InstructionSet::W65816));
+        m.insert((Mnemonic::EOR, AddressModeGeneric::StackRelativeIndirectY), OpCode::with_set(0x53, Mnemonic::EOR, 2, AddressModeGeneric::StackRelativeIndirectY, InstructionSet::W65816));
+        m.insert((Mnemonic::EOR, AddressModeGeneric::ZeroPageIndirectLongY), OpCode::with_set(0x57, Mnemonic::EOR, 2, AddressModeGeneric::ZeroPageIndirectLongY, InstructionSet::W65816));
+        m.insert((Mnemonic::EOR, AddressModeGeneric::AbsoluteLongX), OpCode::with_set(0x5F, Mnemonic::EOR, 4, AddressModeGeneric::AbsoluteLongX, InstructionSet::W65816));
+        m.insert((Mnemonic::LDA, AddressModeGeneric::StackRelative), OpCode::with_set(0xA3, Mnemonic::LDA, 2, AddressModeGeneric::StackRelative, InstructionSet::W65816));
+        m.insert((Mnemonic::LDA, AddressModeGeneric::ZeroPageIndirectLong), OpCode::with_set(0xA7, Mnemonic::LDA, 2, AddressModeGeneric::ZeroPageIndirectLong, InstructionSet::W65816));
+        m.insert((Mnemonic::LDA, AddressModeGeneric::AbsoluteLong), OpCode::with_set(0xAF, Mnemonic::LDA, 4, AddressModeGeneric::AbsoluteLong, InstructionSet::W65816));
+        m.insert((Mnemonic::LDA, AddressModeGeneric::StackRelativeIndirectY), OpCode::with_set(0xB3, Mnemonic::LDA, 2, AddressModeGeneric::StackRelativeIndirectY, InstructionSet::W65816));
+        m.insert((Mnemonic::LDA, AddressModeGeneric::ZeroPageIndirectLongY), OpCode::with_set(0xB7, Mnemonic::LDA, 2, AddressModeGeneric::ZeroPageIndirectLongY, InstructionSet::W65816));
+        m.insert((Mnemonic::LDA, AddressModeGeneric::AbsoluteLongX), OpCode::with_set(0xBF, Mnemonic::LDA, 4, AddressModeGeneric::AbsoluteLongX, InstructionSet::W65816));
+        m.insert((Mnemonic::ORA, AddressModeGeneric::StackRelative), OpCode::with_set(0x03, Mnemonic::ORA, 2, AddressModeGeneric::StackRelative, InstructionSet::W65816));
+        m.insert((Mnemonic::ORA, AddressModeGeneric::ZeroPageIndirectLong), OpCode::with_set(0x07, Mnemonic::ORA, 2, AddressModeGeneric::ZeroPageIndirectLong, InstructionSet::W65816));
+        m.insert((Mnemonic::ORA, AddressModeGeneric::AbsoluteLong), OpCode::with_set(0x0F, Mnemonic::ORA, 4, AddressModeGeneric::AbsoluteLong, InstructionSet::W65816));
+        m.insert((Mnemonic::ORA, AddressModeGeneric::StackRelativeIndirectY), OpCode::with_set(0x13, Mnemonic::ORA, 2, AddressModeGeneric::StackRelativeIndirectY, InstructionSet::W65816));
+        m.insert((Mnemonic::ORA, AddressModeGeneric::ZeroPageIndirectLongY), OpCode::with_set(0x17, Mnemonic::ORA, 2, AddressModeGeneric::ZeroPageIndirectLongY, InstructionSet::W65816));
+        m.insert((Mnemonic::ORA, AddressModeGeneric::AbsoluteLongX), OpCode::with_set(0x1F, Mnemonic::ORA, 4, AddressModeGeneric::AbsoluteLongX, InstructionSet::W65816));
+        m.insert((Mnemonic::SBC, AddressModeGeneric::StackRelative), OpCode::with_set(0xE3, Mnemonic::SBC, 2, AddressModeGeneric::StackRelative, InstructionSet::W65816));
+        m.insert((Mnemonic::SBC, AddressModeGeneric::ZeroPageIndirectLong), OpCode::with_set(0xE7, Mnemonic::SBC, 2, AddressModeGeneric::ZeroPageIndirectLong, InstructionSet::W65816));
+        m.insert((Mnemonic::SBC, AddressModeGeneric::AbsoluteLong), OpCode::with_set(0xEF, Mnemonic::SBC, 4, AddressModeGeneric::AbsoluteLong, InstructionSet::W65816));
+        m.insert((Mnemonic::SBC, AddressModeGeneric::StackRelativeIndirectY), OpCode::with_set(0xF3, Mnemonic::SBC, 2, AddressModeGeneric::StackRelativeIndirectY, InstructionSet::W65816));
+        m.insert((Mnemonic::SBC, AddressModeGeneric::ZeroPageIndirectLongY), OpCode::with_set(0xF7, Mnemonic::SBC, 2, AddressModeGeneric::ZeroPageIndirectLongY, InstructionSet::W65816));
+        m.insert((Mnemonic::SBC, AddressModeGeneric::AbsoluteLongX), OpCode::with_set(0xFF, Mnemonic::SBC, 4, AddressModeGeneric::AbsoluteLongX, InstructionSet::W65816));
+        m.insert((Mnemonic::STA, AddressModeGeneric::StackRelative), OpCode::with_set(0x83, Mnemonic::STA, 2, AddressModeGeneric::StackRelative, InstructionSet::W65816));
+        m.insert((Mnemonic::STA, AddressModeGeneric::ZeroPageIndirectLong), OpCode::with_set(0x87, Mnemonic::STA, 2, AddressModeGeneric::ZeroPageIndirectLong, InstructionSet::W65816));
+        m.insert((Mnemonic::STA, AddressModeGeneric::AbsoluteLong), OpCode::with_set(0x8F, Mnemonic::STA, 4, AddressModeGeneric::AbsoluteLong, InstructionSet::W65816));
+        m.insert((Mnemonic::STA, AddressModeGeneric::StackRelativeIndirectY), OpCode::with_set(0x93, Mnemonic::STA, 2, AddressModeGeneric::StackRelativeIndirectY, InstructionSet::W65816));
+        m.insert((Mnemonic::STA, AddressModeGeneric::ZeroPageIndirectLongY), OpCode::with_set(0x97, Mnemonic::STA, 2, AddressModeGeneric::ZeroPageIndirectLongY, InstructionSet::W65816));
+        m.insert((Mnemonic::STA, AddressModeGeneric::AbsoluteLongX), OpCode::with_set(0x9F, Mnemonic::STA, 4, AddressModeGeneric::AbsoluteLongX, InstructionSet::W65816));
+
+        // BRL
+        m.insert((Mnemonic::BRL, AddressModeGeneric::RelativeLong), OpCode::with_set(0x82, Mnemonic::BRL, 3, AddressModeGeneric::RelativeLong, InstructionSet::W65816));
+
+        // COP
+        m.insert((Mnemonic::COP, AddressModeGeneric::Immediate), OpCode::with_set(0x02, Mnemonic::COP, 2, AddressModeGeneric::Immediate, InstructionSet::W65816));
+
+        // JML
+        m.insert((Mnemonic::JML, AddressModeGeneric::AbsoluteLong), OpCode::with_set(0x5C, Mnemonic::JML, 4, AddressModeGeneric::AbsoluteLong, InstructionSet::W65816));
+        m.insert((Mnemonic::JML, AddressModeGeneric::AbsoluteIndirectLong), OpCode::with_set(0xDC, Mnemonic::JML, 3, AddressModeGeneric::AbsoluteIndirectLong, InstructionSet::W65816));
+
+        // JMP
+        m.insert((Mnemonic::JMP, AddressModeGeneric::AbsoluteLong), OpCode::with_set(0x5C, Mnemonic::JMP, 4, AddressModeGeneric::AbsoluteLong, InstructionSet::W65816));
+        m.insert((Mnemonic::JMP, AddressModeGeneric::AbsoluteIndirectLong), OpCode::with_set(0xDC, Mnemonic::JMP, 3, AddressModeGeneric::AbsoluteIndirectLong, InstructionSet::W65816));
+
+        // JSL
+        m.insert((Mnemonic::JSL, AddressModeGeneric::AbsoluteLong), OpCode::with_set(0x22, Mnemonic::JSL, 4, AddressModeGeneric::AbsoluteLong, InstructionSet::W65816));
+
+        // JSR
+        m.insert((Mnemonic::JSR, AddressModeGeneric::AbsoluteLong), OpCode::with_set(0x22, Mnemonic::JSR, 4, AddressModeGeneric::AbsoluteLong, InstructionSet::W65816));
+        m.insert((Mnemonic::JSR, AddressModeGeneric::AbsoluteIndexedIndirect), OpCode::with_set(0xFC, Mnemonic::JSR, 3, AddressModeGeneric::AbsoluteIndexedIndirect, InstructionSet::W65816));
+
+        // MVN
+        m.insert((Mnemonic::MVN, AddressModeGeneric::BlockMove), OpCode::with_set(0x54, Mnemonic::MVN, 3, AddressModeGeneric::BlockMove, InstructionSet::W65816));
+
+        // MVP
+        m.insert((Mnemonic::MVP, AddressModeGeneric::BlockMove), OpCode::with_set(0x44, Mnemonic::MVP, 3, AddressModeGeneric::BlockMove, InstructionSet::W65816));
+
+        // PEA
+        m.insert((Mnemonic::PEA, AddressModeGeneric::Absolute), OpCode::with_set(0xF4, Mnemonic::PEA, 3, AddressModeGeneric::Absolute, InstructionSet::W65816));
+
+        // PEI
+        m.insert((Mnemonic::PEI, AddressModeGeneric::ZeroPageIndirect), OpCode::with_set(0xD4, Mnemonic::PEI, 2, AddressModeGeneric::ZeroPageIndirect, InstructionSet::W65816));
+
+        // PER
+        m.insert((Mnemonic::PER, AddressModeGeneric::RelativeLong), OpCode::with_set(0x62, Mnemonic::PER, 3, AddressModeGeneric::RelativeLong, InstructionSet::W65816));
+
+        // PHB
+        m.insert((Mnemonic::PHB, AddressModeGeneric::Implied), OpCode::with_set(0x8B, Mnemonic::PHB, 1, AddressModeGeneric::Implied, InstructionSet::W65816));
+
+        // PHD
+        m.insert((Mnemonic::PHD, AddressModeGeneric::Implied), OpCode::with_set(0x0B, Mnemonic::PHD, 1, AddressModeGeneric::Implied, InstructionSet::W65816));
+
+        // PHK
+        m.insert((Mnemonic::PHK, AddressModeGeneric::Implied), OpCode::with_set(0x4B, Mnemonic::PHK, 1, AddressModeGeneric::Implied, InstructionSet::W65816));
+
+        // PLB
+        m.insert((Mnemonic::PLB, AddressModeGeneric::Implied), OpCode::with_set(0xAB, Mnemonic::PLB, 1, AddressModeGeneric::Implied, InstructionSet::W65816));
+
+        // PLD
+        m.insert((Mnemonic::PLD, AddressModeGeneric::Implied), OpCode::with_set(0x2B, Mnemonic::PLD, 1, AddressModeGeneric::Implied, InstructionSet::W65816));
+
+        // REP
+        m.insert((Mnemonic::REP, AddressModeGeneric::Immediate), OpCode::with_set(0xC2, Mnemonic::REP, 2, AddressModeGeneric::Immediate, InstructionSet::W65816));
+
+        // RTL
+        m.insert((Mnemonic::RTL, AddressModeGeneric::Implied), OpCode::with_set(0x6B, Mnemonic::RTL, 1, AddressModeGeneric::Implied, InstructionSet::W65816));
+
+        // SEP
+        m.insert((Mnemonic::SEP, AddressModeGeneric::Immediate), OpCode::with_set(0xE2, Mnemonic::SEP, 2, AddressModeGeneric::Immediate, InstructionSet::W65816));
+
+        // TCD
+        m.insert((Mnemonic::TCD, AddressModeGeneric::Implied), OpCode::with_set(0x5B, Mnemonic::TCD, 1, AddressModeGeneric::Implied, InstructionSet::W65816));
+
+        // TCS
+        m.insert((Mnemonic::TCS, AddressModeGeneric::Implied), OpCode::with_set(0x1B, Mnemonic::TCS, 1, AddressModeGeneric::Implied, InstructionSet::W65816));
+
+        // TDC
+        m.insert((Mnemonic::TDC, AddressModeGeneric::Implied), OpCode::with_set(0x7B, Mnemonic::TDC, 1, AddressModeGeneric::Implied, InstructionSet::W65816));
+
+        // TSC
+        m.insert((Mnemonic::TSC, AddressModeGeneric::Implied), OpCode::with_set(0x3B, Mnemonic::TSC, 1, AddressModeGeneric::Implied, InstructionSet::W65816));
+
+        // TXY
+        m.insert((Mnemonic::TXY, AddressModeGeneric::Implied), OpCode::with_set(0x9B, Mnemonic::TXY, 1, AddressModeGeneric::Implied, InstructionSet::W65816));
+
+        // TYX
+        m.insert((Mnemonic::TYX, AddressModeGeneric::Implied), OpCode::with_set(0xBB, Mnemonic::TYX, 1, AddressModeGeneric::Implied, InstructionSet::W65816));
+
+        // WDM
+        m.insert((Mnemonic::WDM, AddressModeGeneric::Immediate), OpCode::with_set(0x42, Mnemonic::WDM, 2, AddressModeGeneric::Immediate, InstructionSet::W65816));
+
+        // XBA
+        m.insert((Mnemonic::XBA, AddressModeGeneric::Implied), OpCode::with_set(0xEB, Mnemonic::XBA, 1, AddressModeGeneric::Implied, InstructionSet::W65816));
+
+        // XCE
+        m.insert((Mnemonic::XCE, AddressModeGeneric::Implied), OpCode::with_set(0xFB, Mnemonic::XCE, 1, AddressModeGeneric::Implied, InstructionSet::W65816));
+
         m
     };
 }
\ No newline at end of file
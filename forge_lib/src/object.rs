@@ -1,28 +1,590 @@
-use std::collections::HashMap;
+use std::{collections::BTreeMap, io::{Cursor, ErrorKind}};
 
+use bincode::Options;
 use chrono::{DateTime, Utc};
 use semver::Version;
 use serde_derive::{Serialize, Deserialize};
 
-use crate::{line::Line, label::LabelMetaData};
+use crate::{error::ForgeError, expression::{BinaryOp, UnaryOp}, span::Span};
+
+/// The segment code goes in until another one is chosen
+pub const CODE_SEGMENT: &str = "CODE";
+/// The segment `.rodata` switches to, for read only data
+pub const RODATA_SEGMENT: &str = "RODATA";
+/// The segment `.data` switches to, for initialized data that can be changed
+pub const DATA_SEGMENT: &str = "DATA";
+/// The segment `.bss` switches to, for data that starts uninitialized
+pub const BSS_SEGMENT: &str = "BSS";
+/// The segment `.zeropage` switches to. Its labels are taken to be 1-byte addresses
+pub const ZEROPAGE_SEGMENT: &str = "ZEROPAGE";
+
+/// The bytes every object file starts with
+pub const OBJECT_MAGIC: [u8; 8] = *b"FORGEOBJ";
+
+/// The version of the object format written by the assembler
+pub const OBJECT_VERSION: Version = Version::new(0, 6, 0);
+
+/// The oldest version that can still be read. Files from before 0.3.0 have no binary magic, files
+/// from before 0.4.0 have no imports and files from before 0.5.0 have no comments, but are otherwise
+/// laid out the same way. Files from before 0.6.0 used ZeroPage8 for single bytes as well, which are
+/// read as Byte so values that linked before still do
+pub const LEGACY_OBJECT_VERSION: Version = Version::new(0, 2, 0);
+
+/// The name in the header, which is all files from before the binary magic can be recognised by
+const HEADER_NAME: &str = "rust6502forge";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Header {
     pub magic_number: String,     // Bytes for rust6502forge
-    pub timestamp: DateTime<Utc>, 
+    pub timestamp: DateTime<Utc>,
     pub version: Version,
     pub file_name: String,
 }
 
+impl Header {
+    /// Returns the header for an object file written now in the current format
+    pub fn new(file_name: &str) -> Self {
+        Self {
+            magic_number: String::from(HEADER_NAME),
+            timestamp: Utc::now(),
+            version: OBJECT_VERSION,
+            file_name: file_name.to_string(),
+        }
+    }
+}
+
+/// The assembled code of a file. Addresses are not known until the linker places the segments, so
+/// the bytes are written as if each segment started at 0 and the relocations say what to patch
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Contents {
-    pub label_map: HashMap<String, LabelMetaData>,
-    pub constant_map: HashMap<String, u16>,
-    pub parsed_contents: Vec<Line>
+    pub segments: Vec<Segment>,
+    pub symbols: Vec<Symbol>,
+    /// The symbols from other files that are used, sorted by name
+    pub imports: Vec<Import>,
+    /// The comment written with each symbol in the source, keyed by its name. Carried through to the
+    /// symbol files written for debuggers
+    pub comments: BTreeMap<String, String>,
+}
+
+/// The contents of files from before 0.4.0, which could not import symbols
+#[derive(Debug, Serialize, Deserialize)]
+struct LegacyContents {
+    segments: Vec<Segment>,
+    symbols: Vec<Symbol>,
+}
+
+impl From<LegacyContents> for Contents {
+    fn from(value: LegacyContents) -> Self {
+        Self { segments: value.segments, symbols: value.symbols, imports: Vec::new(), comments: BTreeMap::new() }
+    }
+}
+
+/// The contents of 0.4.x files, which had no comments
+#[derive(Debug, Serialize, Deserialize)]
+struct UncommentedContents {
+    segments: Vec<Segment>,
+    symbols: Vec<Symbol>,
+    imports: Vec<Import>,
+}
+
+impl From<UncommentedContents> for Contents {
+    fn from(value: UncommentedContents) -> Self {
+        Self { segments: value.segments, symbols: value.symbols, imports: value.imports, comments: BTreeMap::new() }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OutFile {
     pub header: Header,
     pub contents: Contents
-}
\ No newline at end of file
+}
+
+impl OutFile {
+    /// Encodes the file as the magic followed by the header and contents
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = OBJECT_MAGIC.to_vec();
+        bytes.extend(bincode::serialize(self).unwrap());
+        bytes
+    }
+
+    /// Decodes an object file, checking it is one and that its version can be read. The name of the
+    /// file is only used for the errors
+    pub fn from_bytes(bytes: &[u8], file: &str) -> Result<Self, ForgeError> {
+        let (bytes, has_magic) = match bytes.strip_prefix(&OBJECT_MAGIC) {
+            Some(bytes) => (bytes, true),
+            None => (bytes, false),
+        };
+
+        // The same encoding as bincode::serialize, but limited to the size of the file so a bad length
+        // is reported instead of being allocated
+        let options = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(bytes.len() as u64);
+        let mut reader = Cursor::new(bytes);
+        // Running past the limit means a length was read that is longer than the rest of the file
+        let decode_error = |error: bincode::Error| match *error {
+            bincode::ErrorKind::SizeLimit => ForgeError::TruncatedObjectFile { file: file.to_string() },
+            bincode::ErrorKind::Io(error) if error.kind() == ErrorKind::UnexpectedEof => {
+                ForgeError::TruncatedObjectFile { file: file.to_string() }
+            }
+            _ => ForgeError::CorruptObjectFile { file: file.to_string() },
+        };
+
+        // The header is read on its own first, so a file in a newer format is reported as such
+        // rather than as corrupt
+        let header: Header = match options.deserialize_from(&mut reader) {
+            Ok(header) => header,
+            Err(_) if !has_magic => return Err(ForgeError::BadMagic { file: file.to_string() }),
+            Err(error) => return Err(decode_error(error)),
+        };
+
+        if header.magic_number != HEADER_NAME {
+            return Err(ForgeError::BadMagic { file: file.to_string() });
+        }
+
+        // Only the patch number can differ from a version that can be read, since anything else may
+        // change the layout
+        let contents = match (has_magic, header.version.major, header.version.minor) {
+            (true, 0, 6) | (true, 0, 5) => options.deserialize_from(&mut reader),
+            (true, 0, 4) => options.deserialize_from::<_, UncommentedContents>(&mut reader).map(Contents::from),
+            (false, 0, 2) | (true, 0, 3) => options.deserialize_from::<_, LegacyContents>(&mut reader).map(Contents::from),
+            _ => return Err(ForgeError::UnsupportedObjectVersion { file: file.to_string(), version: header.version }),
+        };
+        let mut contents: Contents = contents.map_err(decode_error)?;
+        if reader.position() != bytes.len() as u64 {
+            return Err(ForgeError::CorruptObjectFile { file: file.to_string() });
+        }
+
+        // Zero page addresses and other single bytes were one kind before 0.6.0, which allowed
+        // negative values
+        if header.version < Version::new(0, 6, 0) {
+            let relocations = contents.segments.iter_mut().flat_map(|segment| &mut segment.relocations);
+            for relocation in relocations.filter(|relocation| relocation.kind == RelocationKind::ZeroPage8) {
+                relocation.kind = RelocationKind::Byte;
+            }
+        }
+
+        Ok(Self { header, contents })
+    }
+}
+
+/// A named block of bytes that is placed in memory as a whole by the linker
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Segment {
+    pub name: String,
+    pub bytes: Vec<u8>,
+    pub relocations: Vec<Relocation>,
+}
+
+impl Segment {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), ..Default::default() }
+    }
+}
+
+/// A label or constant defined in the file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Symbol {
+    pub name: String,
+    pub value: SymbolValue,
+    pub visibility: Visibility,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SymbolValue {
+    /// A constant, which has the same value wherever the code ends up
+    Constant(u16),
+    /// A label, given as an offset from the start of one of the segments in the file
+    Label { segment: usize, offset: u16 },
+}
+
+/// Whether other files can refer to a symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Visibility {
+    Local,
+    Export,
+    /// Exported as a 1-byte address, from `.exportzp` or a label in the zero page segment
+    ExportZeroPage,
+}
+
+impl Visibility {
+    pub fn is_exported(&self) -> bool {
+        matches!(self, Visibility::Export | Visibility::ExportZeroPage)
+    }
+}
+
+/// A symbol used by the file that another file defines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Import {
+    pub name: String,
+    /// True if it was imported with `.importzp`, so it was assembled as a 1-byte address
+    pub zero_page: bool,
+}
+
+/// Bytes in a segment that can only be filled in once the segments are placed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Relocation {
+    /// The offset of the first byte to patch from the start of the segment
+    pub offset: u16,
+    pub kind: RelocationKind,
+    /// The value to patch in
+    pub expression: ObjectExpression,
+    /// Where the value came from in the source, for reporting values that do not fit
+    pub span: Span,
+}
+
+/// How the value of a relocation is written into the segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RelocationKind {
+    /// A little endian 16-bit address
+    Absolute16,
+    /// A little endian 24-bit address on the 65816
+    Absolute24,
+    /// A zero page address, which has to be between $00 and $FF
+    ZeroPage8,
+    /// The low byte of the value, from `<`
+    LowByte,
+    /// The high byte of the value, from `>`
+    HighByte,
+    /// The signed offset of a branch target from the end of the instruction
+    Relative8,
+    /// The signed 16-bit offset of a 65816 long branch target from the end of the instruction
+    Relative16,
+    /// A single byte value, such as an immediate or from `.byte`, which can also be negative. Kept
+    /// last so the kinds already in object files keep their encoding
+    Byte,
+}
+
+impl RelocationKind {
+    /// The number of bytes patched
+    pub fn size(&self) -> u16 {
+        match self {
+            RelocationKind::ZeroPage8
+            | RelocationKind::Byte
+            | RelocationKind::LowByte
+            | RelocationKind::HighByte
+            | RelocationKind::Relative8 => 1,
+            RelocationKind::Absolute16 | RelocationKind::Relative16 => 2,
+            RelocationKind::Absolute24 => 3,
+        }
+    }
+
+    /// Returns the bytes to write for the value, or an error if it does not fit. The address of the
+    /// first byte is needed for the branches, which store how far away the value is from the end
+    /// of the instruction
+    pub fn encode(&self, value: i32, address: i32) -> Result<Vec<u8>, ForgeError> {
+        let out_of_range = || ForgeError::ValueOutOfRange { value, kind: *self };
+        let bytes = match self {
+            RelocationKind::Absolute16 if (-0x8000..=0xFFFF).contains(&value) => value.to_le_bytes()[..2].to_vec(),
+            RelocationKind::Absolute24 if (-0x80_0000..=0xFF_FFFF).contains(&value) => value.to_le_bytes()[..3].to_vec(),
+            RelocationKind::ZeroPage8 if (0..=0xFF).contains(&value) => vec![value as u8],
+            RelocationKind::Byte if (-0x80..=0xFF).contains(&value) => vec![value as u8],
+            RelocationKind::LowByte => vec![value as u8],
+            RelocationKind::HighByte => vec![(value >> 8) as u8],
+            RelocationKind::Relative8 | RelocationKind::Relative16 => {
+                let offset = value.wrapping_sub(address + self.size() as i32);
+                match self {
+                    RelocationKind::Relative8 if i8::try_from(offset).is_ok() => vec![offset as u8],
+                    RelocationKind::Relative16 if i16::try_from(offset).is_ok() => (offset as i16).to_le_bytes().to_vec(),
+                    _ => return Err(ForgeError::ValueOutOfRange { value: offset, kind: *self }),
+                }
+            }
+            _ => return Err(out_of_range()),
+        };
+
+        Ok(bytes)
+    }
+}
+
+/// A value that depends on where segments are placed. Unlike the expressions in the source, the
+/// names have already been looked up, so only symbols from other files are left as names
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ObjectExpression {
+    Number(i32),
+    /// An offset from the start of one of the segments in the same file
+    SegmentOffset { segment: usize, offset: u16 },
+    /// A symbol defined by another file
+    Symbol(String),
+    Binary(BinaryOp, Box<ObjectExpression>, Box<ObjectExpression>),
+    Unary(UnaryOp, Box<ObjectExpression>),
+}
+
+impl ObjectExpression {
+    /// Returns the names of the symbols from other files the value uses
+    pub fn symbol_names(&self) -> Vec<&str> {
+        match self {
+            ObjectExpression::Number(_) | ObjectExpression::SegmentOffset { .. } => Vec::new(),
+            ObjectExpression::Symbol(name) => vec![name.as_str()],
+            ObjectExpression::Binary(_, left, right) => {
+                let mut names = left.symbol_names();
+                names.extend(right.symbol_names());
+                names
+            }
+            ObjectExpression::Unary(_, inner) => inner.symbol_names(),
+        }
+    }
+
+    /// Works out the value once the start address of each segment and the value of each symbol from
+    /// another file can be looked up. Returns the first name that cannot be found as the error
+    pub fn evaluate(
+        &self,
+        segment_start: &impl Fn(usize) -> i32,
+        symbol: &impl Fn(&str) -> Option<i32>,
+    ) -> Result<i32, String> {
+        let value = match self {
+            ObjectExpression::Number(value) => *value,
+            ObjectExpression::SegmentOffset { segment, offset } => segment_start(*segment) + *offset as i32,
+            ObjectExpression::Symbol(name) => symbol(name).ok_or_else(|| name.clone())?,
+            ObjectExpression::Binary(op, left, right) => {
+                let left = left.evaluate(segment_start, symbol)?;
+                let right = right.evaluate(segment_start, symbol)?;
+                match op {
+                    BinaryOp::Add => left.wrapping_add(right),
+                    BinaryOp::Subtract => left.wrapping_sub(right),
+                    BinaryOp::Multiply => left.wrapping_mul(right),
+                    BinaryOp::Divide => left.checked_div(right).unwrap_or(0),
+                    BinaryOp::And => left & right,
+                    BinaryOp::Or => left | right,
+                    BinaryOp::ShiftLeft => left.wrapping_shl(right as u32),
+                    BinaryOp::ShiftRight => left.wrapping_shr(right as u32),
+                }
+            }
+            ObjectExpression::Unary(op, inner) => op.apply(inner.evaluate(segment_start, symbol)? as u16) as i32,
+        };
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod object_tests {
+    use std::{collections::BTreeMap, env, io, path::Path};
+
+    use semver::Version;
+
+    use crate::{error::{ForgeError, IoError}, get_file_contents, span::Span};
+
+    use super::{
+        Contents, Header, Import, LegacyContents, ObjectExpression, OutFile, Relocation, RelocationKind, Segment, Symbol,
+        SymbolValue, UncommentedContents, Visibility, OBJECT_MAGIC,
+    };
+
+    /// A file with something in every part of the contents
+    fn out_file() -> OutFile {
+        let relocation = Relocation {
+            offset: 1,
+            kind: RelocationKind::Absolute16,
+            expression: ObjectExpression::Symbol(String::from("init")),
+            span: Span::new(0, 2, 3, 4),
+        };
+        let segment = Segment { bytes: vec![0x20, 0, 0, 0x60], relocations: vec![relocation], ..Segment::new("CODE") };
+        let symbol = Symbol {
+            name: String::from("main"),
+            value: SymbolValue::Label { segment: 0, offset: 0 },
+            visibility: Visibility::Export,
+        };
+
+        OutFile {
+            header: Header::new("main.asm"),
+            contents: Contents {
+                segments: vec![segment],
+                symbols: vec![symbol],
+                imports: vec![Import { name: String::from("init"), zero_page: false }],
+                comments: BTreeMap::from([(String::from("main"), String::from("Entry point"))]),
+            },
+        }
+    }
+
+    /// A header for the version, which is all that tells the formats apart
+    fn header(version: Version) -> Header {
+        Header { version, ..Header::new("main.asm") }
+    }
+
+    fn with_magic(bytes: Vec<u8>) -> Vec<u8> {
+        let mut file = OBJECT_MAGIC.to_vec();
+        file.extend(bytes);
+        file
+    }
+
+    fn load(bytes: &[u8]) -> Result<OutFile, ForgeError> {
+        OutFile::from_bytes(bytes, "main.out")
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let file = out_file();
+        let loaded = load(&file.to_bytes()).unwrap();
+
+        assert_eq!(loaded.header.version, file.header.version);
+        assert_eq!(loaded.header.file_name, "main.asm");
+        assert_eq!(loaded.contents.segments, file.contents.segments);
+        assert_eq!(loaded.contents.symbols, file.contents.symbols);
+        assert_eq!(loaded.contents.imports, file.contents.imports);
+        assert_eq!(loaded.contents.comments, file.contents.comments);
+    }
+
+    #[test]
+    fn test_load_older_versions() {
+        let Contents { segments, symbols, imports, .. } = out_file().contents;
+
+        let legacy = LegacyContents { segments: segments.clone(), symbols: symbols.clone() };
+        let loaded = load(&bincode::serialize(&(header(Version::new(0, 2, 0)), &legacy)).unwrap()).unwrap();
+        assert_eq!((loaded.contents.segments, loaded.contents.symbols), (segments.clone(), symbols.clone()));
+        assert!(loaded.contents.imports.is_empty());
+
+        let loaded = load(&with_magic(bincode::serialize(&(header(Version::new(0, 3, 1)), &legacy)).unwrap())).unwrap();
+        assert_eq!(loaded.contents.segments, segments);
+        assert!(loaded.contents.imports.is_empty());
+
+        let uncommented = UncommentedContents { segments: segments.clone(), symbols, imports: imports.clone() };
+        let loaded = load(&with_magic(bincode::serialize(&(header(Version::new(0, 4, 0)), &uncommented)).unwrap())).unwrap();
+        assert_eq!((loaded.contents.segments, loaded.contents.imports), (segments, imports));
+        assert!(loaded.contents.comments.is_empty());
+    }
+
+    #[test]
+    fn test_load_legacy_byte_relocations() {
+        let mut file = out_file();
+        let relocation = Relocation {
+            offset: 3,
+            kind: RelocationKind::ZeroPage8,
+            expression: ObjectExpression::Number(-1),
+            span: Span::new(0, 3, 8, 2),
+        };
+        file.contents.segments[0].relocations = vec![relocation];
+
+        // A 0.5 file with `LDA #-1` links as it did then
+        let bytes = with_magic(bincode::serialize(&(header(Version::new(0, 5, 0)), &file.contents)).unwrap());
+        let relocation = &load(&bytes).unwrap().contents.segments[0].relocations[0];
+        assert_eq!(relocation.kind, RelocationKind::Byte);
+        assert_eq!(relocation.kind.encode(-1, 0), Ok(vec![0xFF]));
+
+        // Files in the current version keep the zero page kind
+        let relocation = &load(&file.to_bytes()).unwrap().contents.segments[0].relocations[0];
+        assert_eq!(relocation.kind, RelocationKind::ZeroPage8);
+    }
+
+    #[test]
+    fn test_load_bad_magic() {
+        let bad_magic = Err(ForgeError::BadMagic { file: String::from("main.out") });
+
+        assert_eq!(load(b"not an object file").map(|_| ()), bad_magic);
+        assert_eq!(load(b"").map(|_| ()), bad_magic);
+
+        let header = Header { magic_number: String::from("something else"), ..Header::new("main.asm") };
+        let bytes = with_magic(bincode::serialize(&(header, &out_file().contents)).unwrap());
+        assert_eq!(load(&bytes).map(|_| ()), bad_magic);
+    }
+
+    #[test]
+    fn test_load_unsupported_version() {
+        let contents = out_file().contents;
+        let unsupported = |version: Version| Err(ForgeError::UnsupportedObjectVersion { file: String::from("main.out"), version });
+
+        let bytes = with_magic(bincode::serialize(&(header(Version::new(0, 7, 0)), &contents)).unwrap());
+        assert_eq!(load(&bytes).map(|_| ()), unsupported(Version::new(0, 7, 0)));
+
+        let bytes = with_magic(bincode::serialize(&(header(Version::new(1, 5, 0)), &contents)).unwrap());
+        assert_eq!(load(&bytes).map(|_| ()), unsupported(Version::new(1, 5, 0)));
+
+        // 0.2 files were written before the magic, so one with it is not a 0.2 file
+        let bytes = with_magic(bincode::serialize(&(header(Version::new(0, 2, 0)), &contents)).unwrap());
+        assert_eq!(load(&bytes).map(|_| ()), unsupported(Version::new(0, 2, 0)));
+    }
+
+    #[test]
+    fn test_load_truncated() {
+        let truncated = Err(ForgeError::TruncatedObjectFile { file: String::from("main.out") });
+        let bytes = out_file().to_bytes();
+
+        assert_eq!(load(&bytes[..bytes.len() - 1]).map(|_| ()), truncated);
+        assert_eq!(load(&bytes[..OBJECT_MAGIC.len() + 4]).map(|_| ()), truncated);
+        assert_eq!(load(&OBJECT_MAGIC).map(|_| ()), truncated);
+
+        // A length far longer than the file is reported rather than allocated
+        let mut bytes = with_magic(bincode::serialize(&Header::new("main.asm")).unwrap());
+        bytes.extend(u64::MAX.to_le_bytes());
+        assert_eq!(load(&bytes).map(|_| ()), truncated);
+    }
+
+    #[test]
+    fn test_load_corrupt() {
+        let corrupt = Err(ForgeError::CorruptObjectFile { file: String::from("main.out") });
+
+        let mut bytes = out_file().to_bytes();
+        bytes.push(0);
+        assert_eq!(load(&bytes).map(|_| ()), corrupt);
+
+        // The file name in the header is no longer valid UTF-8
+        let mut bytes = out_file().to_bytes();
+        let name = bytes.windows(8).position(|window| window == b"main.asm").unwrap();
+        bytes[name] = 0xFF;
+        assert_eq!(load(&bytes).map(|_| ()), corrupt);
+    }
+
+    #[test]
+    fn test_load_unreadable() {
+        let missing = Path::new("no/such/main.out");
+        assert_eq!(get_file_contents(missing).map(|_| ()), Err(ForgeError::NoSuchFileOrDir { file: String::from("no/such/main.out") }));
+
+        // A directory opens but cannot be read, which is not the same as it being missing
+        let directory = env::temp_dir();
+        let error = get_file_contents(&directory).map(|_| ()).unwrap_err();
+        let file = directory.to_string_lossy().into_owned();
+        assert_eq!(error, ForgeError::ReadFailed { file: file.clone(), error: IoError(io::ErrorKind::IsADirectory.into()) });
+        assert!(error.to_string().starts_with(&format!("Could not read {}: ", file)));
+    }
+
+    fn out_of_range(value: i32, kind: RelocationKind) -> Result<Vec<u8>, ForgeError> {
+        Err(ForgeError::ValueOutOfRange { value, kind })
+    }
+
+    #[test]
+    fn test_encode_absolute() {
+        assert_eq!(RelocationKind::Absolute16.encode(0x1234, 0), Ok(vec![0x34, 0x12]));
+        assert_eq!(RelocationKind::Absolute16.encode(-1, 0), Ok(vec![0xFF, 0xFF]));
+        assert_eq!(RelocationKind::Absolute16.encode(0x10000, 0), out_of_range(0x10000, RelocationKind::Absolute16));
+        assert_eq!(RelocationKind::Absolute24.encode(0x018000, 0), Ok(vec![0x00, 0x80, 0x01]));
+        assert_eq!(RelocationKind::Absolute24.encode(0x1000000, 0), out_of_range(0x1000000, RelocationKind::Absolute24));
+    }
+
+    #[test]
+    fn test_encode_zero_page() {
+        assert_eq!(RelocationKind::ZeroPage8.encode(0, 0), Ok(vec![0x00]));
+        assert_eq!(RelocationKind::ZeroPage8.encode(0xFF, 0), Ok(vec![0xFF]));
+        assert_eq!(RelocationKind::ZeroPage8.encode(-1, 0), out_of_range(-1, RelocationKind::ZeroPage8));
+        assert_eq!(RelocationKind::ZeroPage8.encode(0x100, 0), out_of_range(0x100, RelocationKind::ZeroPage8));
+    }
+
+    #[test]
+    fn test_encode_bytes() {
+        assert_eq!(RelocationKind::Byte.encode(-1, 0), Ok(vec![0xFF]));
+        assert_eq!(RelocationKind::Byte.encode(-0x80, 0), Ok(vec![0x80]));
+        assert_eq!(RelocationKind::Byte.encode(-0x81, 0), out_of_range(-0x81, RelocationKind::Byte));
+        assert_eq!(RelocationKind::Byte.encode(0x100, 0), out_of_range(0x100, RelocationKind::Byte));
+        assert_eq!(RelocationKind::LowByte.encode(0x1234, 0), Ok(vec![0x34]));
+        assert_eq!(RelocationKind::HighByte.encode(0x1234, 0), Ok(vec![0x12]));
+    }
+
+    #[test]
+    fn test_encode_relative() {
+        // The operand of a branch at $8000 is at $8001, and the offset is from the end at $8002
+        assert_eq!(RelocationKind::Relative8.encode(0x8010, 0x8001), Ok(vec![0x0E]));
+        assert_eq!(RelocationKind::Relative8.encode(0x8000, 0x8001), Ok(vec![0xFE]));
+        assert_eq!(RelocationKind::Relative8.encode(0x8081, 0x8001), Ok(vec![0x7F]));
+        assert_eq!(RelocationKind::Relative8.encode(0x8082, 0x8001), out_of_range(0x80, RelocationKind::Relative8));
+        assert_eq!(RelocationKind::Relative16.encode(0x9003, 0x8001), Ok(vec![0x00, 0x10]));
+        assert_eq!(RelocationKind::Relative16.encode(0x18003, 0x8001), out_of_range(0x10000, RelocationKind::Relative16));
+    }
+
+    #[test]
+    fn test_range_error_messages() {
+        let message = |value, kind| ForgeError::ValueOutOfRange { value, kind }.to_string();
+
+        assert_eq!(message(-1, RelocationKind::ZeroPage8), "-$1 is not a zero page address. It has to be between $00 and $FF");
+        assert_eq!(message(0x1234, RelocationKind::Absolute16), "$1234 does not fit in 16 bits");
+        assert_eq!(message(-0x81, RelocationKind::Byte), "-$81 does not fit in 8 bits");
+        assert_eq!(message(200, RelocationKind::Relative8), "Branch target is 200 bytes away, but it has to be between -128 and 127");
+    }
+}
@@ -7,4 +7,7 @@ pub enum Operand {
     Expression(ExpressionNode),
     AddressMode(AddressMode),
     LocalLabel(String),
+    /// A reference to an anonymous label. Negative values count back from the current line (`:-`
+    /// is -1, `:--` is -2) and positive values count forward (`:+` is 1)
+    AnonymousLabel(i8),
 }
\ No newline at end of file
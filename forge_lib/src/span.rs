@@ -0,0 +1,34 @@
+use std::fmt;
+
+use serde_derive::{Deserialize, Serialize};
+
+/// Where a piece of source code came from. Lines and columns start at 1 and the length is in
+/// characters. Spans are ignored when comparing lines, instructions and expressions so the same
+/// code parsed from two places compares equal
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub file_id: u32,
+    pub line: u32,
+    pub column: u32,
+    pub length: u32,
+}
+
+impl Span {
+    pub fn new(file_id: u32, line: u32, column: u32, length: u32) -> Self {
+        Self { file_id, line, column, length }
+    }
+
+    /// Returns a span covering this span through to the end of the other. Both are expected to
+    /// be on the same line
+    pub fn join(self, other: Span) -> Span {
+        let end = (other.column + other.length).max(self.column + self.length);
+
+        Span { length: end - self.column, ..self }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}